@@ -0,0 +1,94 @@
+struct Crosshair {
+    // xy: the center (px) in UI coordinates, zw: the half size (px) of the window
+    position: vec4<f32>,
+    color: vec4<f32>,
+    outline_color: vec4<f32>,
+    // x: the gap, y: the thickness, z: the length of the lines, w: the width of the outline (px)
+    shape: vec4<f32>,
+    // x: the lines, y: the dot, z: the circle (1.0 if drawn), w: the half size (px) of the quad
+    flags: vec4<f32>,
+    // x: the opacity of the hit marker
+    hit_marker: vec4<f32>,
+}
+@group(0)
+@binding(0)
+var<uniform> crosshair: Crosshair;
+
+struct VertexInput {
+    @location(0) position: vec4<f32>,
+    @location(1) normal: vec4<f32>,
+    @location(2) tex_coord: vec2<f32>,
+}
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) local_position: vec2<f32>,
+}
+
+@vertex
+fn vs_main(vertex_input: VertexInput) -> VertexOutput {
+    var result: VertexOutput;
+
+    // NOTE: The square model is from -0.5 to 0.5.
+    let local_position = vertex_input.position.xy * 2.0 * crosshair.flags.w;
+    let position = (crosshair.position.xy + local_position) / crosshair.position.zw;
+    result.position = vec4<f32>(position, 0.0, 1.0);
+    result.local_position = local_position;
+
+    return result;
+}
+
+// A function to get the signed distance from a point to a box.
+fn box_distance(position: vec2<f32>, center: vec2<f32>, half_size: vec2<f32>) -> f32 {
+    let d = abs(position - center) - half_size;
+    return length(max(d, vec2<f32>(0.0))) + min(max(d.x, d.y), 0.0);
+}
+
+@fragment
+fn fs_main(vertex_output: VertexOutput) -> @location(0) vec4<f32> {
+    let gap = crosshair.shape.x;
+    let thickness = crosshair.shape.y;
+    let line_length = crosshair.shape.z;
+    let outline = crosshair.shape.w;
+
+    // get the distance to the nearest shape
+    // NOTE: The lines are symmetric, so only the right and the top lines are checked.
+    let p = abs(vertex_output.local_position);
+    var distance = 1.0e9;
+    if crosshair.flags.x > 0.5 {
+        let center = gap + line_length / 2.0;
+        let half_size = vec2<f32>(line_length / 2.0, thickness / 2.0);
+        distance = min(distance, box_distance(p, vec2<f32>(center, 0.0), half_size));
+        distance = min(distance, box_distance(p.yx, vec2<f32>(center, 0.0), half_size));
+    }
+    if crosshair.flags.y > 0.5 {
+        distance = min(distance, length(p) - thickness);
+    }
+    if crosshair.flags.z > 0.5 {
+        distance = min(distance, abs(length(p) - gap) - thickness / 2.0);
+    }
+
+    // get the distance to the hit marker
+    // NOTE: The hit marker is the lines rotated by 45 degrees.
+    var hit_distance = 1.0e9;
+    if crosshair.hit_marker.x > 0.0 {
+        let q = abs(vec2<f32>(p.x + p.y, p.x - p.y)) * 0.70710678;
+        let center = gap + line_length / 2.0;
+        let half_size = vec2<f32>(line_length / 2.0, thickness / 2.0);
+        hit_distance = min(box_distance(q, vec2<f32>(center, 0.0), half_size), box_distance(q.yx, vec2<f32>(center, 0.0), half_size));
+    }
+
+    // blend the fill, the hit marker and the outline with antialiasing
+    let fill = clamp(0.5 - distance, 0.0, 1.0);
+    let hit_fill = clamp(0.5 - hit_distance, 0.0, 1.0);
+    let outlined = max(
+        clamp(0.5 - (distance - outline), 0.0, 1.0),
+        clamp(0.5 - (hit_distance - outline), 0.0, 1.0) * crosshair.hit_marker.x,
+    ) * crosshair.outline_color.a;
+    let alpha = max(max(fill * crosshair.color.a, hit_fill * crosshair.hit_marker.x), outlined);
+    if alpha <= 0.0 {
+        discard;
+    }
+    let hit_color = mix(crosshair.outline_color.rgb, vec3<f32>(1.0), hit_fill);
+    let color = mix(hit_color, crosshair.color.rgb, fill);
+    return vec4<f32>(color, alpha);
+}
@@ -0,0 +1,31 @@
+@group(0)
+@binding(0)
+var source_texture: texture_2d<f32>;
+
+@group(0)
+@binding(1)
+var source_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coord: vec2<f32>,
+}
+
+// A fullscreen triangle without any vertex buffer.
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var result: VertexOutput;
+
+    let x = f32((vertex_index << 1u) & 2u);
+    let y = f32(vertex_index & 2u);
+    result.position = vec4<f32>(x * 2.0 - 1.0, 1.0 - y * 2.0, 0.0, 1.0);
+    result.tex_coord = vec2<f32>(x, y);
+
+    return result;
+}
+
+// The bilinear sample at the center of a destination texel is the average of 2x2 source texels.
+@fragment
+fn fs_main(vertex_output: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSampleLevel(source_texture, source_sampler, vertex_output.tex_coord, 0.0);
+}
@@ -0,0 +1,71 @@
+struct Camera {
+    projection_matrix: mat4x4<f32>,
+    view_matrix: mat4x4<f32>,
+}
+@group(0)
+@binding(0)
+var<uniform> camera: Camera;
+
+struct Clock {
+    // x: the current time (frames)
+    time: vec4<f32>,
+}
+@group(0)
+@binding(1)
+var<uniform> clock: Clock;
+
+struct VertexInput {
+    @location(0) position: vec4<f32>,
+    @location(1) normal: vec4<f32>,
+    @location(2) tex_coord: vec2<f32>,
+}
+struct ParticleInput {
+    // xyz: the position when spawned, w: the time (frames) when spawned
+    @location(3) spawn: vec4<f32>,
+    // xyz: the velocity (units per frame), w: the lifetime (frames)
+    @location(4) velocity: vec4<f32>,
+    @location(5) start_color: vec4<f32>,
+    @location(6) end_color: vec4<f32>,
+    // x: the start size, y: the end size, z: the gravity (units per frame^2)
+    @location(7) size: vec4<f32>,
+}
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) local_position: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(vertex_input: VertexInput, particle: ParticleInput) -> VertexOutput {
+    var result: VertexOutput;
+
+    // NOTE: The dead particles and the unused slots are moved out of the clip space.
+    let age = clock.time.x - particle.spawn.w;
+    let lifetime = particle.velocity.w;
+    if age < 0.0 || age >= lifetime {
+        result.position = vec4<f32>(0.0, 0.0, 2.0, 1.0);
+        return result;
+    }
+
+    // simulate the particle analytically
+    let t = age / lifetime;
+    let gravity = vec3<f32>(0.0, -particle.size.z, 0.0);
+    let position = particle.spawn.xyz + particle.velocity.xyz * age + 0.5 * gravity * age * age;
+    let size = mix(particle.size.x, particle.size.y, t);
+
+    // billboard the quad in the view space
+    // NOTE: The square model is from -0.5 to 0.5.
+    var view_position = camera.view_matrix * vec4<f32>(position, 1.0);
+    view_position += vec4<f32>(vertex_input.position.xy * size, 0.0, 0.0);
+    result.position = camera.projection_matrix * view_position;
+    result.local_position = vertex_input.position.xy * 2.0;
+    result.color = mix(particle.start_color, particle.end_color, t);
+    return result;
+}
+
+@fragment
+fn fs_main(vertex_output: VertexOutput) -> @location(0) vec4<f32> {
+    // NOTE: The quad is drawn as a soft circle.
+    let falloff = clamp(1.0 - length(vertex_output.local_position), 0.0, 1.0);
+    return vec4<f32>(vertex_output.color.rgb, vertex_output.color.a * falloff * falloff);
+}
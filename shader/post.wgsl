@@ -0,0 +1,130 @@
+struct Parameter {
+    values: vec4<f32>,
+}
+@group(0)
+@binding(0)
+var input_texture: texture_2d<f32>;
+
+@group(0)
+@binding(1)
+var input_sampler: sampler;
+
+@group(0)
+@binding(2)
+var<uniform> parameter: Parameter;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coord: vec2<f32>,
+}
+
+// A fullscreen triangle without any vertex buffer.
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var result: VertexOutput;
+
+    let x = f32((vertex_index << 1u) & 2u);
+    let y = f32(vertex_index & 2u);
+    result.position = vec4<f32>(x * 2.0 - 1.0, 1.0 - y * 2.0, 0.0, 1.0);
+    result.tex_coord = vec2<f32>(x, y);
+
+    return result;
+}
+
+fn sample(tex_coord: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(input_texture, input_sampler, tex_coord, 0.0);
+}
+
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.299, 0.587, 0.114));
+}
+
+// values.x: threshold, values.y: intensity, values.z: radius in texels
+@fragment
+fn fs_bloom(vertex_output: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = parameter.values.z / vec2<f32>(textureDimensions(input_texture));
+    let color = sample(vertex_output.tex_coord);
+
+    var bloom = vec3<f32>(0.0, 0.0, 0.0);
+    var weight_sum = 0.0;
+    for (var i = -3; i <= 3; i++) {
+        for (var j = -3; j <= 3; j++) {
+            let offset = vec2<f32>(f32(i), f32(j));
+            let weight = exp(-dot(offset, offset) / 8.0);
+            let neighbor = sample(vertex_output.tex_coord + offset * texel).rgb;
+            bloom += max(neighbor - vec3<f32>(parameter.values.x), vec3<f32>(0.0)) * weight;
+            weight_sum += weight;
+        }
+    }
+
+    return vec4<f32>(color.rgb + bloom / weight_sum * parameter.values.y, color.a);
+}
+
+// values.x: exposure
+@fragment
+fn fs_tonemap(vertex_output: VertexOutput) -> @location(0) vec4<f32> {
+    let color = sample(vertex_output.tex_coord);
+
+    // ACES filmic curve fitted by Krzysztof Narkowicz
+    let x = color.rgb * parameter.values.x;
+    let mapped = clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), vec3<f32>(0.0), vec3<f32>(1.0));
+
+    return vec4<f32>(mapped, color.a);
+}
+
+// no parameters
+@fragment
+fn fs_fxaa(vertex_output: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(input_texture));
+    let tex_coord = vertex_output.tex_coord;
+
+    let color = sample(tex_coord);
+    let luma_m = luminance(color.rgb);
+    let luma_nw = luminance(sample(tex_coord + vec2<f32>(-1.0, -1.0) * texel).rgb);
+    let luma_ne = luminance(sample(tex_coord + vec2<f32>(1.0, -1.0) * texel).rgb);
+    let luma_sw = luminance(sample(tex_coord + vec2<f32>(-1.0, 1.0) * texel).rgb);
+    let luma_se = luminance(sample(tex_coord + vec2<f32>(1.0, 1.0) * texel).rgb);
+    let luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    let luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    // find the direction of the edge
+    var direction = vec2<f32>(
+        -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+        (luma_nw + luma_sw) - (luma_ne + luma_se),
+    );
+    let direction_reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * (0.25 / 8.0), 1.0 / 128.0);
+    let direction_scale = 1.0 / (min(abs(direction.x), abs(direction.y)) + direction_reduce);
+    direction = clamp(direction * direction_scale, vec2<f32>(-8.0), vec2<f32>(8.0)) * texel;
+
+    // blend along the edge
+    let color_a = 0.5 * (
+        sample(tex_coord + direction * (1.0 / 3.0 - 0.5)).rgb
+        + sample(tex_coord + direction * (2.0 / 3.0 - 0.5)).rgb
+    );
+    let color_b = color_a * 0.5 + 0.25 * (
+        sample(tex_coord - direction * 0.5).rgb
+        + sample(tex_coord + direction * 0.5).rgb
+    );
+    let luma_b = luminance(color_b);
+    let is_outside = luma_b < luma_min || luma_max < luma_b;
+
+    return vec4<f32>(select(color_b, color_a, is_outside), color.a);
+}
+
+// values.x: intensity, values.y: inner radius, values.z: outer radius
+@fragment
+fn fs_vignette(vertex_output: VertexOutput) -> @location(0) vec4<f32> {
+    let color = sample(vertex_output.tex_coord);
+
+    let radius = length(vertex_output.tex_coord - vec2<f32>(0.5, 0.5));
+    let factor = 1.0 - parameter.values.x * smoothstep(parameter.values.y, parameter.values.z, radius);
+
+    return vec4<f32>(color.rgb * factor, color.a);
+}
+
+// values.x: gamma
+@fragment
+fn fs_gamma(vertex_output: VertexOutput) -> @location(0) vec4<f32> {
+    let color = sample(vertex_output.tex_coord);
+    return vec4<f32>(pow(color.rgb, vec3<f32>(1.0 / parameter.values.x)), color.a);
+}
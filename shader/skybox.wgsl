@@ -8,11 +8,11 @@ var<uniform> camera: Camera;
 
 @group(0)
 @binding(1)
-var image_texture: texture_2d<f32>;
+var cube_texture: texture_cube<f32>;
 
 @group(0)
 @binding(2)
-var image_sampler: sampler;
+var cube_sampler: sampler;
 
 struct VertexInput {
     @location(0) position: vec4<f32>,
@@ -21,7 +21,7 @@ struct VertexInput {
 }
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
-    @location(0) tex_coord: vec2<f32>,
+    @location(0) direction: vec3<f32>,
 }
 
 @vertex
@@ -33,12 +33,13 @@ fn vs_main(vertex_input: VertexInput) -> VertexOutput {
         * camera.view_matrix
         * vertex_input.position;
 
-    result.tex_coord = vertex_input.tex_coord;
+    // NOTE: The sphere is centered at the origin, so a position is the direction to sample.
+    result.direction = vertex_input.position.xyz;
 
     return result;
 }
 
 @fragment
 fn fs_main(vertex_outout: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(image_texture, image_sampler, vertex_outout.tex_coord);
+    return textureSample(cube_texture, cube_sampler, vertex_outout.direction);
 }
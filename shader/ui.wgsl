@@ -8,17 +8,18 @@ var<uniform> camera: Camera;
 struct Instance {
     model_matrix: mat4x4<f32>,
     tex_coord: vec4<f32>,
+    color: vec4<f32>,
 }
 @group(0)
 @binding(1)
-var<uniform> instances: array<Instance, 16>;
+var<uniform> instances: array<Instance, 640>;
 
-@group(0)
-@binding(2)
+@group(1)
+@binding(0)
 var image_texture: texture_2d<f32>;
 
-@group(0)
-@binding(3)
+@group(1)
+@binding(1)
 var image_sampler: sampler;
 
 struct VertexInput {
@@ -29,6 +30,7 @@ struct VertexInput {
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) tex_coord: vec2<f32>,
+    @location(1) color: vec4<f32>,
 }
 
 @vertex
@@ -47,11 +49,12 @@ fn vs_main(
         instances[instance_index].tex_coord.x + instances[instance_index].tex_coord.z * vertex_input.tex_coord.x,
         instances[instance_index].tex_coord.y + instances[instance_index].tex_coord.w * vertex_input.tex_coord.y,
     );
+    result.color = instances[instance_index].color;
 
     return result;
 }
 
 @fragment
 fn fs_main(vertex_outout: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(image_texture, image_sampler, vertex_outout.tex_coord);
+    return textureSample(image_texture, image_sampler, vertex_outout.tex_coord) * vertex_outout.color;
 }
@@ -19,7 +19,20 @@ struct Instance {
 }
 @group(0)
 @binding(2)
-var<uniform> instances: array<Instance, 16>;
+var<uniform> instances: array<Instance, 128>;
+
+struct Material {
+    base_color_factor: vec4<f32>,
+}
+@group(1)
+@binding(0)
+var base_color_texture: texture_2d<f32>;
+@group(1)
+@binding(1)
+var base_color_sampler: sampler;
+@group(1)
+@binding(2)
+var<uniform> material: Material;
 
 struct VertexInput {
     @location(0) position: vec4<f32>,
@@ -29,6 +42,7 @@ struct VertexInput {
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) color: vec4<f32>,
+    @location(1) tex_coord: vec2<f32>,
 }
 
 @vertex
@@ -46,10 +60,12 @@ fn vs_main(
 
     result.position = camera.projection_matrix * camera.view_matrix * position;
     result.color = vec4<f32>(c, c, c, 1.0);
+    result.tex_coord = vertex_input.tex_coord;
     return result;
 }
 
 @fragment
 fn fs_main(vertex_outout: VertexOutput) -> @location(0) vec4<f32> {
-    return vertex_outout.color;
+    let base_color = textureSample(base_color_texture, base_color_sampler, vertex_outout.tex_coord);
+    return vertex_outout.color * base_color * material.base_color_factor;
 }
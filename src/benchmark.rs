@@ -0,0 +1,236 @@
+//! A module for benchmark mode.
+//!
+//! The times of every frame are recorded and the statistics are written to a report file
+//! such as the following:
+//!
+//! ```text
+//! # wgpu-simple-fps benchmark
+//! frames = 1000
+//! adapter = NVIDIA GeForce RTX 3060 (vulkan)
+//! resolution = 1920x1080
+//! total = 4.201s
+//!
+//! # ms     avg      p50      p95      p99
+//! frame    4.201    4.102    5.011    6.321
+//! update   0.021    0.020    0.031    0.044
+//! encode   0.310    0.301    0.402    0.520
+//! gpu      3.512    3.498    4.120    4.988
+//!
+//! # ms (the average of the last frames with timestamp queries)
+//! skybox   0.412
+//! world    1.803
+//! particle 0.212
+//! post     0.684
+//! ui       0.101
+//! ```
+//!
+//! NOTE: The GPU time of a frame is read from the timestamp queries some frames later without waiting for the GPU.
+//!       If the timestamp query isn't supported, it's reported as `n/a`.
+
+use std::{fs, time::Duration};
+
+/// The default path of the report file.
+pub const DEFAULT_REPORT_PATH: &str = "benchmark.txt";
+
+/// The count of the first frames that aren't measured.
+///
+/// NOTE: The first frames include the initialization such as the window creation.
+const WARMUP_FRAMES: u32 = 10;
+
+/// A struct for the times spent on a frame.
+pub struct FrameSample {
+    /// The time from the start of the previous frame to the start of this frame.
+    pub frame: Duration,
+    /// The time to update the scene on the CPU.
+    pub update: Duration,
+    /// The time to encode and submit the commands on the CPU.
+    pub encode: Duration,
+}
+
+/// The names of the metrics in the order of `FrameSample::get_metrics()`.
+const METRIC_NAMES: [&str; 3] = ["frame", "update", "encode"];
+
+impl FrameSample {
+    fn get_metrics(&self) -> [Duration; 3] {
+        [self.frame, self.update, self.encode]
+    }
+}
+
+/// A struct for the statistics of a metric in milliseconds.
+struct Statistics {
+    average: f64,
+    p50: f64,
+    p95: f64,
+    p99: f64,
+}
+
+impl Statistics {
+    /// A constructor.
+    ///
+    /// The percentiles are calculated with the nearest-rank method.
+    fn new(mut values: Vec<f64>) -> Self {
+        if values.is_empty() {
+            return Self {
+                average: 0.0,
+                p50: 0.0,
+                p95: 0.0,
+                p99: 0.0,
+            };
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * values.len() as f64).ceil() as usize;
+            values[rank.clamp(1, values.len()) - 1]
+        };
+        Self {
+            average: values.iter().sum::<f64>() / values.len() as f64,
+            p50: percentile(50.0),
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+        }
+    }
+
+    /// A method to format this as a line of the report.
+    fn to_line(&self, name: &str) -> String {
+        format!(
+            "{:<8} {:<8.3} {:<8.3} {:<8.3} {:.3}\n",
+            name, self.average, self.p50, self.p95, self.p99
+        )
+    }
+}
+
+/// A benchmark that collects the times of a fixed count of frames.
+pub struct Benchmark {
+    frames: u32,
+    report_path: String,
+    warmup_frames: u32,
+    samples: Vec<FrameSample>,
+    /// The GPU times of the profiled frames, which are read asynchronously.
+    gpu_samples: Vec<Duration>,
+}
+
+impl Benchmark {
+    /// A constructor.
+    ///
+    /// * `frames` - the count of frames to measure
+    /// * `report_path` - the path of the report file
+    pub fn new(frames: u32, report_path: &str) -> Self {
+        Self {
+            frames,
+            report_path: report_path.to_string(),
+            warmup_frames: WARMUP_FRAMES,
+            samples: Vec::with_capacity(frames as usize),
+            gpu_samples: Vec::with_capacity(frames as usize),
+        }
+    }
+
+    /// A method to record the times of a frame.
+    ///
+    /// WARN: The samples of the warmup frames are discarded.
+    pub fn push(&mut self, sample: FrameSample) {
+        if self.warmup_frames > 0 {
+            self.warmup_frames -= 1;
+            return;
+        }
+        self.samples.push(sample);
+    }
+
+    /// A method to record the GPU times of the frames read from the GPU profiler.
+    ///
+    /// WARN: The times read during the warmup frames are discarded.
+    pub fn push_gpu_times(&mut self, times: Vec<Duration>) {
+        if self.warmup_frames > 0 {
+            return;
+        }
+        self.gpu_samples.extend(times);
+    }
+
+    /// A method to check if all the frames are measured.
+    pub fn is_finished(&self) -> bool {
+        self.samples.len() >= self.frames as usize
+    }
+
+    /// A method to write the report file.
+    ///
+    /// * `adapter` - the description of the adapter
+    /// * `resolution` - the size of the surface
+    /// * `pass_times` - the names and the average GPU times of the render passes if available
+    pub fn save(
+        &self,
+        adapter: &str,
+        resolution: (u32, u32),
+        pass_times: &[(&str, Duration)],
+    ) -> Result<(), String> {
+        let total = self.samples.iter().map(|n| n.frame).sum::<Duration>();
+        let mut text = format!(
+            "# wgpu-simple-fps benchmark\nframes = {}\nadapter = {}\nresolution = {}x{}\ntotal = {:.3}s\n\n",
+            self.samples.len(),
+            adapter,
+            resolution.0,
+            resolution.1,
+            total.as_secs_f64()
+        );
+        text.push_str("# ms     avg      p50      p95      p99\n");
+        let to_milliseconds = |n: &Duration| n.as_secs_f64() * 1000.0;
+        for (i, name) in METRIC_NAMES.iter().enumerate() {
+            let statistics = Statistics::new(
+                self.samples
+                    .iter()
+                    .map(|n| to_milliseconds(&n.get_metrics()[i]))
+                    .collect(),
+            );
+            text.push_str(&statistics.to_line(name));
+        }
+        // NOTE: Without timestamp queries, the GPU time isn't available.
+        if self.gpu_samples.is_empty() {
+            text.push_str("gpu      n/a\n");
+        } else {
+            let statistics =
+                Statistics::new(self.gpu_samples.iter().map(to_milliseconds).collect());
+            text.push_str(&statistics.to_line("gpu"));
+        }
+        if !pass_times.is_empty() {
+            text.push_str("\n# ms (the average of the last frames with timestamp queries)\n");
+            for (name, time) in pass_times {
+                text.push_str(&format!("{:<8} {:.3}\n", name, time.as_secs_f64() * 1000.0));
+            }
+        }
+        fs::write(&self.report_path, &text).map_err(|e| format!("{}: {}", self.report_path, e))?;
+        info!(
+            "Benchmark.save",
+            "the report is written to {}:\n{}", self.report_path, text
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        // the values are sorted before the calculation
+        let statistics = Statistics::new((1..=100).rev().map(|n| n as f64).collect());
+        assert_eq!(statistics.average, 50.5);
+        assert_eq!(statistics.p50, 50.0);
+        assert_eq!(statistics.p95, 95.0);
+        assert_eq!(statistics.p99, 99.0);
+
+        // the rank is rounded up
+        let statistics = Statistics::new(Vec::from([4.0, 1.0, 3.0, 2.0, 5.0]));
+        assert_eq!(statistics.p50, 3.0);
+        assert_eq!(statistics.p95, 5.0);
+        assert_eq!(statistics.p99, 5.0);
+
+        let statistics = Statistics::new(Vec::from([7.0]));
+        assert_eq!(statistics.average, 7.0);
+        assert_eq!(statistics.p50, 7.0);
+        assert_eq!(statistics.p99, 7.0);
+
+        let statistics = Statistics::new(Vec::new());
+        assert_eq!(statistics.average, 0.0);
+        assert_eq!(statistics.p50, 0.0);
+        assert_eq!(statistics.p99, 0.0);
+    }
+}
@@ -0,0 +1,275 @@
+//! A module for the command line interface.
+//!
+//! The command line is parsed before the event loop is created,
+//! so invalid arguments are reported without opening a window.
+
+use crate::{log::LogLevel, settings::Settings, system::renderer::model::mesh::Shape};
+
+/// The text printed by `--help`.
+pub const HELP: &str = "\
+WebGPU Simple FPS
+
+USAGE:
+    wgpu-simple-fps [OPTIONS]
+
+OPTIONS:
+    -h, --help                 Print this help and exit
+        --list-adapters        Print all the adapters and exit
+        --hot-reload           Reload the shaders, the textures and the level when their files change
+        --level PATH           Load the level from PATH instead of the default level
+        --asset-dir PATH       Search the assets such as shader/world.wgsl in PATH first
+        --seed NUMBER          Set the seed of the random target placement
+        --replay PATH          Play the inputs recorded in PATH and exit at the end
+        --record PATH          Record the inputs to PATH
+        --audio-wav PATH       Render the sounds into the WAV file PATH instead of playing them
+        --benchmark FRAMES     Fly the camera along the camera path of the level for FRAMES frames
+                               without frame rate cap, write the frame time report and exit
+        --benchmark-report PATH
+                               Write the benchmark report to PATH instead of benchmark.txt
+        --export-mesh SHAPE    Write the mesh of SHAPE such as torus(1,0.25,32,16) as Wavefront OBJ
+                               and exit
+        --export-mesh-path PATH
+                               Write the mesh to PATH instead of NAME.obj such as torus.obj
+        --log-level LEVEL      debug, info, warn or error
+        --window-mode MODE     windowed, borderless or exclusive
+        --resolution WxH       The window size or the video mode size such as 1920x1080
+        --backend NAME         auto, vulkan, gl, dx12 or metal
+        --KEY VALUE            Override any other setting in settings.txt such as --present-mode immediate
+";
+
+/// A parsed command line.
+#[derive(Default)]
+pub struct CommandLine {
+    pub is_help: bool,
+    pub is_list_adapters: bool,
+    /// Whether the asset files are watched and reloaded when they are modified.
+    pub is_hot_reload: bool,
+    pub level: Option<String>,
+    /// The directory searched for the assets before the others.
+    pub asset_directory: Option<String>,
+    pub seed: Option<u64>,
+    pub replay: Option<String>,
+    pub record: Option<String>,
+    /// The path of the WAV file the sounds are rendered into offline.
+    pub audio_wav: Option<String>,
+    /// The count of frames to run in benchmark mode.
+    pub benchmark: Option<u32>,
+    pub benchmark_report: Option<String>,
+    /// The procedural mesh exported for debugging.
+    pub export_mesh: Option<Shape>,
+    pub export_mesh_path: Option<String>,
+    pub log_level: Option<LogLevel>,
+    /// The pairs of a setting key and a value that override the settings.
+    pub settings: Vec<(String, String)>,
+}
+
+impl CommandLine {
+    /// A static method to parse command line arguments.
+    ///
+    /// * `arguments` - the arguments without the program name
+    ///
+    /// The values of settings are validated by `apply()`.
+    pub fn parse(arguments: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut command_line = Self::default();
+        let mut arguments = arguments.into_iter();
+        while let Some(n) = arguments.next() {
+            // flags
+            match n.as_str() {
+                "-h" | "--help" => {
+                    command_line.is_help = true;
+                    continue;
+                }
+                "--list-adapters" => {
+                    command_line.is_list_adapters = true;
+                    continue;
+                }
+                "--hot-reload" => {
+                    command_line.is_hot_reload = true;
+                    continue;
+                }
+                _ => (),
+            }
+
+            // options with a value
+            let Some(key) = n.strip_prefix("--") else {
+                return Err(format!("unexpected argument '{}'", n));
+            };
+            // NOTE: A following option isn't taken as the value, e.g. `--level --help`.
+            let Some(value) = arguments.next().filter(|n| !n.starts_with("--")) else {
+                return Err(format!("no value is specified for '{}'", n));
+            };
+            match key {
+                "level" => command_line.level = Some(value),
+                "asset-dir" | "asset_dir" => command_line.asset_directory = Some(value),
+                "seed" => {
+                    command_line.seed = Some(
+                        value
+                            .parse::<u64>()
+                            .map_err(|e| format!("invalid value for '{}': {}", n, e))?,
+                    )
+                }
+                "replay" => command_line.replay = Some(value),
+                "record" => command_line.record = Some(value),
+                "audio-wav" | "audio_wav" => command_line.audio_wav = Some(value),
+                "benchmark" => {
+                    let frames = value
+                        .parse::<u32>()
+                        .map_err(|e| format!("invalid value for '{}': {}", n, e))?;
+                    if frames == 0 {
+                        return Err(format!("the value for '{}' must be 1 or more", n));
+                    }
+                    command_line.benchmark = Some(frames);
+                }
+                "benchmark-report" | "benchmark_report" => {
+                    command_line.benchmark_report = Some(value)
+                }
+                "export-mesh" | "export_mesh" => {
+                    command_line.export_mesh = Some(
+                        Shape::parse(&value)
+                            .map_err(|e| format!("invalid value for '{}': {}", n, e))?,
+                    )
+                }
+                "export-mesh-path" | "export_mesh_path" => {
+                    command_line.export_mesh_path = Some(value)
+                }
+                "log-level" | "log_level" => {
+                    command_line.log_level = Some(
+                        LogLevel::parse(&value)
+                            .map_err(|e| format!("invalid value for '{}': {}", n, e))?,
+                    )
+                }
+                _ => command_line.settings.push((key.replace('-', "_"), value)),
+            }
+        }
+
+        // validate
+        if command_line.replay.is_some() && command_line.record.is_some() {
+            return Err("'--replay' and '--record' cannot be used together".to_string());
+        }
+        if command_line.benchmark_report.is_some() && command_line.benchmark.is_none() {
+            return Err("'--benchmark-report' requires '--benchmark'".to_string());
+        }
+        if command_line.export_mesh_path.is_some() && command_line.export_mesh.is_none() {
+            return Err("'--export-mesh-path' requires '--export-mesh'".to_string());
+        }
+
+        Ok(command_line)
+    }
+
+    /// A method to override the settings with the command line.
+    ///
+    /// In benchmark mode, the frame rate cap is disabled.
+    pub fn apply(&self, settings: &mut Settings) -> Result<(), String> {
+        for (key, value) in &self.settings {
+            settings
+                .set(key, value)
+                .map_err(|e| format!("invalid option '--{}': {}", key.replace('_', "-"), e))?;
+        }
+        if self.benchmark.is_some() {
+            settings.frame_rate_cap = None;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::WindowMode;
+    use wgpu::PresentMode;
+
+    fn parse(arguments: &str) -> Result<CommandLine, String> {
+        CommandLine::parse(arguments.split_whitespace().map(|n| n.to_string()))
+    }
+
+    #[test]
+    fn flags_and_options_are_parsed() {
+        assert!(parse("-h").unwrap().is_help);
+        assert!(parse("--help").unwrap().is_help);
+        let command_line =
+            parse("--hot-reload --list-adapters --level level/warehouse.txt --seed 7").unwrap();
+        assert!(command_line.is_hot_reload);
+        assert!(command_line.is_list_adapters);
+        assert!(!command_line.is_help);
+        assert_eq!(command_line.level.as_deref(), Some("level/warehouse.txt"));
+        assert_eq!(command_line.seed, Some(7));
+        let command_line = parse("--benchmark 100 --benchmark-report report.txt").unwrap();
+        assert_eq!(command_line.benchmark, Some(100));
+        assert_eq!(command_line.benchmark_report.as_deref(), Some("report.txt"));
+        assert!(parse("--export-mesh torus(1,0.25,32,16) --export-mesh-path torus.obj").is_ok());
+    }
+
+    #[test]
+    fn invalid_arguments_are_rejected() {
+        let check = |arguments: &str, expected: &str| {
+            let error = parse(arguments).err().unwrap();
+            assert!(error.starts_with(expected), "{}", error);
+        };
+        check("level.txt", "unexpected argument 'level.txt'");
+        check("--level", "no value is specified for '--level'");
+        check("--level --help", "no value is specified for '--level'");
+        check("--seed x", "invalid value for '--seed'");
+        check(
+            "--benchmark 0",
+            "the value for '--benchmark' must be 1 or more",
+        );
+        check("--log-level verbose", "invalid value for '--log-level'");
+        check("--export-mesh cube", "invalid value for '--export-mesh'");
+
+        // conflicting or incomplete options
+        check("--replay a.txt --record b.txt", "'--replay' and '--record'");
+        check(
+            "--benchmark-report report.txt",
+            "'--benchmark-report' requires",
+        );
+        check(
+            "--export-mesh-path torus.obj",
+            "'--export-mesh-path' requires",
+        );
+    }
+
+    #[test]
+    fn other_options_override_settings() {
+        let command_line = parse("--window-mode exclusive --present_mode immediate").unwrap();
+        assert_eq!(
+            command_line.settings,
+            [
+                ("window_mode".to_string(), "exclusive".to_string()),
+                ("present_mode".to_string(), "immediate".to_string()),
+            ]
+        );
+        let mut settings = Settings::default();
+        command_line.apply(&mut settings).unwrap();
+        assert!(settings.window_mode == WindowMode::Exclusive);
+        assert_eq!(settings.present_mode, PresentMode::Immediate);
+
+        // the frame rate cap is disabled in benchmark mode
+        settings.frame_rate_cap = Some(60);
+        parse("--benchmark 10")
+            .unwrap()
+            .apply(&mut settings)
+            .unwrap();
+        assert_eq!(settings.frame_rate_cap, None);
+
+        // the values are validated when they are applied
+        let error = parse("--window-mode maximized")
+            .unwrap()
+            .apply(&mut Settings::default())
+            .err()
+            .unwrap();
+        assert!(
+            error.starts_with("invalid option '--window-mode': unknown window mode"),
+            "{}",
+            error
+        );
+        let error = parse("--no-such-key 1")
+            .unwrap()
+            .apply(&mut Settings::default())
+            .err()
+            .unwrap();
+        assert_eq!(
+            error,
+            "invalid option '--no-such-key': unknown key 'no_such_key'"
+        );
+    }
+}
@@ -1,2 +1,8 @@
+pub mod camera_path;
+pub mod debug_overlay;
+pub mod emitter;
 pub mod entity;
+pub mod layout;
+pub mod level;
 pub mod scene;
+pub mod widget;
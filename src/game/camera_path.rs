@@ -0,0 +1,56 @@
+use crate::util::camera::CameraController;
+use glam::Vec3;
+
+/// The distance ratio to the point that the camera looks at ahead on the path.
+const LOOK_AHEAD: f32 = 0.02;
+
+/// A closed path that the camera flies along, used for benchmarks.
+///
+/// The camera moves at a constant speed through the points and returns to the first point.
+pub struct CameraPath {
+    points: Vec<Vec3>,
+    /// The accumulated distances from the first point to each point, including the closing one.
+    distances: Vec<f32>,
+}
+
+impl CameraPath {
+    /// A constructor.
+    ///
+    /// WARN: `points` must have 2 or more distinct points.
+    pub fn new(points: Vec<Vec3>) -> Self {
+        let mut distances = Vec::from([0.0]);
+        for i in 0..points.len() {
+            let distance = points[i].distance(points[(i + 1) % points.len()]);
+            distances.push(distances[i] + distance);
+        }
+        Self { points, distances }
+    }
+
+    /// A method to get the position on the path.
+    ///
+    /// * `t` - the ratio of the distance from the first point; it's wrapped into [0, 1)
+    pub fn get_position(&self, t: f32) -> Vec3 {
+        let length = self.distances[self.points.len()];
+        let distance = t.rem_euclid(1.0) * length;
+        let i = self
+            .distances
+            .iter()
+            .rposition(|n| *n <= distance)
+            .unwrap_or(0)
+            .min(self.points.len() - 1);
+        let section = self.distances[i + 1] - self.distances[i];
+        let s = if section > 0.0 {
+            (distance - self.distances[i]) / section
+        } else {
+            0.0
+        };
+        self.points[i].lerp(self.points[(i + 1) % self.points.len()], s)
+    }
+
+    /// A method to move the camera to the position on the path and look ahead along the path.
+    pub fn apply(&self, camera_controller: &mut CameraController, t: f32) {
+        let position = self.get_position(t);
+        camera_controller.position = position;
+        camera_controller.look_to(self.get_position(t + LOOK_AHEAD) - position);
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    game::{
+        entity::text::{Text, TextAlign},
+        layout::{Anchor, Layout, Placement},
+    },
+    system::renderer::{
+        profiler::{GpuProfiler, GPU_PASSES},
+        shader::ui::{self, DrawUiDescriptor, UiTextureId},
+        RenderRequest,
+    },
+    util::{font::Font, instance::InstanceController},
+};
+use glam::{EulerRot, Vec2, Vec3, Vec4};
+use std::{collections::VecDeque, rc::Rc, time::Duration};
+
+/// The count of the UI instances the scenes can use.
+///
+/// WARN: The scenes must use the UI instances below it because the overlay uses the following ones.
+pub const SCENE_UI_INSTANCE_LIMIT: usize = 256;
+/// The first index of the UI instances used by the overlay.
+const INSTANCE_OFFSET: usize = SCENE_UI_INSTANCE_LIMIT;
+const _: () = assert!(INSTANCE_OFFSET < ui::MAX_INSTANCE_COUNT as usize);
+/// The count of frames shown in the frame-time graph.
+const GRAPH_FRAME_COUNT: usize = 120;
+/// The width (logical px) of a bar in the frame-time graph.
+const GRAPH_BAR_WIDTH: f32 = 2.0;
+/// The height (logical px) of the frame-time graph.
+const GRAPH_HEIGHT: f32 = 60.0;
+/// The frame time (ms) at the top of the frame-time graph.
+const GRAPH_MAX_MILLISECONDS: f32 = 1000.0 / 30.0;
+/// The frame time (ms) of 60 fps shown as a line in the frame-time graph.
+const GRAPH_TARGET_MILLISECONDS: f32 = 1000.0 / 60.0;
+/// The margin (logical px) between the items.
+const MARGIN: f32 = 8.0;
+/// The scale of the text.
+const TEXT_SCALE: f32 = 0.5;
+
+fn create_rectangle(
+    uv: Vec4,
+    left: f32,
+    top: f32,
+    width: f32,
+    height: f32,
+    color: Vec4,
+) -> Option<InstanceController> {
+    Some(InstanceController {
+        scale: Vec3::new(width, height, 1.0),
+        position: Vec3::new(left + width / 2.0, top - height / 2.0, 0.0),
+        uv,
+        color,
+        ..Default::default()
+    })
+}
+
+/// A toggleable overlay that shows the runtime state for debugging.
+///
+/// It shows the following information:
+/// - FPS and a frame-time graph
+/// - the current scene
+/// - the camera position and rotation
+/// - the instance counts per pipeline
+/// - the GPU times per render pass if the GPU profiler is available
+pub struct DebugOverlay {
+    font: Rc<Font>,
+    is_visible: bool,
+    layout: Layout,
+    /// The frame times (ms) of the last frames.
+    frame_times: VecDeque<f32>,
+    /// Whether the instances were truncated in the last frame, not to report it every frame.
+    is_truncated: bool,
+}
+
+impl DebugOverlay {
+    /// A constructor.
+    ///
+    /// The overlay is hidden at first.
+    pub fn new(font: Rc<Font>, layout: Layout) -> Self {
+        Self {
+            font,
+            is_visible: false,
+            layout,
+            frame_times: VecDeque::with_capacity(GRAPH_FRAME_COUNT),
+            is_truncated: false,
+        }
+    }
+
+    /// A method to show or hide the overlay.
+    pub fn toggle(&mut self) {
+        self.is_visible = !self.is_visible;
+    }
+
+    /// A method to lay out the overlay again depending on the window size and the scale factor.
+    pub fn resize(&mut self, layout: Layout) {
+        self.layout = layout;
+    }
+
+    /// A method to update the overlay and append its render requests.
+    ///
+    /// NOTE: The frame time is recorded even if the overlay is hidden.
+    ///
+    /// * `frame_time` - the time of the last frame
+    /// * `scene_name` - the name of the current scene
+    /// * `gpu_profiler` - the GPU profiler of the renderer if available
+    /// * `render_requests` - the render requests of the scene in this frame
+    pub fn update(
+        &mut self,
+        frame_time: Duration,
+        scene_name: &str,
+        gpu_profiler: Option<&GpuProfiler>,
+        render_requests: &mut Vec<RenderRequest>,
+    ) {
+        // record the frame time
+        if self.frame_times.len() >= GRAPH_FRAME_COUNT {
+            self.frame_times.pop_front();
+        }
+        self.frame_times
+            .push_back(frame_time.as_secs_f32() * 1000.0);
+
+        // check
+        if !self.is_visible {
+            return;
+        }
+
+        // create the texts
+        // NOTE: The overlay is placed at the top left corner.
+        let layout = &self.layout;
+        let origin = layout.place(
+            &Placement::new(Anchor::TopLeft, Anchor::TopLeft, Vec2::ZERO),
+            Vec2::ZERO,
+        );
+        let (left, top) = (origin.left, origin.top);
+        let margin = layout.scale(MARGIN);
+        let text_scale = layout.scale(TEXT_SCALE);
+        let graph_height = layout.scale(GRAPH_HEIGHT);
+        let bar_width = layout.scale(GRAPH_BAR_WIDTH);
+        let white = Vec4::new(1.0, 1.0, 1.0, 1.0);
+        let average = self.frame_times.iter().sum::<f32>() / self.frame_times.len() as f32;
+        let mut fps_text = Text::new(
+            &self.font,
+            left + margin,
+            top - margin,
+            text_scale,
+            white,
+            TextAlign::Left,
+            &format!("FPS {:.1} ({:.2} ms)", 1000.0 / average, average),
+        );
+        let graph_bottom = top - margin - fps_text.get_height() - margin - graph_height;
+        let mut lines = Vec::from([format!("scene  {}", scene_name)]);
+        lines.extend(get_camera_lines(render_requests));
+        lines.push(get_instances_line(render_requests));
+        if let Some(profiler) = gpu_profiler {
+            let times = GPU_PASSES
+                .iter()
+                .filter_map(|n| {
+                    let time = profiler.get_average(*n)?;
+                    Some(format!("{} {:.2}", n.to_str(), time.as_secs_f64() * 1000.0))
+                })
+                .collect::<Vec<String>>();
+            lines.push(format!("gpu ms  {}", times.join("  ")));
+        }
+        lines.push("F3 to hide".to_string());
+        let mut info_text = Text::new(
+            &self.font,
+            left + margin,
+            graph_bottom - margin,
+            text_scale,
+            white,
+            TextAlign::Left,
+            &lines.join("\n"),
+        );
+
+        // create a background
+        // NOTE: The rectangles are drawn with the solid glyph of the font.
+        let mut instance_controllers = Vec::new();
+        if let Some(uv) = self.font.get_solid_uv() {
+            let graph_width = GRAPH_FRAME_COUNT as f32 * bar_width;
+            let background_width = fps_text
+                .get_width()
+                .max(info_text.get_width())
+                .max(graph_width)
+                + margin * 2.0;
+            let background_height = top - (graph_bottom - margin - info_text.get_height()) + margin;
+            instance_controllers.push(create_rectangle(
+                uv,
+                left,
+                top,
+                background_width,
+                background_height,
+                Vec4::new(0.0, 0.0, 0.0, 0.6),
+            ));
+
+            // create a frame-time graph
+            let graph_left = left + margin;
+            for (i, n) in self.frame_times.iter().enumerate() {
+                let bar_height = (n / GRAPH_MAX_MILLISECONDS).min(1.0) * graph_height;
+                let color = if *n <= GRAPH_TARGET_MILLISECONDS {
+                    Vec4::new(0.2, 0.9, 0.2, 1.0)
+                } else if *n <= GRAPH_MAX_MILLISECONDS {
+                    Vec4::new(0.9, 0.9, 0.2, 1.0)
+                } else {
+                    Vec4::new(0.9, 0.2, 0.2, 1.0)
+                };
+                instance_controllers.push(create_rectangle(
+                    uv,
+                    graph_left + i as f32 * bar_width,
+                    graph_bottom + bar_height,
+                    bar_width,
+                    bar_height,
+                    color,
+                ));
+            }
+            let target_height = GRAPH_TARGET_MILLISECONDS / GRAPH_MAX_MILLISECONDS * graph_height;
+            instance_controllers.push(create_rectangle(
+                uv,
+                graph_left,
+                graph_bottom + target_height,
+                graph_width,
+                1.0,
+                Vec4::new(1.0, 1.0, 1.0, 0.5),
+            ));
+        }
+        instance_controllers.extend(fps_text.get_instance_controllers());
+        instance_controllers.extend(info_text.get_instance_controllers());
+
+        // draw
+        // NOTE: The instances before `INSTANCE_OFFSET` are `None` not to overwrite the scene's ones.
+        let end = INSTANCE_OFFSET + instance_controllers.len();
+        let is_truncated = end > ui::MAX_INSTANCE_COUNT as usize;
+        if is_truncated && !self.is_truncated {
+            warn!(
+                "DebugOverlay.update",
+                "{} instances are truncated.",
+                end - ui::MAX_INSTANCE_COUNT as usize
+            );
+        }
+        self.is_truncated = is_truncated;
+        let end = end.min(ui::MAX_INSTANCE_COUNT as usize);
+        let mut update_requests = vec![None; INSTANCE_OFFSET];
+        update_requests.extend(instance_controllers);
+        render_requests.push(RenderRequest::UpdateUiInstances(update_requests));
+        render_requests.push(RenderRequest::DrawUi(DrawUiDescriptor {
+            clear_color: None,
+            instance_indices: Vec::from([(UiTextureId::Font, INSTANCE_OFFSET as u32, end as u32)]),
+        }));
+    }
+}
+
+fn get_camera_lines(render_requests: &[RenderRequest]) -> Vec<String> {
+    let camera_controller = render_requests.iter().rev().find_map(|n| match n {
+        RenderRequest::UpdateCamera(n) => Some(n),
+        _ => None,
+    });
+    let Some(camera_controller) = camera_controller else {
+        return Vec::from(["camera -".to_string()]);
+    };
+    let position = camera_controller.position;
+    let (yaw, pitch, _) = camera_controller.rotation.to_euler(EulerRot::YXZ);
+    Vec::from([
+        format!(
+            "camera {:.2} {:.2} {:.2}",
+            position.x, position.y, position.z
+        ),
+        format!(
+            "yaw {:.1} pitch {:.1}",
+            yaw.to_degrees(),
+            pitch.to_degrees()
+        ),
+    ])
+}
+
+fn get_instances_line(render_requests: &[RenderRequest]) -> String {
+    let mut skybox = 0;
+    let mut world = 0;
+    let mut ui = 0;
+    for n in render_requests {
+        match n {
+            RenderRequest::DrawSkybox => skybox += 1,
+            RenderRequest::DrawWorld(n) => {
+                world += n
+                    .instance_indices
+                    .iter()
+                    .map(|(_, start, end)| end.saturating_sub(*start))
+                    .sum::<u32>()
+            }
+            RenderRequest::DrawUi(n) => {
+                ui += n
+                    .instance_indices
+                    .iter()
+                    .map(|(_, start, end)| end.saturating_sub(*start))
+                    .sum::<u32>()
+            }
+            _ => (),
+        }
+    }
+    format!("instances skybox {} world {} ui {}", skybox, world, ui)
+}
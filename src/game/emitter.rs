@@ -0,0 +1,94 @@
+//! A module for the presets of particles emitted at once.
+//!
+//! An `Emitter` only creates particles; they're simulated and drawn by the particle pipeline.
+//! The speeds and the lifetimes are in frames like the other entities.
+
+use crate::{system::renderer::shader::particle::Particle, util::random::Random};
+use glam::{Quat, Vec3, Vec4};
+use std::f32::consts::PI;
+
+/// A preset of a burst of particles.
+pub struct Emitter {
+    pub count: u32,
+    /// The range of the speed (units per frame).
+    pub speed: (f32, f32),
+    /// The max angle (rad) between the direction and the velocities. `PI` emits in all directions.
+    pub spread: f32,
+    /// The range of the lifetime (frames).
+    pub lifetime: (f32, f32),
+    /// The acceleration (units per frame^2) downward.
+    pub gravity: f32,
+    /// The linear color when spawned. It can exceed 1.0 to glow with the bloom.
+    pub start_color: Vec4,
+    pub end_color: Vec4,
+    pub start_size: f32,
+    pub end_size: f32,
+}
+
+/// A flash at the muzzle when the player fires.
+pub const MUZZLE_FLASH: Emitter = Emitter {
+    count: 6,
+    speed: (0.005, 0.02),
+    spread: 0.4,
+    lifetime: (3.0, 5.0),
+    gravity: 0.0,
+    start_color: Vec4::new(4.0, 2.4, 0.8, 1.0),
+    end_color: Vec4::new(1.0, 0.3, 0.0, 0.0),
+    start_size: 0.06,
+    end_size: 0.02,
+};
+
+/// Sparks bouncing back toward the shooter when a shot hits.
+pub const IMPACT: Emitter = Emitter {
+    count: 12,
+    speed: (0.03, 0.08),
+    spread: 0.6,
+    lifetime: (8.0, 16.0),
+    gravity: 0.004,
+    start_color: Vec4::new(3.0, 2.6, 1.6, 1.0),
+    end_color: Vec4::new(1.0, 0.4, 0.1, 0.0),
+    start_size: 0.05,
+    end_size: 0.01,
+};
+
+/// A burst in all directions when a target is destroyed.
+pub const EXPLOSION: Emitter = Emitter {
+    count: 48,
+    speed: (0.02, 0.06),
+    spread: PI,
+    lifetime: (20.0, 40.0),
+    gravity: 0.002,
+    start_color: Vec4::new(2.0, 1.2, 0.4, 1.0),
+    end_color: Vec4::new(0.6, 0.1, 0.0, 0.0),
+    start_size: 0.12,
+    end_size: 0.04,
+};
+
+impl Emitter {
+    /// A method to create the particles of a burst.
+    ///
+    /// * `direction` - the normalized center direction of the velocities
+    pub fn emit(&self, random: &mut Random, position: Vec3, direction: Vec3) -> Vec<Particle> {
+        // NOTE: The velocities are uniformly distributed on the spherical cap around `direction`.
+        let rotation = Quat::from_rotation_arc(Vec3::Z, direction);
+        let min_cos = self.spread.cos();
+        (0..self.count)
+            .map(|_| {
+                let cos = random.range(min_cos, 1.0);
+                let sin = (1.0 - cos * cos).max(0.0).sqrt();
+                let angle = random.range(0.0, PI * 2.0);
+                let local = Vec3::new(sin * angle.cos(), sin * angle.sin(), cos);
+                Particle {
+                    position,
+                    velocity: rotation.mul_vec3(local) * random.range(self.speed.0, self.speed.1),
+                    gravity: self.gravity,
+                    lifetime: random.range(self.lifetime.0, self.lifetime.1),
+                    start_color: self.start_color,
+                    end_color: self.end_color,
+                    start_size: self.start_size,
+                    end_size: self.end_size,
+                }
+            })
+            .collect()
+    }
+}
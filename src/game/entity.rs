@@ -1,6 +1,10 @@
+pub mod crosshair;
+pub mod destruction;
 pub mod digits;
 pub mod floor;
 pub mod message;
-pub mod reticle;
+pub mod popup;
+pub mod prop;
 pub mod target;
+pub mod text;
 pub mod wall;
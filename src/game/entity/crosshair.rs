@@ -0,0 +1,84 @@
+use crate::{
+    game::layout::Layout, settings::CrosshairSettings,
+    system::renderer::shader::crosshair::DrawCrosshairDescriptor,
+};
+use glam::{Vec2, Vec4};
+
+/// The gap (logical px) added while moving.
+const MAX_SPREAD: f32 = 8.0;
+/// The rate the spread approaches the target in a frame.
+const SPREAD_RATE: f32 = 0.2;
+/// The count of frames the hit marker is shown.
+const HIT_MARKER_FRAMES: u32 = 12;
+
+/// A function to convert an sRGB component into linear.
+///
+/// NOTE: The UI is drawn on an sRGB surface, so the colors must be linear.
+fn to_linear(component: u8) -> f32 {
+    let n = component as f32 / 255.0;
+    if n <= 0.04045 {
+        n / 12.92
+    } else {
+        ((n + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// A procedural crosshair entity.
+///
+/// It's drawn by `RenderRequest::DrawCrosshair` instead of an UI instance.
+pub struct Crosshair {
+    settings: CrosshairSettings,
+    /// The current spread (logical px) added to the gap.
+    spread: f32,
+    /// The remaining frames of the hit marker.
+    hit_marker_count: u32,
+}
+
+impl Crosshair {
+    /// A constructor.
+    pub fn new(settings: &CrosshairSettings) -> Self {
+        Self {
+            settings: settings.clone(),
+            spread: 0.0,
+            hit_marker_count: 0,
+        }
+    }
+
+    /// A method to update the spread.
+    ///
+    /// The gap spreads smoothly while moving if the spread is enabled in the settings.
+    pub fn update(&mut self, is_moving: bool) {
+        let target = if is_moving && self.settings.is_spread {
+            MAX_SPREAD
+        } else {
+            0.0
+        };
+        self.spread += (target - self.spread) * SPREAD_RATE;
+        self.hit_marker_count = self.hit_marker_count.saturating_sub(1);
+    }
+
+    /// A method to show the hit marker.
+    pub fn on_hit(&mut self) {
+        self.hit_marker_count = HIT_MARKER_FRAMES;
+    }
+
+    /// A method to get the draw request of this.
+    ///
+    /// * `position` - the center in UI coordinates
+    pub fn get_descriptor(&self, layout: &Layout, position: Vec2) -> DrawCrosshairDescriptor {
+        let settings = &self.settings;
+        let (r, g, b) = settings.color;
+        DrawCrosshairDescriptor {
+            position,
+            has_lines: settings.has_lines,
+            has_dot: settings.has_dot,
+            has_circle: settings.has_circle,
+            gap: layout.scale(settings.gap + self.spread),
+            thickness: layout.scale(settings.thickness),
+            length: layout.scale(settings.length),
+            outline: layout.scale(settings.outline),
+            color: Vec4::new(to_linear(r), to_linear(g), to_linear(b), settings.opacity),
+            hit_marker: self.hit_marker_count as f32 / HIT_MARKER_FRAMES as f32,
+        }
+    }
+}
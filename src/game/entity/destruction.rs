@@ -0,0 +1,47 @@
+use crate::util::instance::InstanceController;
+use glam::{Quat, Vec3};
+
+/// The count of frames the animation lasts.
+const DESTRUCTION_FRAMES: u32 = 15;
+/// The scale of the target.
+const TARGET_SCALE: f32 = 0.2;
+
+/// A shrinking sphere entity on the world left where a target is shot.
+///
+/// It pops a little, then shrinks to nothing while spinning.
+pub struct Destruction {
+    instance_controller: InstanceController,
+    count: u32,
+}
+
+impl Destruction {
+    /// A constructor.
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            instance_controller: InstanceController {
+                position,
+                scale: Vec3::splat(TARGET_SCALE),
+                ..Default::default()
+            },
+            count: 0,
+        }
+    }
+
+    /// A method to get the `InstanceController` of this.
+    pub fn get_instance_controller(&self) -> InstanceController {
+        self.instance_controller.clone()
+    }
+
+    /// A method to advance the animation.
+    ///
+    /// It returns `false` if the animation is finished.
+    pub fn update(&mut self) -> bool {
+        self.count += 1;
+        let t = self.count as f32 / DESTRUCTION_FRAMES as f32;
+        // NOTE: It peaks at 1.125 times the target at t = 0.25 and reaches 0 at t = 1.
+        let scale = TARGET_SCALE * (1.0 - t).max(0.0) * (1.0 + t * 2.0);
+        self.instance_controller.scale = Vec3::splat(scale);
+        self.instance_controller.rotation = Quat::from_rotation_y(t * 4.0);
+        self.count < DESTRUCTION_FRAMES
+    }
+}
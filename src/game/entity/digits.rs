@@ -62,6 +62,15 @@ impl Digits {
             .collect()
     }
 
+    /// A method to get the vector of `InstanceController` of this again at the next update.
+    ///
+    /// NOTE: It's used when the renderer is recreated and the instances on the GPU are lost.
+    pub fn invalidate(&mut self) {
+        for n in &mut self.instance_controllers {
+            n.invalidate();
+        }
+    }
+
     /// A method to set number.
     pub fn set_number(&mut self, number: u32) {
         self.instance_controllers = new(self.x, self.y, self.width, number);
@@ -23,4 +23,11 @@ impl Floor {
     pub fn get_instance_controller(&mut self) -> Option<InstanceController> {
         self.instance_controller.cache()
     }
+
+    /// A method to get the `InstanceController` of this again at the next update.
+    ///
+    /// NOTE: It's used when the renderer is recreated and the instances on the GPU are lost.
+    pub fn invalidate(&mut self) {
+        self.instance_controller.invalidate();
+    }
 }
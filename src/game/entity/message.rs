@@ -1,5 +1,8 @@
-use crate::util::{cache::Cache, instance::InstanceController};
-use glam::{Vec3, Vec4};
+use crate::{
+    game::layout::{Layout, Placement},
+    util::{cache::Cache, instance::InstanceController},
+};
+use glam::{Vec2, Vec3, Vec4};
 
 /// A message entity.
 pub struct Message {
@@ -21,6 +24,16 @@ impl Message {
         }
     }
 
+    /// A constructor to place the message by a layout.
+    ///
+    /// * `width` - the width in logical px
+    pub fn placed(layout: &Layout, placement: &Placement, width: f32, uv: Vec4) -> Self {
+        let width = layout.scale(width);
+        let rect = layout.place(placement, Vec2::new(width, width * 0.125 / uv.z));
+        let center = rect.get_center();
+        Self::new(center.x, center.y, width, uv)
+    }
+
     /// A method to get the `InstanceController` of this.
     ///
     /// WARN: If no update is needed, return `None`.
@@ -28,13 +41,15 @@ impl Message {
         self.instance_controller.cache()
     }
 
+    /// A method to get the `InstanceController` of this again at the next update.
+    ///
+    /// NOTE: It's used when the renderer is recreated and the instances on the GPU are lost.
+    pub fn invalidate(&mut self) {
+        self.instance_controller.invalidate();
+    }
+
     /// A method to get the height.
     pub fn get_height(&self) -> f32 {
         self.instance_controller.get().scale.y
     }
-
-    /// A method to set the position.
-    pub fn set_position(&mut self, x: f32, y: f32) {
-        self.instance_controller.get_mut().position = Vec3::new(x, y, 0.0);
-    }
 }
@@ -0,0 +1,66 @@
+use crate::{
+    game::{
+        entity::text::{Text, TextAlign},
+        layout::{Anchor, Layout, Placement},
+    },
+    util::{camera::CameraController, font::Font},
+};
+use glam::{Vec3, Vec4};
+
+/// The count of frames the popup is shown.
+const POPUP_FRAMES: u32 = 45;
+/// The distance (logical px) the popup rises in its lifetime.
+const POPUP_RISE: f32 = 48.0;
+
+/// A text entity rising from a point on the world and fading out, e.g. the score of a shot target.
+pub struct Popup {
+    /// The origin on the world.
+    position: Vec3,
+    text: String,
+    count: u32,
+}
+
+impl Popup {
+    /// A constructor.
+    pub fn new(position: Vec3, text: &str) -> Self {
+        Self {
+            position,
+            text: text.to_string(),
+            count: 0,
+        }
+    }
+
+    /// A method to advance the animation.
+    ///
+    /// It returns `false` if the animation is finished.
+    pub fn update(&mut self) -> bool {
+        self.count += 1;
+        self.count < POPUP_FRAMES
+    }
+
+    /// A method to create the text of the current frame.
+    ///
+    /// It returns `None` if the origin is behind the camera.
+    pub fn get_text(
+        &self,
+        font: &Font,
+        layout: &Layout,
+        camera_controller: &CameraController,
+    ) -> Option<Text> {
+        let t = self.count as f32 / POPUP_FRAMES as f32;
+        let offset = camera_controller.project(self.position)? / layout.scale_factor;
+        Some(Text::placed(
+            font,
+            layout,
+            &Placement::new(
+                Anchor::Center,
+                Anchor::Bottom,
+                offset.with_y(offset.y + POPUP_RISE * t),
+            ),
+            1.0,
+            Vec4::new(1.0, 0.8, 0.2, 1.0 - t * t),
+            TextAlign::Center,
+            &self.text,
+        ))
+    }
+}
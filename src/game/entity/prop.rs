@@ -0,0 +1,48 @@
+use crate::{
+    system::renderer::model::ModelId,
+    util::{cache::Cache, instance::InstanceController},
+};
+use glam::{Quat, Vec3};
+
+/// A prop entity on the world, which is a static glTF model without collision.
+pub struct Prop {
+    model_id: ModelId,
+    instance_controller: Cache<InstanceController>,
+}
+
+impl Prop {
+    /// A constructor.
+    ///
+    /// * `rotation` - the rotation angle (rad) around y-axis
+    /// * `scale` - the uniform scale of the model
+    pub fn new(model_id: ModelId, position: Vec3, rotation: f32, scale: f32) -> Self {
+        Self {
+            model_id,
+            instance_controller: Cache::new(InstanceController {
+                position,
+                scale: Vec3::splat(scale),
+                rotation: Quat::from_rotation_y(rotation),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// A method to get the id of the model drawn.
+    pub fn get_model_id(&self) -> ModelId {
+        self.model_id
+    }
+
+    /// A method to get the `InstanceController` of the prop entity.
+    ///
+    /// WARN: If no update is needed, return `None`.
+    pub fn get_instance_controller(&mut self) -> Option<InstanceController> {
+        self.instance_controller.cache()
+    }
+
+    /// A method to get the `InstanceController` of this again at the next update.
+    ///
+    /// NOTE: It's used when the renderer is recreated and the instances on the GPU are lost.
+    pub fn invalidate(&mut self) {
+        self.instance_controller.invalidate();
+    }
+}
@@ -1,16 +1,21 @@
 use crate::util::instance::InstanceController;
 use glam::Vec3;
 
+/// A type of a function to update the position of a target.
+///
+/// It's called with the position and the count of frames since the target was created.
+pub type UpdateFunction = Box<dyn FnMut(&mut Vec3, u32)>;
+
 /// A target entity on the world.
 pub struct Target {
     instance_controller: InstanceController,
-    update_function: Box<dyn FnMut(&mut Vec3, u32)>,
+    update_function: UpdateFunction,
     count: u32,
 }
 
 impl Target {
     /// A constructor.
-    pub fn new(position: Vec3, update_function: Box<dyn FnMut(&mut Vec3, u32)>) -> Self {
+    pub fn new(position: Vec3, update_function: UpdateFunction) -> Self {
         Self {
             instance_controller: InstanceController {
                 position,
@@ -27,6 +32,11 @@ impl Target {
         self.instance_controller.clone()
     }
 
+    /// A method to get the current position.
+    pub fn get_position(&self) -> Vec3 {
+        self.instance_controller.position
+    }
+
     /// A method to update this by calling `update_function`.
     pub fn update(&mut self) {
         (self.update_function)(&mut self.instance_controller.position, self.count);
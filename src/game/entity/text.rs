@@ -0,0 +1,182 @@
+use crate::{
+    game::layout::{Layout, Placement},
+    util::{cache::Cache, font::Font, instance::InstanceController},
+};
+use glam::{Vec2, Vec3, Vec4};
+
+/// An enum for enumerating the horizontal alignments of texts.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextAlign {
+    /// `x` is the left of each line.
+    Left,
+    /// `x` is the center of each line.
+    Center,
+    /// `x` is the right of each line.
+    Right,
+}
+
+fn get_line_width(font: &Font, line: &str) -> f32 {
+    let mut width = 0.0;
+    let mut previous = None;
+    for character in line.chars() {
+        let Some(glyph) = font.get_glyph(character) else {
+            continue;
+        };
+        if let Some(n) = previous {
+            width += font.get_kerning(n, character);
+        }
+        width += glyph.x_advance;
+        previous = Some(character);
+    }
+    width
+}
+
+fn new(
+    font: &Font,
+    x: f32,
+    y: f32,
+    scale: f32,
+    color: Vec4,
+    align: TextAlign,
+    text: &str,
+) -> (Vec<Cache<InstanceController>>, f32) {
+    let mut result = Vec::new();
+    let mut max_width: f32 = 0.0;
+    for (row, line) in text.lines().enumerate() {
+        // align the line
+        let width = get_line_width(font, line) * scale;
+        max_width = max_width.max(width);
+        let left = match align {
+            TextAlign::Left => x,
+            TextAlign::Center => x - width / 2.0,
+            TextAlign::Right => x - width,
+        };
+        // NOTE: Put the line on the pixel grid not to blur the glyphs.
+        let mut pen = left.round();
+        let top = (y - row as f32 * font.get_line_height() * scale).round();
+
+        // put the glyphs
+        let mut previous = None;
+        for character in line.chars() {
+            // NOTE: The characters the font doesn't have are drawn as '?' or skipped.
+            let Some(glyph) = font.get_glyph(character) else {
+                continue;
+            };
+            if let Some(n) = previous {
+                pen += font.get_kerning(n, character) * scale;
+            }
+            previous = Some(character);
+            if glyph.width > 0.0 && glyph.height > 0.0 {
+                let width = glyph.width * scale;
+                let height = glyph.height * scale;
+                let glyph_left = pen + glyph.x_offset * scale;
+                let glyph_top = top - glyph.y_offset * scale;
+                result.push(Cache::new(InstanceController {
+                    scale: Vec3::new(width, height, 1.0),
+                    position: Vec3::new(glyph_left + width / 2.0, glyph_top - height / 2.0, 0.0),
+                    uv: font.get_uv(glyph),
+                    color,
+                    ..Default::default()
+                }));
+            }
+            pen += glyph.x_advance * scale;
+        }
+    }
+    (result, max_width)
+}
+
+/// A text entity drawn with a bitmap font.
+///
+/// Any UTF-8 string is available and `\n` starts a new line.
+/// Each glyph uses an UI instance, so the text must be drawn with `UiTextureId::Font`.
+pub struct Text {
+    instance_controllers: Vec<Cache<InstanceController>>,
+    width: f32,
+    height: f32,
+}
+
+impl Text {
+    /// A constructor.
+    ///
+    /// * `font` - the font whose page image is `/image/font.png`
+    /// * `x` - the left, the center or the right of the text depending on `align`
+    /// * `y` - the top of the text
+    /// * `scale` - the scale of glyphs; 1.0 means the size in the page image
+    /// * `color` - the color multiplied by the glyphs
+    /// * `align` - the horizontal alignment of each line
+    pub fn new(
+        font: &Font,
+        x: f32,
+        y: f32,
+        scale: f32,
+        color: Vec4,
+        align: TextAlign,
+        text: &str,
+    ) -> Self {
+        let (instance_controllers, width) = new(font, x, y, scale, color, align, text);
+        Self {
+            instance_controllers,
+            width,
+            height: text.lines().count() as f32 * font.get_line_height() * scale,
+        }
+    }
+
+    /// A constructor to place the text by a layout.
+    ///
+    /// The bounding box of all the lines is placed and each line is aligned in it.
+    ///
+    /// * `scale` - the scale of glyphs in logical px
+    #[allow(clippy::too_many_arguments)]
+    pub fn placed(
+        font: &Font,
+        layout: &Layout,
+        placement: &Placement,
+        scale: f32,
+        color: Vec4,
+        align: TextAlign,
+        text: &str,
+    ) -> Self {
+        let scale = layout.scale(scale);
+        let width = text
+            .lines()
+            .map(|n| get_line_width(font, n) * scale)
+            .fold(0.0, f32::max);
+        let height = text.lines().count() as f32 * font.get_line_height() * scale;
+        let rect = layout.place(placement, Vec2::new(width, height));
+        let x = match align {
+            TextAlign::Left => rect.left,
+            TextAlign::Center => rect.get_center().x,
+            TextAlign::Right => rect.get_right(),
+        };
+        Self::new(font, x, rect.top, scale, color, align, text)
+    }
+
+    /// A method to get the vector of `InstanceController` of this.
+    ///
+    /// WARN: If no update is needed, return `None`.
+    pub fn get_instance_controllers(&mut self) -> Vec<Option<InstanceController>> {
+        self.instance_controllers
+            .iter_mut()
+            .map(|n| n.cache())
+            .collect()
+    }
+
+    /// A method to get the vector of `InstanceController` of this again at the next update.
+    ///
+    /// NOTE: It's used when the renderer is recreated and the instances on the GPU are lost.
+    pub fn invalidate(&mut self) {
+        for n in &mut self.instance_controllers {
+            n.invalidate();
+        }
+    }
+
+    /// A method to get the width of the longest line.
+    pub fn get_width(&self) -> f32 {
+        self.width
+    }
+
+    /// A method to get the height of all the lines.
+    pub fn get_height(&self) -> f32 {
+        self.height
+    }
+}
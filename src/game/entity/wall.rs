@@ -16,7 +16,7 @@ fn get_intersection(p11: Vec2, p12: Vec2, p21: Vec2, p22: Vec2) -> Option<Vec2>
     }
     let s = cross(p21 - p11, v2) / c;
     let t = cross(v1, p11 - p21) / c;
-    if s < 0.0 || s > 1.0 || t < 0.0 || t > 1.0 {
+    if !(0.0..=1.0).contains(&s) || !(0.0..=1.0).contains(&t) {
         return None;
     }
     Some(p11 + s * v1)
@@ -70,6 +70,13 @@ impl Wall {
         self.instance_controller.cache()
     }
 
+    /// A method to get the `InstanceController` of this again at the next update.
+    ///
+    /// NOTE: It's used when the renderer is recreated and the instances on the GPU are lost.
+    pub fn invalidate(&mut self) {
+        self.instance_controller.invalidate();
+    }
+
     /// A method to check if the wall entity and `position + velocity` is collided.
     ///
     /// If a collision occurs, it returns new velocity.
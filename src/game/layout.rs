@@ -0,0 +1,153 @@
+//! A module for resolution-independent UI layout.
+//!
+//! An UI element is placed by a `Placement`:
+//! the pivot point of the element is put on the anchor point of the screen and moved by the offset.
+//! The anchor points on the edges are inside the safe margins.
+//!
+//! The sizes and the offsets are in logical px, which are multiplied by the scale factor of the window.
+//! If the window is smaller than 1280x720 logical px, the UI is shrunk to fit it.
+//! The UI coordinates are in physical px with the origin at the center of the window and y up.
+
+use glam::Vec2;
+
+/// The margin (logical px) from the edges of the window.
+const SAFE_MARGIN: f32 = 16.0;
+/// The smallest window size (logical px) the UI is designed for.
+const MIN_LOGICAL_SIZE: (f32, f32) = (1280.0, 720.0);
+
+/// An enum for enumerating the points on a rectangle.
+///
+/// NOTE: All the points are provided even if no UI uses some of them.
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// A method to get the position of the point relative to the size of a rectangle.
+    ///
+    /// The center is `(0, 0)` and the top right is `(0.5, 0.5)`.
+    fn get_factor(self) -> Vec2 {
+        match self {
+            Self::TopLeft => Vec2::new(-0.5, 0.5),
+            Self::Top => Vec2::new(0.0, 0.5),
+            Self::TopRight => Vec2::new(0.5, 0.5),
+            Self::Left => Vec2::new(-0.5, 0.0),
+            Self::Center => Vec2::new(0.0, 0.0),
+            Self::Right => Vec2::new(0.5, 0.0),
+            Self::BottomLeft => Vec2::new(-0.5, -0.5),
+            Self::Bottom => Vec2::new(0.0, -0.5),
+            Self::BottomRight => Vec2::new(0.5, -0.5),
+        }
+    }
+}
+
+/// A rectangle in UI coordinates.
+#[derive(Clone, Copy, Debug)]
+pub struct Rect {
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    /// A method to check if a point is in this.
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.x >= self.left
+            && point.x < self.left + self.width
+            && point.y <= self.top
+            && point.y > self.top - self.height
+    }
+
+    /// A method to get the center.
+    pub fn get_center(&self) -> Vec2 {
+        Vec2::new(self.left + self.width / 2.0, self.top - self.height / 2.0)
+    }
+
+    /// A method to get the right.
+    pub fn get_right(&self) -> f32 {
+        self.left + self.width
+    }
+}
+
+/// A placement of an UI element.
+#[derive(Clone, Copy, Debug)]
+pub struct Placement {
+    /// The point on the window.
+    pub anchor: Anchor,
+    /// The point on the element put on `anchor`.
+    pub pivot: Anchor,
+    /// The offset (logical px) from `anchor`. y is up.
+    pub offset: Vec2,
+}
+
+impl Placement {
+    /// A constructor.
+    pub fn new(anchor: Anchor, pivot: Anchor, offset: Vec2) -> Self {
+        Self {
+            anchor,
+            pivot,
+            offset,
+        }
+    }
+}
+
+/// A layout of the window that places UI elements.
+#[derive(Clone, Copy, Debug)]
+pub struct Layout {
+    /// The width (physical px) of the window.
+    pub width: f32,
+    /// The height (physical px) of the window.
+    pub height: f32,
+    /// The scale factor from logical px to physical px.
+    ///
+    /// NOTE: It's smaller than `Window::scale_factor()` if the window is small.
+    pub scale_factor: f32,
+}
+
+impl Layout {
+    /// A constructor.
+    ///
+    /// * `scale_factor` - `Window::scale_factor()`
+    pub fn new(width: f32, height: f32, scale_factor: f32) -> Self {
+        let fit = (width / (MIN_LOGICAL_SIZE.0 * scale_factor))
+            .min(height / (MIN_LOGICAL_SIZE.1 * scale_factor))
+            .clamp(0.0, 1.0);
+        Self {
+            width,
+            height,
+            scale_factor: scale_factor * fit,
+        }
+    }
+
+    /// A method to convert logical px into physical px.
+    pub fn scale(&self, logical: f32) -> f32 {
+        logical * self.scale_factor
+    }
+
+    /// A method to place an element.
+    ///
+    /// * `size` - the size (physical px) of the element
+    pub fn place(&self, placement: &Placement, size: Vec2) -> Rect {
+        let margin = self.scale(SAFE_MARGIN);
+        let area = Vec2::new(self.width - margin * 2.0, self.height - margin * 2.0).max(Vec2::ZERO);
+        let anchor = placement.anchor.get_factor() * area + placement.offset * self.scale_factor;
+        let center = anchor - placement.pivot.get_factor() * size;
+        Rect {
+            left: center.x - size.x / 2.0,
+            top: center.y + size.y / 2.0,
+            width: size.x,
+            height: size.y,
+        }
+    }
+}
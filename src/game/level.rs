@@ -0,0 +1,564 @@
+//! A module for levels.
+//!
+//! A level is described in a text file.
+//! See `/level/default.txt` for the format.
+
+use crate::{
+    game::{
+        camera_path::CameraPath,
+        entity::{
+            floor::Floor,
+            prop::Prop,
+            target::{Target, UpdateFunction},
+            wall::Wall,
+        },
+    },
+    system::{
+        asset::AssetManager,
+        renderer::{
+            model::{ModelId, ModelSource},
+            shader::{skybox::SkyboxSource, world},
+        },
+    },
+    util::random::Random,
+};
+use glam::Vec3;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The asset directory of the level files listed on the level select screen.
+const LEVEL_DIRECTORY: &str = "level";
+/// The asset path of the level used unless another level is selected.
+const DEFAULT_LEVEL_PATH: &str = "level/default.txt";
+
+/// An enum for enumerating the motions of targets.
+#[derive(Clone)]
+enum TargetMotion {
+    Static,
+    /// Oscillates along an axis.
+    ///
+    /// `position[axis] = base[axis] + sin(count * speed) * amplitude`
+    Wave {
+        axis: usize,
+        amplitude: f32,
+        speed: f32,
+    },
+    /// Jumps on the x-y plane every `interval` frames.
+    ///
+    /// `position = base + (sin(k * step_x), sin(k * step_y), 0) * amplitude` where `k = count / interval`
+    Hop {
+        interval: u32,
+        amplitude: f32,
+        step_x: f32,
+        step_y: f32,
+    },
+}
+
+impl TargetMotion {
+    fn create_update_function(&self, base: Vec3) -> UpdateFunction {
+        match self.clone() {
+            Self::Static => Box::new(|_, _| ()),
+            Self::Wave {
+                axis,
+                amplitude,
+                speed,
+            } => Box::new(move |n: &mut Vec3, i: u32| {
+                n[axis] = base[axis] + (i as f32 * speed).to_radians().sin() * amplitude
+            }),
+            Self::Hop {
+                interval,
+                amplitude,
+                step_x,
+                step_y,
+            } => Box::new(move |n: &mut Vec3, i: u32| {
+                let k = (i / interval) as f32;
+                *n = base
+                    + Vec3::new(
+                        (k * step_x).to_radians().sin(),
+                        (k * step_y).to_radians().sin(),
+                        0.0,
+                    ) * amplitude
+            }),
+        }
+    }
+}
+
+/// An enum for enumerating how to place targets.
+enum TargetPlacement {
+    Fixed(Vec3),
+    /// Placed at a random position in the box between `min` and `max`.
+    Random {
+        min: Vec3,
+        max: Vec3,
+    },
+}
+
+struct TargetDescriptor {
+    placement: TargetPlacement,
+    motion: TargetMotion,
+}
+
+struct WallDescriptor {
+    position: Vec3,
+    rotation: f32,
+    scale: Vec3,
+}
+
+struct PropDescriptor {
+    model_id: ModelId,
+    position: Vec3,
+    rotation: f32,
+    scale: f32,
+}
+
+fn parse_f32s(values: &[&str]) -> Result<Vec<f32>, String> {
+    values
+        .iter()
+        .map(|n| {
+            n.parse::<f32>()
+                .map_err(|e| format!("invalid number '{}': {}", n, e))
+        })
+        .collect()
+}
+
+fn parse_exact_f32s(values: &[&str], count: usize) -> Result<Vec<f32>, String> {
+    if values.len() != count {
+        return Err(format!(
+            "{} values are expected but {} found",
+            count,
+            values.len()
+        ));
+    }
+    parse_f32s(values)
+}
+
+fn parse_motion(values: &[&str]) -> Result<TargetMotion, String> {
+    let Some((name, values)) = values.split_first() else {
+        return Ok(TargetMotion::Static);
+    };
+    match *name {
+        "static" => {
+            parse_exact_f32s(values, 0)?;
+            Ok(TargetMotion::Static)
+        }
+        "wave" => {
+            let Some((axis, values)) = values.split_first() else {
+                return Err("no axis is specified for wave".to_string());
+            };
+            let axis = match *axis {
+                "x" => 0,
+                "y" => 1,
+                "z" => 2,
+                _ => return Err(format!("unknown axis '{}'", axis)),
+            };
+            let n = parse_exact_f32s(values, 2)?;
+            Ok(TargetMotion::Wave {
+                axis,
+                amplitude: n[0],
+                speed: n[1],
+            })
+        }
+        "hop" => {
+            let n = parse_exact_f32s(values, 4)?;
+            if n[0] < 1.0 {
+                return Err("the interval of hop must be 1 or more".to_string());
+            }
+            Ok(TargetMotion::Hop {
+                interval: n[0] as u32,
+                amplitude: n[1],
+                step_x: n[2],
+                step_y: n[3],
+            })
+        }
+        _ => Err(format!("unknown motion '{}'", name)),
+    }
+}
+
+/// A function to list the level files in the assets, including the embedded ones.
+///
+/// It returns the pairs of the name and the asset path sorted by the path.
+pub fn list_levels(assets: &AssetManager) -> Vec<(String, String)> {
+    assets
+        .list(LEVEL_DIRECTORY, "txt")
+        .into_iter()
+        .filter_map(|n| Some((Path::new(&n).file_stem()?.to_str()?.to_string(), n)))
+        .collect()
+}
+
+/// A level that describes the layout of a game scene.
+pub struct Level {
+    /// The name shown on the title screen.
+    pub name: String,
+    /// The file the level is loaded from, which is watched for hot-reload.
+    ///
+    /// If it isn't loaded from a file, e.g. it's embedded, it's `None`.
+    pub path: Option<PathBuf>,
+    pub spawn: Vec3,
+    floor: (f32, f32),
+    walls: Vec<WallDescriptor>,
+    targets: Vec<TargetDescriptor>,
+    /// The pairs of the id and the source of the models used by the props.
+    models: Vec<(ModelId, ModelSource)>,
+    props: Vec<PropDescriptor>,
+    skybox: SkyboxSource,
+    camera_path: Vec<Vec3>,
+}
+
+impl Level {
+    /// A static method to load the default level from the assets.
+    pub fn default_level(assets: &AssetManager) -> Result<Self, String> {
+        Self::load_asset(assets, DEFAULT_LEVEL_PATH)
+    }
+
+    /// A static method to load a level from the assets, e.g. the one selected on the title screen.
+    ///
+    /// * `path` - the path relative to the asset directory such as `level/warehouse.txt`
+    pub fn load_asset(assets: &AssetManager, path: &str) -> Result<Self, String> {
+        let text = assets.read_text(path).map_err(|e| e.to_string())?;
+        Self::parse_file(&text, path, assets.resolve(path))
+    }
+
+    /// A static method to load a level from a file outside the assets specified by `--level`.
+    pub fn load(path: &str) -> Result<Self, String> {
+        Self::read_file(Path::new(path))
+    }
+
+    /// A method to load this level again from the file it's loaded from for hot-reload.
+    ///
+    /// If it isn't loaded from a file, e.g. it's embedded, it returns an error.
+    pub fn reload(&self) -> Result<Self, String> {
+        match &self.path {
+            Some(n) => Self::read_file(n),
+            None => Err(format!("the level '{}' has no file", self.name)),
+        }
+    }
+
+    fn read_file(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::parse_file(&text, &path.to_string_lossy(), Some(path.to_path_buf()))
+    }
+
+    /// A static method to parse a level text named after the file stem of `path`.
+    ///
+    /// * `file_path` - the file on the file system, which is watched for hot-reload
+    fn parse_file(text: &str, path: &str, file_path: Option<PathBuf>) -> Result<Self, String> {
+        let mut level = Self::parse(text).map_err(|e| format!("{}:{}", path, e))?;
+        if let Some(n) = Path::new(path).file_stem() {
+            level.name = n.to_string_lossy().to_string();
+        }
+        level.path = file_path;
+        Ok(level)
+    }
+
+    /// A static method to parse a level text.
+    ///
+    /// If it fails, the error message starts with the line number.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut level = Self {
+            name: "default".to_string(),
+            path: None,
+            spawn: Vec3::new(0.0, 1.5, 0.0),
+            floor: (40.0, 40.0),
+            walls: Vec::new(),
+            targets: Vec::new(),
+            models: Vec::new(),
+            props: Vec::new(),
+            skybox: SkyboxSource::default(),
+            camera_path: Vec::new(),
+        };
+        for (i, line) in text.lines().enumerate() {
+            level
+                .parse_line(line)
+                .map_err(|e| format!("{}: {}", i + 1, e))?;
+        }
+
+        // check
+        if level.targets.is_empty() {
+            return Err("0: no target is placed".to_string());
+        }
+        let entities_count = level.walls.len() + 1 + level.props.len() + level.targets.len();
+        if entities_count > world::MAX_INSTANCE_COUNT as usize {
+            return Err(format!(
+                "0: too many entities: {} (max {})",
+                entities_count,
+                world::MAX_INSTANCE_COUNT
+            ));
+        }
+        let is_camera_path_valid = level.camera_path.is_empty()
+            || level.camera_path.iter().any(|n| *n != level.camera_path[0]);
+        if !is_camera_path_valid {
+            return Err("0: camera path needs 2 or more distinct points".to_string());
+        }
+
+        Ok(level)
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+        let tokens = line.split_whitespace().collect::<Vec<&str>>();
+        let (name, values) = (tokens[0], &tokens[1..]);
+        match name {
+            "spawn" => {
+                let n = parse_exact_f32s(values, 3)?;
+                self.spawn = Vec3::new(n[0], n[1], n[2]);
+            }
+            "floor" => {
+                let n = parse_exact_f32s(values, 2)?;
+                self.floor = (n[0], n[1]);
+            }
+            "wall" => {
+                let n = parse_exact_f32s(values, 7)?;
+                self.walls.push(WallDescriptor {
+                    position: Vec3::new(n[0], n[1], n[2]),
+                    rotation: n[3].to_radians(),
+                    scale: Vec3::new(n[4], n[5], n[6]),
+                });
+            }
+            "target" => {
+                let (position, motion) = values.split_at(values.len().min(3));
+                let n = parse_exact_f32s(position, 3)?;
+                self.targets.push(TargetDescriptor {
+                    placement: TargetPlacement::Fixed(Vec3::new(n[0], n[1], n[2])),
+                    motion: parse_motion(motion)?,
+                });
+            }
+            "random_target" => {
+                let (range, motion) = values.split_at(values.len().min(6));
+                let n = parse_exact_f32s(range, 6)?;
+                let min = Vec3::new(n[0], n[1], n[2]);
+                let max = Vec3::new(n[3], n[4], n[5]);
+                self.targets.push(TargetDescriptor {
+                    placement: TargetPlacement::Random {
+                        min: min.min(max),
+                        max: min.max(max),
+                    },
+                    motion: parse_motion(motion)?,
+                });
+            }
+            "prop" => {
+                let Some((model, values)) = values.split_first() else {
+                    return Err("no model is specified for prop".to_string());
+                };
+                let source = ModelSource::parse(model)?;
+                let n = parse_exact_f32s(values, 5)?;
+                // NOTE: The props of the same model share the id.
+                let model_id = match self.models.iter().find(|(_, n)| *n == source) {
+                    Some((n, _)) => *n,
+                    None => {
+                        let model_id = ModelId::allocate();
+                        self.models.push((model_id, source));
+                        model_id
+                    }
+                };
+                self.props.push(PropDescriptor {
+                    model_id,
+                    position: Vec3::new(n[0], n[1], n[2]),
+                    rotation: n[3].to_radians(),
+                    scale: n[4],
+                });
+            }
+            "skybox" => self.skybox = SkyboxSource::parse(values)?,
+            "camera_path" => {
+                let n = parse_exact_f32s(values, 3)?;
+                self.camera_path.push(Vec3::new(n[0], n[1], n[2]));
+            }
+            _ => return Err(format!("unknown element '{}'", name)),
+        }
+        Ok(())
+    }
+
+    /// A method to create the floor entity.
+    pub fn create_floor(&self) -> Floor {
+        Floor::new(self.floor.0, self.floor.1)
+    }
+
+    /// A method to create the wall entities.
+    pub fn create_walls(&self) -> Vec<Wall> {
+        self.walls
+            .iter()
+            .map(|n| Wall::new(n.position, n.rotation, n.scale))
+            .collect()
+    }
+
+    /// A method to get the pairs of the id and the source of the models used by the props.
+    ///
+    /// NOTE: The models must be loaded by the renderer to draw the props.
+    pub fn get_models(&self) -> &[(ModelId, ModelSource)] {
+        &self.models
+    }
+
+    /// A method to get the source of the skybox.
+    ///
+    /// NOTE: The skybox must be loaded by the renderer to be drawn.
+    pub fn get_skybox(&self) -> &SkyboxSource {
+        &self.skybox
+    }
+
+    /// A method to get the asset paths of the model files used by the props.
+    ///
+    /// NOTE: The procedural meshes have no files.
+    pub fn get_model_paths(&self) -> Vec<&str> {
+        self.models
+            .iter()
+            .filter_map(|(_, source)| match source {
+                ModelSource::Asset(path) => Some(path.as_str()),
+                ModelSource::Shape(_) => None,
+            })
+            .collect()
+    }
+
+    /// A method to create the prop entities.
+    ///
+    /// The props are sorted by the model to be drawn together.
+    pub fn create_props(&self) -> Vec<Prop> {
+        self.models
+            .iter()
+            .flat_map(|(model_id, _)| self.props.iter().filter(|n| n.model_id == *model_id))
+            .map(|n| Prop::new(n.model_id, n.position, n.rotation, n.scale))
+            .collect()
+    }
+
+    /// A method to create the target entities.
+    ///
+    /// The positions of randomly placed targets are decided with `random`.
+    pub fn create_targets(&self, random: &mut Random) -> Vec<Target> {
+        self.targets
+            .iter()
+            .map(|n| {
+                let position = match &n.placement {
+                    TargetPlacement::Fixed(n) => *n,
+                    TargetPlacement::Random { min, max } => Vec3::new(
+                        random.range(min.x, max.x),
+                        random.range(min.y, max.y),
+                        random.range(min.z, max.z),
+                    ),
+                };
+                Target::new(position, n.motion.create_update_function(position))
+            })
+            .collect()
+    }
+
+    /// A method to create the camera path for benchmarks.
+    ///
+    /// If the level has no camera path, a rectangle inside the floor at the spawn height is used.
+    pub fn create_camera_path(&self) -> CameraPath {
+        if !self.camera_path.is_empty() {
+            return CameraPath::new(self.camera_path.clone());
+        }
+        let x = self.floor.0 * 0.3;
+        let z = self.floor.1 * 0.3;
+        let y = self.spawn.y;
+        CameraPath::new(Vec::from([
+            Vec3::new(-x, y, -z),
+            Vec3::new(x, y, -z),
+            Vec3::new(x, y, z),
+            Vec3::new(-x, y, z),
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_are_parsed() {
+        let text = "
+            # comment
+            spawn 1 2 3
+            floor 10 20
+            wall 0 4 10 90 10 8 1
+            target 1 2 3
+            target 0 3 0 wave y 2 1
+            random_target 5 1 5 -5 3 -5 hop 40 1 51 79
+            prop cuboid(1,1,1) 0 0 0 45 1
+            prop model/crate.glb 1 0 0 0 1
+            prop cuboid(1,1,1) 2 0 0 0 2
+            skybox equirect image/warehouse.hdr
+            camera_path 0 2 0
+            camera_path 5 2 0
+        ";
+        let level = Level::parse(text).unwrap();
+        assert_eq!(level.name, "default");
+        assert_eq!(level.spawn, Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(level.floor, (10.0, 20.0));
+        assert_eq!(level.create_walls().len(), 1);
+        assert_eq!(
+            *level.get_skybox(),
+            SkyboxSource::Equirect("image/warehouse.hdr".to_string())
+        );
+        assert_eq!(level.camera_path.len(), 2);
+
+        // the props of the same model share the id
+        assert_eq!(level.get_models().len(), 2);
+        assert_eq!(level.get_model_paths(), ["model/crate.glb"]);
+        let props = level.create_props();
+        assert_eq!(props.len(), 3);
+        assert_eq!(props[0].get_model_id(), props[1].get_model_id());
+        assert_ne!(props[1].get_model_id(), props[2].get_model_id());
+
+        // the random targets are placed inside the box
+        let targets = level.create_targets(&mut Random::new(0));
+        assert_eq!(targets.len(), 3);
+        assert_eq!(targets[0].get_position(), Vec3::new(1.0, 2.0, 3.0));
+        let position = targets[2].get_position();
+        assert!((-5.0..=5.0).contains(&position.x), "{}", position);
+        assert!((1.0..=3.0).contains(&position.y), "{}", position);
+        assert!((-5.0..=5.0).contains(&position.z), "{}", position);
+    }
+
+    #[test]
+    fn invalid_levels_are_rejected() {
+        let error = |text: &str| Level::parse(text).err().unwrap();
+
+        // the errors of a line start with the line number
+        let error_message = error("target 0 0 0\nlight 0 0 0\n");
+        assert!(
+            error_message.starts_with("2: unknown element 'light'"),
+            "{}",
+            error_message
+        );
+        let error_message = error("target 0 0\n");
+        assert!(
+            error_message.starts_with("1: 3 values are expected"),
+            "{}",
+            error_message
+        );
+        let error_message = error("\ntarget 0 0 x\n");
+        assert!(
+            error_message.starts_with("2: invalid number 'x'"),
+            "{}",
+            error_message
+        );
+        let error_message = error("target 0 0 0 wave w 2 1\n");
+        assert!(
+            error_message.starts_with("1: unknown axis 'w'"),
+            "{}",
+            error_message
+        );
+        let error_message = error("target 0 0 0 hop 0 1 51 79\n");
+        assert!(
+            error_message.starts_with("1: the interval"),
+            "{}",
+            error_message
+        );
+        let error_message = error("target 0 0 0\nprop\n");
+        assert!(
+            error_message.starts_with("2: no model"),
+            "{}",
+            error_message
+        );
+
+        // the errors of the whole level start with 0
+        assert!(error("spawn 0 0 0\n").starts_with("0: no target"));
+        let text = "target 0 0 0\n".repeat(world::MAX_INSTANCE_COUNT as usize);
+        assert!(error(&text).starts_with("0: too many entities"));
+        let text = "target 0 0 0\ncamera_path 1 2 3\ncamera_path 1 2 3\n";
+        assert!(error(text).starts_with("0: camera path"));
+    }
+}
@@ -1,10 +1,48 @@
 mod game;
 mod title;
 
-use crate::system::{input::InputStates, renderer::RenderRequest};
+use crate::{
+    game::{layout::Layout, level::Level},
+    settings::Settings,
+    system::{
+        asset::AssetManager,
+        audio::{music::MusicId, AudioRequest},
+        input::InputStates,
+        renderer::RenderRequest,
+    },
+    util::font::Font,
+};
 use game::GameSceneState;
+use std::rc::Rc;
 use title::TitleSceneState;
 
+/// A configuration shared by all the games in this process.
+#[derive(Clone)]
+pub struct GameConfig {
+    pub level: Rc<Level>,
+    /// The assets shared with the application.
+    pub assets: Rc<AssetManager>,
+    /// The font of the texts.
+    pub font: Rc<Font>,
+    /// The user settings edited on the settings screen.
+    pub settings: Settings,
+    /// The seed of the random number generator used when a game starts.
+    pub seed: u64,
+    /// The count of frames in benchmark mode.
+    ///
+    /// If it's `Some`, the title is skipped and the camera flies along the camera path of the level
+    /// instead of being controlled by inputs.
+    pub benchmark_frames: Option<u32>,
+}
+
+/// An enum for enumerating requests from scenes to the application.
+pub enum SystemRequest {
+    /// Apply the settings and write them to the settings file.
+    ApplySettings(Settings),
+    Exit,
+}
+
+#[allow(clippy::enum_variant_names, clippy::large_enum_variant)]
 enum Scene {
     /// A dummy scene for waiting for a window to be created.
     ///
@@ -18,47 +56,197 @@ enum Scene {
 /// A scene manager.
 pub struct SceneManager {
     scene: Scene,
+    game_config: GameConfig,
+    /// The music requested for the current scene.
+    music: Option<MusicId>,
 }
 
 impl SceneManager {
     /// A constructor.
     ///
     /// The first scene is prepare scene.
-    pub fn new() -> Self {
+    pub fn new(game_config: GameConfig) -> Self {
         Self {
             scene: Scene::PrepareScene,
+            game_config,
+            music: None,
         }
     }
 
     /// A method to update the scene.
-    pub fn update(&mut self, input_states: &InputStates, render_requests: &mut Vec<RenderRequest>) {
+    ///
+    /// When the music of the scene changes, e.g. a game starts or ends, it requests a crossfade.
+    pub fn update(
+        &mut self,
+        input_states: &InputStates,
+        render_requests: &mut Vec<RenderRequest>,
+        system_requests: &mut Vec<SystemRequest>,
+        audio_requests: &mut Vec<AudioRequest>,
+    ) {
         let new_scene = match &mut self.scene {
             Scene::PrepareScene => return,
-            Scene::TitleScene(n) => n.update(input_states, render_requests),
-            Scene::GameScene(n) => n.update(input_states, render_requests),
+            Scene::TitleScene(n) => n.update(
+                input_states,
+                render_requests,
+                system_requests,
+                audio_requests,
+            ),
+            Scene::GameScene(n) => n.update(input_states, render_requests, audio_requests),
         };
         if let Some(n) = new_scene {
             self.scene = n;
         }
+
+        // crossfade the music
+        let music = match &self.scene {
+            Scene::PrepareScene => None,
+            Scene::TitleScene(_) => Some(MusicId::Title),
+            Scene::GameScene(n) => Some(n.get_music()),
+        };
+        if music != self.music {
+            self.music = music;
+            audio_requests.push(AudioRequest::PlayMusic(music));
+        }
     }
 
-    /// A method to move on to title scene from prepare scene.
+    /// A method to move on to title scene (or game scene in benchmark mode) from prepare scene.
     ///
     /// WARN: If the scene isn't prepare scene, it does nothing.
-    pub fn on_window_created(&mut self, width: f32, height: f32) {
-        match self.scene {
-            Scene::PrepareScene => {
-                self.scene = Scene::TitleScene(TitleSceneState::new(width, height))
+    pub fn on_window_created(&mut self, layout: Layout) {
+        if let Scene::PrepareScene = self.scene {
+            let game_config = self.game_config.clone();
+            self.scene = if game_config.benchmark_frames.is_some() {
+                Scene::GameScene(GameSceneState::new(layout, game_config))
+            } else {
+                Scene::TitleScene(TitleSceneState::new(layout, game_config))
             }
-            _ => return,
         }
     }
 
-    /// A method to resize something that depends on the window size.
-    pub fn resize(&mut self, width: f32, height: f32) {
+    /// A method to get the level of the current scene.
+    pub fn get_level(&self) -> &Rc<Level> {
+        match &self.scene {
+            Scene::PrepareScene => &self.game_config.level,
+            Scene::TitleScene(n) => n.get_level(),
+            Scene::GameScene(n) => n.get_level(),
+        }
+    }
+
+    /// A method to replace the level of the current scene with the reloaded one.
+    ///
+    /// NOTE: The game being played restarts with the level.
+    pub fn reload_level(&mut self, level: Level) {
+        let level = Rc::new(level);
+        match &mut self.scene {
+            Scene::PrepareScene => self.game_config.level = level,
+            Scene::TitleScene(n) => n.reload_level(level),
+            Scene::GameScene(n) => n.reload_level(level),
+        }
+    }
+
+    /// A method to get the name of the current scene.
+    pub fn get_scene_name(&self) -> &'static str {
+        match self.scene {
+            Scene::PrepareScene => "PrepareScene",
+            Scene::TitleScene(_) => "TitleScene",
+            Scene::GameScene(_) => "GameScene",
+        }
+    }
+
+    /// A method to emit all the cached instances of the current scene again at the next update.
+    ///
+    /// It must be called when the renderer is recreated since the new one has no instances.
+    pub fn invalidate(&mut self) {
         match &mut self.scene {
-            Scene::GameScene(n) => n.resize(width, height),
-            _ => (),
+            Scene::PrepareScene => (),
+            Scene::TitleScene(n) => n.invalidate(),
+            Scene::GameScene(n) => n.invalidate(),
         }
     }
+
+    /// A method to lay out something that depends on the window size or the scale factor again.
+    pub fn resize(&mut self, layout: Layout) {
+        match &mut self.scene {
+            Scene::PrepareScene => (),
+            Scene::TitleScene(n) => n.resize(layout),
+            Scene::GameScene(n) => n.resize(layout),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_world_instances(render_requests: &[RenderRequest]) -> usize {
+        render_requests
+            .iter()
+            .filter_map(|n| match n {
+                RenderRequest::UpdateWorldInstances(n) => Some(n.iter().flatten().count()),
+                _ => None,
+            })
+            .sum()
+    }
+
+    fn update(scene_manager: &mut SceneManager) -> Vec<RenderRequest> {
+        let mut render_requests = Vec::new();
+        scene_manager.update(
+            &InputStates::default(),
+            &mut render_requests,
+            &mut Vec::new(),
+            &mut Vec::new(),
+        );
+        render_requests
+    }
+
+    fn count_clear_particles(render_requests: &[RenderRequest]) -> usize {
+        render_requests
+            .iter()
+            .filter(|n| matches!(n, RenderRequest::ClearParticles))
+            .count()
+    }
+
+    /// A function to create a scene manager in benchmark mode, which starts the game scene at once.
+    fn create_game_scene_manager() -> SceneManager {
+        let assets = Rc::new(AssetManager::new(None));
+        let game_config = GameConfig {
+            level: Rc::new(Level::default_level(&assets).unwrap()),
+            font: Rc::new(Font::default_font(&assets).unwrap()),
+            assets,
+            settings: Settings::default(),
+            seed: 0,
+            benchmark_frames: Some(10),
+        };
+        let mut scene_manager = SceneManager::new(game_config);
+        scene_manager.on_window_created(Layout::new(1280.0, 720.0, 1.0));
+        scene_manager
+    }
+
+    #[test]
+    fn instances_are_emitted_again_after_invalidation() {
+        let mut scene_manager = create_game_scene_manager();
+
+        // the cached instances are emitted only at the first frame
+        let first = count_world_instances(&update(&mut scene_manager));
+        let second = count_world_instances(&update(&mut scene_manager));
+        assert!(second < first);
+
+        // the renderer is recreated
+        scene_manager.invalidate();
+        assert_eq!(count_world_instances(&update(&mut scene_manager)), first);
+        assert_eq!(count_world_instances(&update(&mut scene_manager)), second);
+    }
+
+    #[test]
+    fn particles_are_cleared_when_game_starts() {
+        let mut scene_manager = create_game_scene_manager();
+        assert_eq!(count_clear_particles(&update(&mut scene_manager)), 1);
+        assert_eq!(count_clear_particles(&update(&mut scene_manager)), 0);
+
+        // the game restarts with the reloaded level
+        let level = Level::default_level(&scene_manager.game_config.assets).unwrap();
+        scene_manager.reload_level(level);
+        assert_eq!(count_clear_particles(&update(&mut scene_manager)), 1);
+        assert_eq!(count_clear_particles(&update(&mut scene_manager)), 0);
+    }
 }
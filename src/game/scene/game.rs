@@ -1,20 +1,91 @@
-use super::{title::TitleSceneState, Scene};
+use super::{title::TitleSceneState, GameConfig, Scene};
 use crate::{
-    game::entity::{
-        digits::Digits, floor::Floor, message::Message, reticle::Reticle, target::Target,
-        wall::Wall,
+    game::{
+        camera_path::CameraPath,
+        debug_overlay::SCENE_UI_INSTANCE_LIMIT,
+        emitter,
+        entity::{
+            crosshair::Crosshair,
+            destruction::Destruction,
+            digits::Digits,
+            floor::Floor,
+            message::Message,
+            popup::Popup,
+            prop::Prop,
+            target::Target,
+            text::{Text, TextAlign},
+            wall::Wall,
+        },
+        layout::{Anchor, Layout, Placement},
+        level::Level,
+        widget::WidgetState,
     },
     system::{
+        audio::{music::MusicId, sound::SoundId, AudioRequest},
         input::{InputStates, PressingInput},
         renderer::{
             model::ModelId,
-            shader::{ui::DrawUiDescriptor, world::DrawWorldDescriptor},
+            shader::{
+                particle::{DrawParticlesDescriptor, Particle},
+                ui::{DrawUiDescriptor, UiTextureId},
+                world::DrawWorldDescriptor,
+            },
             RenderRequest,
         },
     },
-    util::camera::CameraController,
+    util::{camera::CameraController, instance::InstanceController, random::Random},
 };
-use glam::{Vec3, Vec4};
+use glam::{Vec2, Vec3, Vec4};
+use std::{mem, rc::Rc};
+
+/// The width (logical px) of a character of the score.
+const SCORE_WIDTH: f32 = 100.0;
+/// The offset (logical px) of the end messages from the center of the window.
+const END_MESSAGE_OFFSET: f32 = 120.0;
+/// The score added when a target is shot.
+const HIT_BONUS: u32 = 100;
+/// The position of the muzzle relative to the camera.
+const MUZZLE_OFFSET: Vec3 = Vec3::new(0.12, -0.1, 0.5);
+/// The frames between footsteps while moving.
+const FOOTSTEP_INTERVAL: u32 = 18;
+
+/// A function to create the score placed at the top right corner.
+fn create_score_ui(layout: &Layout, score: u32) -> Digits {
+    let placement = Placement::new(Anchor::TopRight, Anchor::TopRight, Vec2::ZERO);
+    let rect = layout.place(&placement, Vec2::ZERO);
+    Digits::new(rect.left, rect.top, layout.scale(SCORE_WIDTH), score)
+}
+
+/// A function to create the text of the remaining targets shown under the score.
+fn create_targets_text(
+    game_config: &GameConfig,
+    layout: &Layout,
+    count: u32,
+    target_count: u32,
+) -> Text {
+    // NOTE: The height of the score is 1.25 times its width of a character.
+    Text::placed(
+        &game_config.font,
+        layout,
+        &Placement::new(
+            Anchor::TopRight,
+            Anchor::TopRight,
+            Vec2::new(0.0, -SCORE_WIDTH * 1.25 - 8.0),
+        ),
+        1.0,
+        Vec4::new(1.0, 1.0, 1.0, 0.9),
+        TextAlign::Right,
+        &format!("Targets {} / {}", count, target_count),
+    )
+}
+
+/// An enum for enumerating the events emitted when a shot is resolved.
+enum ShotEvent {
+    /// The player fired from the muzzle toward the direction.
+    Fired { position: Vec3, direction: Vec3 },
+    /// A target at the position was shot. It's emitted for each target.
+    Hit { position: Vec3, direction: Vec3 },
+}
 
 enum State {
     Game,
@@ -24,125 +95,157 @@ enum State {
 /// A states of game scene.
 pub struct GameSceneState {
     state: State,
-    width: f32,
-    height: f32,
+    layout: Layout,
+    game_config: GameConfig,
+    /// The camera path in benchmark mode.
+    camera_path: Option<CameraPath>,
+    frame_count: u32,
     camera_controller: CameraController,
     floor: Floor,
-    walls: [Wall; 8],
+    walls: Vec<Wall>,
+    props: Vec<Prop>,
     targets: Vec<Target>,
-    reticle: Reticle,
+    /// The animations of the shot targets.
+    destructions: Vec<Destruction>,
+    crosshair: Crosshair,
     message: Option<Message>,
     indication: Option<Message>,
     score_ui: Digits,
     score: u32,
+    /// The count of targets when the game starts.
+    target_count: u32,
+    shot_count: u32,
+    targets_text: Text,
+    result_text: Option<Text>,
+    /// The scores rising from the shot targets.
+    popups: Vec<Popup>,
+    /// The particles spawned in this frame.
+    particles: Vec<Particle>,
+    /// The sounds requested in this frame.
+    sounds: Vec<AudioRequest>,
+    /// The frames since the player started moving.
+    moving_frames: u32,
+    random: Random,
+    /// Whether the pause menu is open.
+    is_paused: bool,
+    widget_state: WidgetState,
 }
 
 impl GameSceneState {
     /// A constructor.
-    pub fn new(width: f32, height: f32) -> Self {
+    ///
+    /// The entities are created from the level of `game_config`.
+    pub fn new(layout: Layout, game_config: GameConfig) -> Self {
         // create a camera controller
-        let mut camera_controller = CameraController::default();
-        camera_controller.width = width;
-        camera_controller.height = height;
-        camera_controller.position.y = 1.5;
-        camera_controller.position.z = -35.0;
+        let camera_controller = CameraController {
+            width: layout.width,
+            height: layout.height,
+            position: game_config.level.spawn,
+            ..Default::default()
+        };
 
         // create entities
-        let floor = Floor::new(40.0, 80.0);
-        let walls = [
-            // outer
-            Wall::new(
-                Vec3::new(0.0, 4.0, 40.0),
-                0.0f32.to_radians(),
-                Vec3::new(40.0, 8.0, 1.0),
-            ),
-            Wall::new(
-                Vec3::new(0.0, 4.0, -40.0),
-                180.0f32.to_radians(),
-                Vec3::new(40.0, 8.0, 1.0),
-            ),
-            Wall::new(
-                Vec3::new(20.0, 4.0, 0.0),
-                90.0f32.to_radians(),
-                Vec3::new(80.0, 8.0, 1.0),
-            ),
-            Wall::new(
-                Vec3::new(-20.0, 4.0, 0.0),
-                -90.0f32.to_radians(),
-                Vec3::new(80.0, 8.0, 1.0),
-            ),
-            // inner
-            Wall::new(
-                Vec3::new(-2.0, 1.5, -20.0),
-                0.0f32.to_radians(),
-                Vec3::new(36.0, 3.0, 1.0),
-            ),
-            Wall::new(
-                Vec3::new(2.0, 1.5, 20.0),
-                0.0f32.to_radians(),
-                Vec3::new(36.0, 3.0, 1.0),
-            ),
-            Wall::new(
-                Vec3::new(11.0, 3.5, 0.0),
-                0.0f32.to_radians(),
-                Vec3::new(18.0, 7.0, 1.0),
-            ),
-            Wall::new(
-                Vec3::new(-11.0, 3.5, 0.0),
-                0.0f32.to_radians(),
-                Vec3::new(18.0, 7.0, 1.0),
-            ),
-        ];
-
-        // create targets
-        let targets = Vec::from([
-            Target::new(Vec3::new(15.0, 2.5, -15.0), Box::new(|_, _| ())),
-            Target::new(
-                Vec3::new(0.0, 2.0, 0.0),
-                Box::new(|n: &mut Vec3, i: u32| n.y = (i as f32).to_radians().sin() * 2.0 + 3.0),
-            ),
-            Target::new(Vec3::new(15.0, 3.0, 15.0), Box::new(|_, _| ())),
-            Target::new(
-                Vec3::new(0.0, 2.0, 35.0),
-                Box::new(|n: &mut Vec3, i: u32| {
-                    *n = Vec3::new(
-                        ((i / 40) as f32 * 51.0).to_radians().sin(),
-                        ((i / 40) as f32 * 79.0).to_radians().sin() + 2.5,
-                        35.0,
-                    )
-                }),
-            ),
-            Target::new(
-                Vec3::new(25.0, 30.0, -20.0),
-                Box::new(|n: &mut Vec3, i: u32| n.z = (i as f32).to_radians().sin() * 2.0 + -20.0),
-            ),
-        ]);
+        let floor = game_config.level.create_floor();
+        let walls = game_config.level.create_walls();
+        let props = game_config.level.create_props();
+        let mut random = Random::new(game_config.seed);
+        let targets = game_config.level.create_targets(&mut random);
+        let camera_path = game_config
+            .benchmark_frames
+            .map(|_| game_config.level.create_camera_path());
 
         // create uis
-        let reticle = Reticle::new();
-        let score_ui = Digits::new(width / 2.0, height / 2.0, 100.0, 1200);
+        let crosshair = Crosshair::new(&game_config.settings.crosshair);
+        let score_ui = create_score_ui(&layout, 1200);
+        let target_count = targets.len() as u32;
+        let targets_text = create_targets_text(&game_config, &layout, target_count, target_count);
 
         // finish
         Self {
             state: State::Game,
-            width,
-            height,
+            layout,
+            game_config,
+            camera_path,
+            frame_count: 0,
             camera_controller,
             floor,
             walls,
+            props,
             targets,
-            reticle,
+            destructions: Vec::new(),
+            crosshair,
             message: None,
             indication: None,
             score_ui,
             score: 1200,
+            target_count,
+            shot_count: 0,
+            targets_text,
+            result_text: None,
+            popups: Vec::new(),
+            particles: Vec::new(),
+            sounds: Vec::new(),
+            moving_frames: 0,
+            random,
+            is_paused: false,
+            widget_state: WidgetState::new(),
         }
     }
 
-    /// A method to resize the camera width and height.
-    pub fn resize(&mut self, width: f32, height: f32) {
-        self.camera_controller.width = width;
-        self.camera_controller.height = height;
+    /// A method to get the music of the current state.
+    pub fn get_music(&self) -> MusicId {
+        match self.state {
+            State::Game => MusicId::Game,
+            State::End => MusicId::End,
+        }
+    }
+
+    /// A method to get the level being played.
+    pub fn get_level(&self) -> &Rc<Level> {
+        &self.game_config.level
+    }
+
+    /// A method to restart the game with the reloaded level.
+    pub fn reload_level(&mut self, level: Rc<Level>) {
+        let mut game_config = self.game_config.clone();
+        game_config.level = level;
+        *self = Self::new(self.layout, game_config);
+    }
+
+    /// A method to resize the camera and lay out the ui again depending on the window size and the scale factor.
+    pub fn resize(&mut self, layout: Layout) {
+        self.layout = layout;
+        self.camera_controller.width = layout.width;
+        self.camera_controller.height = layout.height;
+        self.score_ui = create_score_ui(&layout, self.score);
+        self.update_targets_text();
+        if let State::End = self.state {
+            self.place_end_messages();
+        }
+    }
+
+    /// A method to emit all the cached instances again at the next update.
+    ///
+    /// NOTE: The targets, the destructions, the popups and the widgets are emitted every frame.
+    pub fn invalidate(&mut self) {
+        self.floor.invalidate();
+        for n in &mut self.walls {
+            n.invalidate();
+        }
+        for n in &mut self.props {
+            n.invalidate();
+        }
+        if let Some(n) = &mut self.message {
+            n.invalidate();
+        }
+        if let Some(n) = &mut self.indication {
+            n.invalidate();
+        }
+        self.score_ui.invalidate();
+        self.targets_text.invalidate();
+        if let Some(n) = &mut self.result_text {
+            n.invalidate();
+        }
     }
 
     /// A method to update the scene.
@@ -150,47 +253,38 @@ impl GameSceneState {
         &mut self,
         input_states: &InputStates,
         render_requests: &mut Vec<RenderRequest>,
+        audio_requests: &mut Vec<AudioRequest>,
     ) -> Option<Scene> {
-        // rotate camera
-        let moving = &input_states.moving;
-        self.camera_controller.rotate(
-            moving.x as f32 / self.camera_controller.width * 90.0f32.to_radians(),
-            moving.y as f32 / self.camera_controller.height * 90.0f32.to_radians(),
-        );
-
-        // move camera
-        let pressing = &input_states.pressing;
-        let r = pressing.get(&PressingInput::KeyD) > 0;
-        let l = pressing.get(&PressingInput::KeyA) > 0;
-        let f = pressing.get(&PressingInput::KeyW) > 0;
-        let b = pressing.get(&PressingInput::KeyS) > 0;
-        let rl = r as i32 - l as i32;
-        let fb = f as i32 - b as i32;
-        if rl != 0 || fb != 0 {
-            // create a correct velocity
-            let mut velocity = self
-                .camera_controller
-                .align_to_direction(Vec3::new(rl as f32, 0.0, fb as f32).normalize() * 0.25);
-
-            // check wall collisions
-            for n in self.walls.iter() {
-                velocity = n.check_collision(self.camera_controller.position, velocity);
-            }
-
-            // move
-            self.camera_controller.position += velocity;
+        // open or close the pause menu
+        // NOTE: The game can't be paused in benchmark mode.
+        if self.camera_path.is_none() && input_states.pressing.get(&PressingInput::Escape) == 1 {
+            self.is_paused = !self.is_paused;
+            self.widget_state.reset();
         }
 
-        // do depends on state
-        let next_scene = match self.state {
-            State::Game => self.update_game(input_states),
-            State::End => self.update_end(input_states),
+        let mut next_scene = if self.is_paused {
+            None
+        } else if let Some(n) = &self.camera_path {
+            // fly along the camera path
+            let frames = self.game_config.benchmark_frames.unwrap_or(1);
+            n.apply(
+                &mut self.camera_controller,
+                self.frame_count as f32 / frames as f32,
+            );
+            None
+        } else {
+            self.update_player(input_states)
         };
+        self.frame_count += 1;
 
-        // update targets
+        // update targets and effects
         // NOTE: Update targets after player's shooting is processed.
-        for n in &mut self.targets {
-            n.update();
+        if !self.is_paused {
+            for n in &mut self.targets {
+                n.update();
+            }
+            self.destructions.retain_mut(|n| n.update());
+            self.popups.retain_mut(|n| n.update());
         }
 
         // collect update requests
@@ -199,11 +293,16 @@ impl GameSceneState {
             update_world_requests.push(n.get_instance_controller());
         }
         update_world_requests.push(self.floor.get_instance_controller());
+        for n in &mut self.props {
+            update_world_requests.push(n.get_instance_controller());
+        }
         for n in &mut self.targets {
             update_world_requests.push(Some(n.get_instance_controller()));
         }
+        for n in &self.destructions {
+            update_world_requests.push(Some(n.get_instance_controller()));
+        }
         let mut update_ui_requests = Vec::new();
-        update_ui_requests.push(self.reticle.get_instance_controller());
         if let Some(n) = &mut self.message {
             update_ui_requests.push(n.get_instance_controller());
         }
@@ -213,37 +312,191 @@ impl GameSceneState {
         for n in self.score_ui.get_instance_controllers() {
             update_ui_requests.push(n);
         }
+        let ui_entities_count = update_ui_requests.len() as u32;
+        update_ui_requests.extend(self.targets_text.get_instance_controllers());
+        if let Some(n) = &mut self.result_text {
+            update_ui_requests.extend(n.get_instance_controllers());
+        }
+        // NOTE: The popups are created every frame, so the following indices are always updated.
+        for n in &self.popups {
+            let text = n.get_text(
+                &self.game_config.font,
+                &self.layout,
+                &self.camera_controller,
+            );
+            if let Some(mut n) = text {
+                update_ui_requests.extend(n.get_instance_controllers());
+            }
+        }
+        if self.is_paused {
+            let (widget_instance_controllers, pause_scene) = self.update_pause_menu(input_states);
+            update_ui_requests.extend(widget_instance_controllers);
+            next_scene = next_scene.or(pause_scene);
+        }
 
         // define entities count on the world
         let static_entities_count = self.walls.len() as u32 + 1;
-        let all_entities_count = static_entities_count + self.targets.len() as u32;
+        let props_end = static_entities_count + self.props.len() as u32;
+        let all_entities_count =
+            props_end + self.targets.len() as u32 + self.destructions.len() as u32;
 
-        // get uis count
-        let ui_entities_count = update_ui_requests.len();
+        // get texts count
+        let all_uis_count = update_ui_requests.len() as u32;
+        debug_assert!(
+            update_ui_requests.len() <= SCENE_UI_INSTANCE_LIMIT,
+            "the game scene uses {} UI instances",
+            update_ui_requests.len()
+        );
 
         // draw
         render_requests.push(RenderRequest::UpdateCamera(self.camera_controller.clone()));
         render_requests.push(RenderRequest::DrawSkybox);
         render_requests.push(RenderRequest::UpdateWorldInstances(update_world_requests));
+        let mut instance_indices = Vec::from([(ModelId::CUBE, 0, static_entities_count)]);
+        // NOTE: The props of the same model are consecutive.
+        for (i, n) in self.props.iter().enumerate() {
+            let index = static_entities_count + i as u32;
+            match instance_indices.last_mut() {
+                Some((id, _, end)) if *id == n.get_model_id() => *end = index + 1,
+                _ => instance_indices.push((n.get_model_id(), index, index + 1)),
+            }
+        }
+        instance_indices.push((ModelId::SPHERE, props_end, all_entities_count));
         render_requests.push(RenderRequest::DrawWorld(DrawWorldDescriptor {
-            instance_indices: Vec::from([
-                (ModelId::Cube, 0, static_entities_count),
-                (ModelId::Sphere, static_entities_count, all_entities_count),
-            ]),
+            instance_indices,
         }));
+        // NOTE: The particles of the previous game are left in the pipeline when the game (re)starts.
+        if self.frame_count == 1 {
+            render_requests.push(RenderRequest::ClearParticles);
+        }
+        if !self.particles.is_empty() {
+            render_requests.push(RenderRequest::SpawnParticles(mem::take(
+                &mut self.particles,
+            )));
+        }
+        // NOTE: The particles stop while the game is paused.
+        render_requests.push(RenderRequest::DrawParticles(DrawParticlesDescriptor {
+            elapsed: if self.is_paused { 0.0 } else { 1.0 },
+        }));
+        // NOTE: The crosshair is hidden behind the pause menu.
+        if !self.is_paused {
+            render_requests.push(RenderRequest::DrawCrosshair(
+                self.crosshair.get_descriptor(&self.layout, Vec2::ZERO),
+            ));
+        }
         render_requests.push(RenderRequest::UpdateUiInstances(update_ui_requests));
         render_requests.push(RenderRequest::DrawUi(DrawUiDescriptor {
             clear_color: None,
-            instance_indices: Vec::from([(0, ui_entities_count as u32)]),
+            instance_indices: Vec::from([
+                (UiTextureId::Ui, 0, ui_entities_count),
+                (UiTextureId::Font, ui_entities_count, all_uis_count),
+            ]),
         }));
 
+        // play sounds
+        audio_requests.push(AudioRequest::UpdateListener(self.camera_controller.clone()));
+        audio_requests.append(&mut self.sounds);
+
         next_scene
     }
 
+    /// A method to declare the widgets of the pause menu.
+    ///
+    /// It returns the instances of the widgets and the next scene.
+    fn update_pause_menu(
+        &mut self,
+        input_states: &InputStates,
+    ) -> (Vec<Option<InstanceController>>, Option<Scene>) {
+        let mut next_scene = None;
+        let font = Rc::clone(&self.game_config.font);
+        let mut widgets = self.widget_state.begin(
+            &font,
+            input_states,
+            &self.layout,
+            &Placement::new(Anchor::Center, Anchor::Center, Vec2::ZERO),
+            360.0,
+        );
+        widgets.label("Paused", 1.0);
+        if widgets.button("Resume") {
+            self.is_paused = false;
+        }
+        if widgets.button("Restart") {
+            next_scene = Some(Scene::GameScene(GameSceneState::new(
+                self.layout,
+                self.game_config.clone(),
+            )));
+        }
+        if widgets.button("Quit to Title") {
+            next_scene = Some(Scene::TitleScene(TitleSceneState::new(
+                self.layout,
+                self.game_config.clone(),
+            )));
+        }
+        let instance_controllers = widgets.end();
+        if let Some(n) = self.widget_state.take_sound() {
+            self.sounds.push(AudioRequest::Play(n));
+        }
+        (instance_controllers, next_scene)
+    }
+
+    /// A method to update the camera and the state by the player's inputs.
+    fn update_player(&mut self, input_states: &InputStates) -> Option<Scene> {
+        // rotate camera
+        let moving = &input_states.moving;
+        let sensitivity = self.game_config.settings.mouse_sensitivity;
+        self.camera_controller.rotate(
+            moving.x as f32 / self.camera_controller.width * 90.0f32.to_radians() * sensitivity,
+            moving.y as f32 / self.camera_controller.height * 90.0f32.to_radians() * sensitivity,
+        );
+
+        // move camera
+        let pressing = &input_states.pressing;
+        let r = pressing.get(&PressingInput::KeyD) > 0;
+        let l = pressing.get(&PressingInput::KeyA) > 0;
+        let f = pressing.get(&PressingInput::KeyW) > 0;
+        let b = pressing.get(&PressingInput::KeyS) > 0;
+        let rl = r as i32 - l as i32;
+        let fb = f as i32 - b as i32;
+        self.crosshair.update(rl != 0 || fb != 0);
+        if rl != 0 || fb != 0 {
+            // step
+            if self.moving_frames.is_multiple_of(FOOTSTEP_INTERVAL) {
+                self.sounds.push(AudioRequest::Play(SoundId::Footstep));
+            }
+            self.moving_frames += 1;
+
+            // create a correct velocity
+            let mut velocity = self
+                .camera_controller
+                .align_to_direction(Vec3::new(rl as f32, 0.0, fb as f32).normalize() * 0.25);
+
+            // check wall collisions
+            for n in self.walls.iter() {
+                velocity = n.check_collision(self.camera_controller.position, velocity);
+            }
+
+            // move
+            self.camera_controller.position += velocity;
+        } else {
+            self.moving_frames = 0;
+        }
+
+        // do depends on state
+        match self.state {
+            State::Game => self.update_game(input_states),
+            State::End => self.update_end(input_states),
+        }
+    }
+
     fn update_game(&mut self, input_states: &InputStates) -> Option<Scene> {
         // decrement score
         self.score = if self.score == 0 { 0 } else { self.score - 1 };
         self.score_ui.set_number(self.score);
+        // NOTE: The texts follow the score in the instance buffer,
+        //       so their indices are shifted when the count of digits decreases.
+        if (self.score + 1).to_string().len() != self.score.to_string().len() {
+            self.update_targets_text();
+        }
 
         // shoot
         if input_states.pressing.get(&PressingInput::MouseLeft) == 1 {
@@ -251,42 +504,138 @@ impl GameSceneState {
                 .camera_controller
                 .rotation
                 .mul_vec3(Vec3::new(0.0, 0.0, 1.0));
-            self.targets
-                .retain(|n| !n.check_shot(self.camera_controller.position, direction));
+            let position = self.camera_controller.position;
+            let mut events = Vec::from([ShotEvent::Fired {
+                position: position + self.camera_controller.rotation.mul_vec3(MUZZLE_OFFSET),
+                direction,
+            }]);
+            self.targets.retain(|n| {
+                let is_shot = n.check_shot(position, direction);
+                if is_shot {
+                    events.push(ShotEvent::Hit {
+                        position: n.get_position(),
+                        direction,
+                    });
+                }
+                !is_shot
+            });
+            for n in events {
+                self.handle_shot_event(n);
+            }
         }
 
         // check game clear or over
         if self.targets.is_empty() || self.score == 0 {
-            let uv = if self.targets.is_empty() {
-                Vec4::new(0.0, 0.375, 0.8, 0.125)
-            } else {
-                Vec4::new(0.0, 0.5, 0.7, 0.125)
-            };
-            let y = self.height * 0.25;
-            let message = Message::new(0.0, y, self.width * 0.3, uv);
-            let mut indication = Message::new(
-                0.0,
-                0.0,
-                self.width * 0.35,
-                Vec4::new(0.0, 0.25, 1.0, 0.125),
-            );
-            indication.set_position(
-                0.0,
-                y - message.get_height() / 2.0 - indication.get_height() / 2.0,
-            );
-            self.message = Some(message);
-            self.indication = Some(indication);
+            self.place_end_messages();
             self.state = State::End;
         }
 
         None
     }
 
+    /// A method to apply an event of a shot to the state and start its feedback.
+    fn handle_shot_event(&mut self, event: ShotEvent) {
+        match event {
+            ShotEvent::Fired {
+                position,
+                direction,
+            } => {
+                self.shot_count += 1;
+                self.sounds
+                    .push(AudioRequest::PlayAt(SoundId::Shot, position));
+                self.particles.extend(emitter::MUZZLE_FLASH.emit(
+                    &mut self.random,
+                    position,
+                    direction,
+                ));
+            }
+            ShotEvent::Hit {
+                position,
+                direction,
+            } => {
+                self.score += HIT_BONUS;
+                self.score_ui.set_number(self.score);
+                self.update_targets_text();
+                self.crosshair.on_hit();
+                self.sounds
+                    .push(AudioRequest::PlayAt(SoundId::Hit, position));
+                self.destructions.push(Destruction::new(position));
+                self.particles
+                    .extend(emitter::IMPACT.emit(&mut self.random, position, -direction));
+                self.particles
+                    .extend(emitter::EXPLOSION.emit(&mut self.random, position, Vec3::Y));
+                self.popups
+                    .push(Popup::new(position, &format!("+{}", HIT_BONUS)));
+            }
+        }
+    }
+
+    /// A method to create the messages shown at the end of the game.
+    ///
+    /// If all the targets are shot, it shows game clear, otherwise game over.
+    fn place_end_messages(&mut self) {
+        let uv = if self.targets.is_empty() {
+            Vec4::new(0.0, 0.375, 0.8, 0.125)
+        } else {
+            Vec4::new(0.0, 0.5, 0.7, 0.125)
+        };
+        // NOTE: The message is above the offset and the others are below it.
+        let offset = Vec2::new(0.0, END_MESSAGE_OFFSET);
+        let message = Message::placed(
+            &self.layout,
+            &Placement::new(Anchor::Center, Anchor::Bottom, offset),
+            480.0,
+            uv,
+        );
+        let indication = Message::placed(
+            &self.layout,
+            &Placement::new(Anchor::Center, Anchor::Top, offset),
+            560.0,
+            Vec4::new(0.0, 0.25, 1.0, 0.125),
+        );
+
+        // create a result text
+        let hit_count = self.target_count - self.targets.len() as u32;
+        let accuracy = if self.shot_count > 0 {
+            hit_count as f32 / self.shot_count as f32 * 100.0
+        } else {
+            0.0
+        };
+        let result_offset = offset.y - indication.get_height() / self.layout.scale_factor - 16.0;
+        let result_text = Text::placed(
+            &self.game_config.font,
+            &self.layout,
+            &Placement::new(Anchor::Center, Anchor::Top, Vec2::new(0.0, result_offset)),
+            1.0,
+            Vec4::new(1.0, 1.0, 1.0, 1.0),
+            TextAlign::Center,
+            &format!(
+                "Score {}\nShots {}  Hits {}  Accuracy {:.0}%",
+                self.score, self.shot_count, hit_count, accuracy
+            ),
+        );
+
+        self.message = Some(message);
+        self.indication = Some(indication);
+        self.result_text = Some(result_text);
+        // NOTE: The texts follow the messages in the instance buffer, so their indices are shifted.
+        self.update_targets_text();
+    }
+
+    fn update_targets_text(&mut self) {
+        self.targets_text = create_targets_text(
+            &self.game_config,
+            &self.layout,
+            self.targets.len() as u32,
+            self.target_count,
+        );
+    }
+
     fn update_end(&mut self, input_states: &InputStates) -> Option<Scene> {
         if input_states.pressing.get(&PressingInput::KeyE) == 1 {
             Some(Scene::TitleScene(TitleSceneState::new(
-                self.width,
-                self.height,
+                self.layout,
+                self.game_config.clone(),
             )))
         } else {
             None
@@ -1,59 +1,325 @@
-use super::{game::GameSceneState, Scene};
+use super::{game::GameSceneState, GameConfig, Scene, SystemRequest};
 use crate::{
-    game::entity::message::Message,
+    game::{
+        debug_overlay::SCENE_UI_INSTANCE_LIMIT,
+        entity::{crosshair::Crosshair, message::Message},
+        layout::{Anchor, Layout, Placement},
+        level::{self, Level},
+        widget::WidgetState,
+    },
+    settings::{Settings, WindowMode},
     system::{
+        audio::AudioRequest,
         input::{InputStates, PressingInput},
-        renderer::{shader::ui::DrawUiDescriptor, RenderRequest},
+        renderer::{
+            shader::ui::{DrawUiDescriptor, UiTextureId},
+            RenderRequest,
+        },
     },
 };
-use glam::Vec4;
+use glam::{Vec2, Vec4};
+use std::rc::Rc;
+use wgpu::PresentMode;
+
+/// The title of the game shown on the main screen.
+const TITLE: &str = "WebGPU Simple FPS";
+/// The width (logical px) of the menu column.
+const MENU_WIDTH: f32 = 420.0;
+/// The count of visible rows of the level list.
+const LEVEL_LIST_ROWS: usize = 6;
+/// The offset (logical px) of the crosshair preview from the center.
+const CROSSHAIR_PREVIEW_OFFSET: f32 = MENU_WIDTH / 2.0 + 120.0;
+/// The names and the sRGB colors selectable for the crosshair.
+const CROSSHAIR_COLORS: [(&str, (u8, u8, u8)); 6] = [
+    ("Green", (0, 255, 0)),
+    ("White", (255, 255, 255)),
+    ("Yellow", (255, 255, 0)),
+    ("Cyan", (0, 255, 255)),
+    ("Red", (255, 0, 0)),
+    ("Magenta", (255, 0, 255)),
+];
+
+fn create_message(layout: &Layout) -> Message {
+    Message::placed(
+        layout,
+        &Placement::new(Anchor::Bottom, Anchor::Bottom, Vec2::new(0.0, 32.0)),
+        480.0,
+        Vec4::new(0.0, 0.125, 1.0, 0.125),
+    )
+}
+
+/// A function to get the fullscreen mode used when the fullscreen checkbox is switched on.
+fn get_fullscreen_mode(settings: &Settings) -> WindowMode {
+    match settings.window_mode {
+        WindowMode::Windowed => WindowMode::Borderless,
+        n => n,
+    }
+}
+
+/// An enum for enumerating the screens in title scene.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Screen {
+    Main,
+    LevelSelect,
+    Settings,
+    Crosshair,
+}
 
 /// A states of title scene.
 pub struct TitleSceneState {
-    width: f32,
-    height: f32,
+    layout: Layout,
+    game_config: GameConfig,
     message: Message,
+    screen: Screen,
+    widget_state: WidgetState,
+    /// The pairs of the name and the asset path of the level files.
+    levels: Vec<(String, String)>,
+    selected_level: usize,
+    /// The error of the last level loading.
+    level_error: Option<String>,
+    /// The settings being edited on the settings screen.
+    settings: Settings,
+    /// The fullscreen mode restored when the fullscreen checkbox is switched on again.
+    fullscreen_mode: WindowMode,
 }
 
 impl TitleSceneState {
     /// A constructor.
-    pub fn new(width: f32, height: f32) -> Self {
+    pub fn new(layout: Layout, game_config: GameConfig) -> Self {
         Self {
-            width,
-            height,
-            message: Message::new(
-                0.0,
-                -height * 0.25,
-                width * 0.3,
-                Vec4::new(0.0, 0.125, 1.0, 0.125),
-            ),
+            layout,
+            settings: game_config.settings.clone(),
+            fullscreen_mode: get_fullscreen_mode(&game_config.settings),
+            game_config,
+            message: create_message(&layout),
+            screen: Screen::Main,
+            widget_state: WidgetState::new(),
+            levels: Vec::new(),
+            selected_level: 0,
+            level_error: None,
         }
     }
 
+    /// A method to lay out the ui again depending on the window size and the scale factor.
+    pub fn resize(&mut self, layout: Layout) {
+        self.layout = layout;
+        self.message = create_message(&layout);
+    }
+
+    /// A method to get the level selected.
+    pub fn get_level(&self) -> &Rc<Level> {
+        &self.game_config.level
+    }
+
+    /// A method to replace the level selected with the reloaded one.
+    pub fn reload_level(&mut self, level: Rc<Level>) {
+        self.game_config.level = level;
+    }
+
+    /// A method to emit all the cached instances again at the next update.
+    ///
+    /// NOTE: The widgets are emitted every frame.
+    pub fn invalidate(&mut self) {
+        self.message.invalidate();
+    }
+
     /// A method to update the scene.
     pub fn update(
         &mut self,
         input_states: &InputStates,
         render_requests: &mut Vec<RenderRequest>,
+        system_requests: &mut Vec<SystemRequest>,
+        audio_requests: &mut Vec<AudioRequest>,
     ) -> Option<Scene> {
-        // update
-        let next_scene = if input_states.pressing.get(&PressingInput::KeyE) == 1 {
-            Some(Scene::GameScene(GameSceneState::new(
-                self.width,
-                self.height,
-            )))
-        } else {
-            None
-        };
+        let is_escaped = input_states.pressing.get(&PressingInput::Escape) == 1;
+        let mut next_scene = None;
+        let mut next_screen = None;
+
+        // declare widgets
+        let font = Rc::clone(&self.game_config.font);
+        let mut widgets = self.widget_state.begin(
+            &font,
+            input_states,
+            &self.layout,
+            &Placement::new(Anchor::Center, Anchor::Center, Vec2::new(0.0, 40.0)),
+            MENU_WIDTH,
+        );
+        match self.screen {
+            Screen::Main => {
+                widgets.label(TITLE, 1.5);
+                widgets.label(&format!("Level: {}", self.game_config.level.name), 0.75);
+                widgets.space(16.0);
+                if widgets.button("Start") {
+                    next_scene = Some(Scene::GameScene(GameSceneState::new(
+                        self.layout,
+                        self.game_config.clone(),
+                    )));
+                }
+                if widgets.button("Select Level") {
+                    self.levels = level::list_levels(&self.game_config.assets);
+                    self.level_error = None;
+                    next_screen = Some(Screen::LevelSelect);
+                }
+                if widgets.button("Settings") {
+                    self.settings = self.game_config.settings.clone();
+                    self.fullscreen_mode = get_fullscreen_mode(&self.settings);
+                    next_screen = Some(Screen::Settings);
+                }
+                if widgets.button("Quit") || is_escaped {
+                    system_requests.push(SystemRequest::Exit);
+                }
+            }
+            Screen::LevelSelect => {
+                widgets.label("Select Level", 1.0);
+                if self.levels.is_empty() {
+                    widgets.label("No level is found.", 0.75);
+                } else {
+                    let names = self
+                        .levels
+                        .iter()
+                        .map(|(n, _)| n.clone())
+                        .collect::<Vec<String>>();
+                    if widgets.list(&names, &mut self.selected_level, LEVEL_LIST_ROWS) {
+                        let (_, path) = &self.levels[self.selected_level];
+                        match Level::load_asset(&self.game_config.assets, path) {
+                            Ok(n) => {
+                                self.game_config.level = Rc::new(n);
+                                next_screen = Some(Screen::Main);
+                            }
+                            Err(e) => {
+                                warn!("TitleSceneState.update", "failed to load a level: {}", e);
+                                self.level_error = Some(e);
+                            }
+                        }
+                    }
+                }
+                if let Some(n) = &self.level_error {
+                    widgets.label(n, 0.5);
+                }
+                if widgets.button("Back") || is_escaped {
+                    next_screen = Some(Screen::Main);
+                }
+            }
+            Screen::Settings => {
+                widgets.label("Settings", 1.0);
+                let mut is_fullscreen = self.settings.window_mode != WindowMode::Windowed;
+                if widgets.checkbox("Fullscreen", &mut is_fullscreen) {
+                    // NOTE: Keep the fullscreen mode, e.g. exclusive, to restore it.
+                    self.settings.window_mode = if is_fullscreen {
+                        self.fullscreen_mode
+                    } else {
+                        self.fullscreen_mode = get_fullscreen_mode(&self.settings);
+                        WindowMode::Windowed
+                    };
+                }
+                let mut is_vsync = self.settings.present_mode == PresentMode::Fifo;
+                if widgets.checkbox("VSync", &mut is_vsync) {
+                    self.settings.present_mode = if is_vsync {
+                        PresentMode::Fifo
+                    } else {
+                        PresentMode::Immediate
+                    };
+                }
+                let mut frame_rate_cap = self.settings.frame_rate_cap.unwrap_or(0) as f32;
+                if widgets.slider("Frame rate cap", &mut frame_rate_cap, 0.0, 240.0, 10.0) {
+                    // NOTE: 0 means no limit.
+                    self.settings.frame_rate_cap = Some(frame_rate_cap as u32).filter(|n| *n > 0);
+                }
+                widgets.slider(
+                    "Mouse sensitivity",
+                    &mut self.settings.mouse_sensitivity,
+                    0.1,
+                    3.0,
+                    0.1,
+                );
+                widgets.slider("Brightness", &mut self.settings.brightness, 0.5, 2.0, 0.1);
+                widgets.checkbox("Bloom", &mut self.settings.has_bloom);
+                // NOTE: The volumes are heard after they are applied.
+                let volume = &mut self.settings.volume;
+                widgets.slider("Master volume", &mut volume.master, 0.0, 1.0, 0.05);
+                widgets.slider("Music volume", &mut volume.music, 0.0, 1.0, 0.05);
+                widgets.slider("SFX volume", &mut volume.sfx, 0.0, 1.0, 0.05);
+                if widgets.button("Crosshair") {
+                    next_screen = Some(Screen::Crosshair);
+                }
+                widgets.space(16.0);
+                if widgets.button("Apply") {
+                    self.game_config.settings = self.settings.clone();
+                    system_requests.push(SystemRequest::ApplySettings(self.settings.clone()));
+                }
+                if widgets.button("Back") || is_escaped {
+                    next_screen = Some(Screen::Main);
+                }
+            }
+            Screen::Crosshair => {
+                // NOTE: The changes are applied on the settings screen.
+                let crosshair = &mut self.settings.crosshair;
+                widgets.label("Crosshair", 1.0);
+                widgets.checkbox("Lines", &mut crosshair.has_lines);
+                widgets.checkbox("Dot", &mut crosshair.has_dot);
+                widgets.checkbox("Circle", &mut crosshair.has_circle);
+                widgets.slider("Gap", &mut crosshair.gap, 0.0, 32.0, 1.0);
+                widgets.slider("Thickness", &mut crosshair.thickness, 1.0, 8.0, 0.5);
+                widgets.slider("Length", &mut crosshair.length, 1.0, 32.0, 1.0);
+                widgets.slider("Outline", &mut crosshair.outline, 0.0, 4.0, 0.5);
+                widgets.slider("Opacity", &mut crosshair.opacity, 0.1, 1.0, 0.05);
+                let color_index = CROSSHAIR_COLORS
+                    .iter()
+                    .position(|(_, n)| *n == crosshair.color);
+                let color_name = color_index.map_or("Custom", |i| CROSSHAIR_COLORS[i].0);
+                if widgets.button(&format!("Color: {}", color_name)) {
+                    let next = color_index.map_or(0, |i| (i + 1) % CROSSHAIR_COLORS.len());
+                    crosshair.color = CROSSHAIR_COLORS[next].1;
+                }
+                widgets.checkbox("Spread while moving", &mut crosshair.is_spread);
+                widgets.space(16.0);
+                if widgets.button("Back") || is_escaped {
+                    next_screen = Some(Screen::Settings);
+                }
+            }
+        }
+        let widget_instance_controllers = widgets.end();
+        if let Some(n) = self.widget_state.take_sound() {
+            audio_requests.push(AudioRequest::Play(n));
+        }
+
+        // change the screen
+        if let Some(n) = next_screen {
+            self.screen = n;
+            self.widget_state.reset();
+        }
 
         // draw
-        let mut update_requests = Vec::new();
-        update_requests.push(self.message.get_instance_controller());
+        // NOTE: The message is drawn only on the main screen.
+        let mut update_requests = Vec::from([self.message.get_instance_controller()]);
+        update_requests.extend(widget_instance_controllers);
+        debug_assert!(
+            update_requests.len() <= SCENE_UI_INSTANCE_LIMIT,
+            "the title scene uses {} UI instances",
+            update_requests.len()
+        );
+        let mut instance_indices = Vec::new();
+        if self.screen == Screen::Main {
+            instance_indices.push((UiTextureId::Ui, 0, 1));
+        }
+        instance_indices.push((UiTextureId::Font, 1, update_requests.len() as u32));
         render_requests.push(RenderRequest::UpdateUiInstances(update_requests));
         render_requests.push(RenderRequest::DrawUi(DrawUiDescriptor {
             clear_color: Some([0.0, 0.0, 0.0]),
-            instance_indices: Vec::from([(0, 1)]),
+            instance_indices,
         }));
+        if self.screen == Screen::Crosshair {
+            let placement = Placement::new(
+                Anchor::Center,
+                Anchor::Center,
+                Vec2::new(CROSSHAIR_PREVIEW_OFFSET, 0.0),
+            );
+            let position = self.layout.place(&placement, Vec2::ZERO).get_center();
+            let crosshair = Crosshair::new(&self.settings.crosshair);
+            render_requests.push(RenderRequest::DrawCrosshair(
+                crosshair.get_descriptor(&self.layout, position),
+            ));
+        }
 
         // finish
         next_scene
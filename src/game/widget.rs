@@ -0,0 +1,618 @@
+//! A module for immediate-mode UI widgets.
+//!
+//! The widgets are declared every frame in the order they are shown from top to bottom,
+//! and each declaration returns whether it's operated in this frame.
+//!
+//! ```ignore
+//! let placement = Placement::new(Anchor::Center, Anchor::Center, Vec2::ZERO);
+//! let mut widgets = widget_state.begin(&font, input_states, &layout, &placement, 360.0);
+//! if widgets.button("Start") {
+//!     // start
+//! }
+//! widgets.slider("Volume", &mut volume, 0.0, 1.0, 0.1);
+//! let instance_controllers = widgets.end();
+//! ```
+//!
+//! They are operated by the mouse and the keyboard:
+//! - the virtual cursor follows the mouse and the hovered widget is focused
+//! - W/S or up/down arrows move the focus
+//! - A/D or left/right arrows change the value of the focused slider or checkbox
+//! - E or the left click activates the focused or the hovered widget
+//!
+//! NOTE: The window cursor is always invisible and centered for the camera,
+//!       so the virtual cursor is moved by the cursor movements and drawn as a widget.
+//!
+//! The sizes are in logical px and multiplied by the scale factor of the layout.
+//! The column is placed with the height of the last frame since it's unknown until all the widgets are declared.
+//!
+//! All the widgets are drawn with the font texture, so the instances must be drawn with `UiTextureId::Font`.
+//!
+//! The widgets request an UI sound when they are operated or the focus moves,
+//! which can be taken by `WidgetState::take_sound()` after `Widgets::end()`.
+
+use crate::{
+    game::{
+        entity::text::{Text, TextAlign},
+        layout::{Layout, Placement, Rect},
+    },
+    system::{
+        audio::sound::SoundId,
+        input::{InputStates, PressingInput},
+    },
+    util::{font::Font, instance::InstanceController},
+};
+use glam::{Vec2, Vec3, Vec4};
+use std::collections::HashMap;
+
+/// The height (logical px) of a row.
+const ROW_HEIGHT: f32 = 40.0;
+/// The space (logical px) between rows.
+const ROW_SPACING: f32 = 8.0;
+/// The padding (logical px) in a row.
+const PADDING: f32 = 12.0;
+/// The scale of the texts in rows.
+const TEXT_SCALE: f32 = 0.75;
+/// The frames a key is held before it repeats.
+const REPEAT_DELAY: u32 = 24;
+/// The frames between the repeats.
+const REPEAT_INTERVAL: u32 = 4;
+
+const TEXT_COLOR: Vec4 = Vec4::new(1.0, 1.0, 1.0, 1.0);
+// NOTE: The colors are linear and the UI is drawn on an sRGB surface.
+const BACKGROUND_COLOR: Vec4 = Vec4::new(0.01, 0.01, 0.015, 0.85);
+const FOCUSED_COLOR: Vec4 = Vec4::new(0.03, 0.12, 0.45, 0.9);
+const TRACK_COLOR: Vec4 = Vec4::new(0.1, 0.1, 0.12, 1.0);
+const ACCENT_COLOR: Vec4 = Vec4::new(0.95, 0.95, 0.95, 1.0);
+
+/// A function to check if a pressing input triggers an action in this frame.
+///
+/// It triggers when pressed and repeats while held.
+fn is_triggered(frames: u32) -> bool {
+    frames == 1
+        || (frames > REPEAT_DELAY && (frames - REPEAT_DELAY).is_multiple_of(REPEAT_INTERVAL))
+}
+
+fn get_direction(
+    input_states: &InputStates,
+    negatives: [PressingInput; 2],
+    positives: [PressingInput; 2],
+) -> i32 {
+    let pressing = &input_states.pressing;
+    let negative = negatives.iter().any(|n| is_triggered(pressing.get(n)));
+    let positive = positives.iter().any(|n| is_triggered(pressing.get(n)));
+    positive as i32 - negative as i32
+}
+
+/// A state of widgets kept across frames.
+pub struct WidgetState {
+    /// The position of the virtual cursor in UI coordinates.
+    cursor: Vec2,
+    /// The index of the focused widget in the declaration order.
+    focus: usize,
+    /// The first visible rows of the lists keyed by the widget index.
+    scrolls: HashMap<usize, usize>,
+    /// The height (logical px) of the column in the last frame.
+    column_height: f32,
+    /// The UI sound requested in the last frame.
+    sound: Option<SoundId>,
+}
+
+impl WidgetState {
+    /// A constructor.
+    pub fn new() -> Self {
+        Self {
+            cursor: Vec2::ZERO,
+            focus: 0,
+            scrolls: HashMap::new(),
+            column_height: 0.0,
+            sound: None,
+        }
+    }
+
+    /// A method to focus the first widget and forget the scrolls.
+    ///
+    /// It should be called when the widgets on the screen are replaced.
+    pub fn reset(&mut self) {
+        self.focus = 0;
+        self.scrolls.clear();
+    }
+
+    /// A method to take the UI sound requested by the widgets in the last frame.
+    pub fn take_sound(&mut self) -> Option<SoundId> {
+        self.sound.take()
+    }
+
+    /// A method to begin declaring widgets in this frame.
+    ///
+    /// The widgets are stacked in a column.
+    ///
+    /// * `layout` - the layout of the window
+    /// * `placement` - the placement of the column
+    /// * `column_width` - the width (logical px) of the column
+    pub fn begin<'a>(
+        &'a mut self,
+        font: &'a Font,
+        input_states: &InputStates,
+        layout: &Layout,
+        placement: &Placement,
+        column_width: f32,
+    ) -> Widgets<'a> {
+        // move the virtual cursor
+        let moving = &input_states.moving;
+        let is_cursor_moved = moving.x != 0.0 || moving.y != 0.0;
+        let (width, height) = (layout.width, layout.height);
+        self.cursor = Vec2::new(
+            (self.cursor.x + moving.x as f32).clamp(-width / 2.0, width / 2.0),
+            (self.cursor.y - moving.y as f32).clamp(-height / 2.0, height / 2.0),
+        );
+
+        // place the column
+        let column = layout.place(
+            placement,
+            Vec2::new(layout.scale(column_width), layout.scale(self.column_height)),
+        );
+
+        // get the operations
+        let pressing = &input_states.pressing;
+        Widgets {
+            initial_focus: self.focus,
+            solid_uv: font.get_solid_uv().unwrap_or(Vec4::ZERO),
+            state: self,
+            font,
+            is_cursor_moved,
+            is_clicked: pressing.get(&PressingInput::MouseLeft) == 1,
+            is_dragged: pressing.get(&PressingInput::MouseLeft) > 0,
+            is_confirmed: pressing.get(&PressingInput::KeyE) == 1,
+            navigation: get_direction(
+                input_states,
+                [PressingInput::KeyW, PressingInput::ArrowUp],
+                [PressingInput::KeyS, PressingInput::ArrowDown],
+            ),
+            adjustment: get_direction(
+                input_states,
+                [PressingInput::KeyA, PressingInput::ArrowLeft],
+                [PressingInput::KeyD, PressingInput::ArrowRight],
+            ),
+            scale_factor: layout.scale_factor,
+            left: column.left,
+            column_top: column.top,
+            top: column.top,
+            width: column.width,
+            index: 0,
+            sound: None,
+            instance_controllers: Vec::new(),
+        }
+    }
+}
+
+/// Widgets declared in a frame.
+///
+/// It's created by `WidgetState::begin()` and finished by `Widgets::end()`.
+pub struct Widgets<'a> {
+    state: &'a mut WidgetState,
+    /// The focus when this frame began.
+    initial_focus: usize,
+    font: &'a Font,
+    solid_uv: Vec4,
+    is_cursor_moved: bool,
+    is_clicked: bool,
+    is_dragged: bool,
+    is_confirmed: bool,
+    /// The focus movement requested by the keyboard.
+    /// It's consumed by a list if the selection of the list moves instead.
+    navigation: i32,
+    /// The value change requested by the keyboard.
+    adjustment: i32,
+    /// The scale factor from logical px to physical px.
+    scale_factor: f32,
+    /// The left of the column.
+    left: f32,
+    /// The top of the column.
+    column_top: f32,
+    /// The top of the next widget.
+    top: f32,
+    /// The width of the column.
+    width: f32,
+    /// The index of the next focusable widget.
+    index: usize,
+    sound: Option<SoundId>,
+    instance_controllers: Vec<Option<InstanceController>>,
+}
+
+impl<'a> Widgets<'a> {
+    /// A method to convert logical px into physical px.
+    fn scale(&self, logical: f32) -> f32 {
+        logical * self.scale_factor
+    }
+
+    /// A method to request an UI sound.
+    ///
+    /// NOTE: A click takes priority over the other sounds in a frame.
+    fn play(&mut self, sound: SoundId) {
+        if self.sound != Some(SoundId::Click) {
+            self.sound = Some(sound);
+        }
+    }
+
+    fn push_rectangle(&mut self, left: f32, top: f32, width: f32, height: f32, color: Vec4) {
+        self.instance_controllers.push(Some(InstanceController {
+            scale: Vec3::new(width, height, 1.0),
+            position: Vec3::new(left + width / 2.0, top - height / 2.0, 0.0),
+            uv: self.solid_uv,
+            color,
+            ..Default::default()
+        }));
+    }
+
+    /// A method to draw a text vertically centered at `y`.
+    ///
+    /// * `scale` - the scale of the text in logical px
+    fn push_text(&mut self, x: f32, y: f32, scale: f32, align: TextAlign, text: &str) {
+        let scale = self.scale(scale);
+        let top = y + self.font.get_line_height() * scale / 2.0;
+        let mut text = Text::new(self.font, x, top, scale, TEXT_COLOR, align, text);
+        self.instance_controllers
+            .extend(text.get_instance_controllers());
+    }
+
+    /// A method to allocate the next row in the column.
+    ///
+    /// * `height` - the height (physical px) of the row
+    fn next_rect(&mut self, height: f32) -> Rect {
+        let rect = Rect {
+            left: self.left,
+            top: self.top,
+            width: self.width,
+            height,
+        };
+        self.top -= height + self.scale(ROW_SPACING);
+        rect
+    }
+
+    /// A method to register a focusable widget.
+    ///
+    /// It returns whether the widget is hovered and focused.
+    fn next_widget(&mut self, rect: &Rect) -> (bool, bool) {
+        let index = self.index;
+        self.index += 1;
+        let is_hovered = rect.contains(self.state.cursor);
+        if is_hovered && self.is_cursor_moved {
+            self.state.focus = index;
+        }
+        (is_hovered, self.state.focus == index)
+    }
+
+    fn push_background(&mut self, rect: &Rect, is_focused: bool) {
+        let color = if is_focused {
+            FOCUSED_COLOR
+        } else {
+            BACKGROUND_COLOR
+        };
+        self.push_rectangle(rect.left, rect.top, rect.width, rect.height, color);
+    }
+
+    /// A method to add a space.
+    ///
+    /// * `height` - the height in logical px
+    pub fn space(&mut self, height: f32) {
+        self.top -= self.scale(height);
+    }
+
+    /// A method to show a centered text that can't be focused.
+    ///
+    /// * `scale` - the scale of the text; 1.0 means the size in the font at the scale factor 1.0
+    pub fn label(&mut self, text: &str, scale: f32) {
+        let scale = self.scale(scale);
+        let line_height = self.font.get_line_height() * scale;
+        let rect = self.next_rect(line_height * text.lines().count().max(1) as f32);
+        let mut text = Text::new(
+            self.font,
+            rect.get_center().x,
+            rect.top,
+            scale,
+            TEXT_COLOR,
+            TextAlign::Center,
+            text,
+        );
+        self.instance_controllers
+            .extend(text.get_instance_controllers());
+    }
+
+    /// A method to show a button.
+    ///
+    /// It returns `true` if it's clicked or activated by the keyboard.
+    pub fn button(&mut self, label: &str) -> bool {
+        let rect = self.next_rect(self.scale(ROW_HEIGHT));
+        let (is_hovered, is_focused) = self.next_widget(&rect);
+        self.push_background(&rect, is_focused);
+        let center = rect.get_center();
+        self.push_text(center.x, center.y, TEXT_SCALE, TextAlign::Center, label);
+        let is_activated = (is_hovered && self.is_clicked) || (is_focused && self.is_confirmed);
+        if is_activated {
+            self.play(SoundId::Click);
+        }
+        is_activated
+    }
+
+    /// A method to show a checkbox.
+    ///
+    /// It returns `true` if `value` is toggled.
+    pub fn checkbox(&mut self, label: &str, value: &mut bool) -> bool {
+        let rect = self.next_rect(self.scale(ROW_HEIGHT));
+        let (is_hovered, is_focused) = self.next_widget(&rect);
+        let is_toggled = (is_hovered && self.is_clicked)
+            || (is_focused && (self.is_confirmed || self.adjustment != 0));
+        if is_toggled {
+            *value = !*value;
+            self.play(SoundId::Click);
+        }
+
+        // draw
+        self.push_background(&rect, is_focused);
+        let padding = self.scale(PADDING);
+        let center_y = rect.get_center().y;
+        self.push_text(
+            rect.left + padding,
+            center_y,
+            TEXT_SCALE,
+            TextAlign::Left,
+            label,
+        );
+        let size = self.scale(ROW_HEIGHT) - padding * 2.0;
+        let box_left = rect.get_right() - padding - size;
+        let box_top = center_y + size / 2.0;
+        self.push_rectangle(box_left, box_top, size, size, TRACK_COLOR);
+        if *value {
+            let inset = self.scale(4.0);
+            self.push_rectangle(
+                box_left + inset,
+                box_top - inset,
+                size - inset * 2.0,
+                size - inset * 2.0,
+                ACCENT_COLOR,
+            );
+        }
+
+        is_toggled
+    }
+
+    /// A method to show a slider.
+    ///
+    /// The value is snapped to a multiple of `step` from `min`.
+    /// It returns `true` if `value` is changed.
+    ///
+    /// NOTE: If `step` isn't positive, the value isn't snapped and the keys change it by 1% of the range.
+    pub fn slider(&mut self, label: &str, value: &mut f32, min: f32, max: f32, step: f32) -> bool {
+        let rect = self.next_rect(self.scale(ROW_HEIGHT));
+        let (is_hovered, is_focused) = self.next_widget(&rect);
+        let padding = self.scale(PADDING);
+        let value_width = self.scale(64.0);
+        let track_left = rect.left + rect.width * 0.5;
+        let track_width = rect.width * 0.5 - padding * 2.0 - value_width;
+
+        // change the value
+        let old_value = *value;
+        let mut new_value = *value;
+        if is_focused {
+            let key_step = if step > 0.0 {
+                step
+            } else {
+                (max - min) / 100.0
+            };
+            new_value += self.adjustment as f32 * key_step;
+        }
+        if is_hovered && self.is_dragged && self.state.cursor.x >= track_left - padding {
+            let rate = (self.state.cursor.x - track_left) / track_width;
+            new_value = min + rate.clamp(0.0, 1.0) * (max - min);
+        }
+        if step > 0.0 {
+            new_value = min + ((new_value - min) / step).round() * step;
+        }
+        *value = new_value.clamp(min, max);
+
+        // draw
+        self.push_background(&rect, is_focused);
+        let center_y = rect.get_center().y;
+        self.push_text(
+            rect.left + padding,
+            center_y,
+            TEXT_SCALE,
+            TextAlign::Left,
+            label,
+        );
+        let rate = if max > min {
+            (*value - min) / (max - min)
+        } else {
+            0.0
+        };
+        let track_height = self.scale(4.0);
+        let track_top = center_y + track_height / 2.0;
+        self.push_rectangle(
+            track_left,
+            track_top,
+            track_width,
+            track_height,
+            TRACK_COLOR,
+        );
+        self.push_rectangle(
+            track_left,
+            track_top,
+            track_width * rate,
+            track_height,
+            ACCENT_COLOR,
+        );
+        let (thumb_width, thumb_height) = (self.scale(10.0), self.scale(20.0));
+        self.push_rectangle(
+            track_left + track_width * rate - thumb_width / 2.0,
+            center_y + thumb_height / 2.0,
+            thumb_width,
+            thumb_height,
+            ACCENT_COLOR,
+        );
+        let decimals = if step >= 1.0 {
+            0
+        } else if step >= 0.1 {
+            1
+        } else {
+            2
+        };
+        self.push_text(
+            rect.get_right() - padding,
+            center_y,
+            TEXT_SCALE,
+            TextAlign::Right,
+            &format!("{:.*}", decimals, *value),
+        );
+
+        if *value != old_value {
+            self.play(SoundId::Focus);
+        }
+        *value != old_value
+    }
+
+    /// A method to show a scrollable list.
+    ///
+    /// While the list is focused, the keyboard moves the selection instead of the focus
+    /// until the selection reaches the first or the last item.
+    /// The scroll bar on the right can be dragged.
+    /// It returns `true` if an item is clicked or the selection is activated by the keyboard.
+    ///
+    /// * `rows` - the count of the visible rows
+    pub fn list(&mut self, items: &[String], selected: &mut usize, rows: usize) -> bool {
+        let rows = rows.max(1);
+        let row_height = self.scale(ROW_HEIGHT);
+        let padding = self.scale(PADDING);
+        let rect = self.next_rect(row_height * rows as f32);
+        let index = self.index;
+        let (is_hovered, is_focused) = self.next_widget(&rect);
+        let mut scroll = self.state.scrolls.get(&index).copied().unwrap_or(0);
+        let max_scroll = items.len().saturating_sub(rows);
+        *selected = (*selected).min(items.len().saturating_sub(1));
+
+        // move the selection by the keyboard
+        if is_focused && self.navigation != 0 {
+            let next = *selected as i32 + self.navigation;
+            if next >= 0 && (next as usize) < items.len() {
+                *selected = next as usize;
+                self.navigation = 0;
+                self.play(SoundId::Focus);
+                if *selected < scroll {
+                    scroll = *selected;
+                } else if *selected >= scroll + rows {
+                    scroll = *selected + 1 - rows;
+                }
+            }
+        }
+
+        // scroll by dragging the scroll bar
+        let scroll_bar_left = rect.get_right() - padding;
+        let cursor = self.state.cursor;
+        if is_hovered && self.is_dragged && cursor.x >= scroll_bar_left && max_scroll > 0 {
+            let rate = (rect.top - cursor.y) / rect.height;
+            scroll = ((rate * items.len() as f32) as usize).min(max_scroll);
+        }
+        let scroll = scroll.min(max_scroll);
+        self.state.scrolls.insert(index, scroll);
+
+        // select by the mouse
+        let mut is_activated = is_focused && self.is_confirmed;
+        let visible_count = rows.min(items.len() - scroll.min(items.len()));
+        for i in 0..visible_count {
+            let row = Rect {
+                left: rect.left,
+                top: rect.top - i as f32 * row_height,
+                width: rect.width - padding,
+                height: row_height,
+            };
+            if is_hovered && self.is_clicked && row.contains(cursor) {
+                *selected = scroll + i;
+                is_activated = true;
+            }
+        }
+
+        // draw
+        self.push_rectangle(
+            rect.left,
+            rect.top,
+            rect.width,
+            rect.height,
+            BACKGROUND_COLOR,
+        );
+        for i in 0..visible_count {
+            let top = rect.top - i as f32 * row_height;
+            if scroll + i == *selected {
+                let color = if is_focused {
+                    FOCUSED_COLOR
+                } else {
+                    TRACK_COLOR
+                };
+                self.push_rectangle(rect.left, top, rect.width - padding, row_height, color);
+            }
+            self.push_text(
+                rect.left + padding,
+                top - row_height / 2.0,
+                TEXT_SCALE,
+                TextAlign::Left,
+                &items[scroll + i],
+            );
+        }
+        if max_scroll > 0 {
+            let thumb_height = rect.height * rows as f32 / items.len() as f32;
+            let thumb_top = rect.top - rect.height * scroll as f32 / items.len() as f32;
+            let inset = self.scale(2.0);
+            self.push_rectangle(
+                scroll_bar_left + inset,
+                thumb_top,
+                padding - inset * 2.0,
+                thumb_height,
+                TRACK_COLOR,
+            );
+        }
+
+        if is_activated {
+            self.play(SoundId::Click);
+        }
+        is_activated
+    }
+
+    /// A method to finish declaring widgets.
+    ///
+    /// It returns the instances of the widgets and the virtual cursor.
+    pub fn end(mut self) -> Vec<Option<InstanceController>> {
+        // move the focus
+        if self.index > 0 {
+            let focus = self.state.focus.min(self.index - 1) as i32;
+            self.state.focus = (focus + self.navigation).rem_euclid(self.index as i32) as usize;
+        }
+        if self.state.focus != self.initial_focus {
+            self.play(SoundId::Focus);
+        }
+        self.state.sound = self.sound;
+
+        // keep the height of the column for the next frame
+        // NOTE: The spacing after the last row isn't a part of the column.
+        let height = self.column_top - self.top - self.scale(ROW_SPACING);
+        self.state.column_height = height.max(0.0) / self.scale_factor;
+
+        // draw the virtual cursor
+        let cursor = self.state.cursor;
+        let (outer, inner) = (self.scale(12.0), self.scale(8.0));
+        let inset = (outer - inner) / 2.0;
+        self.push_rectangle(
+            cursor.x,
+            cursor.y,
+            outer,
+            outer,
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        );
+        self.push_rectangle(
+            cursor.x + inset,
+            cursor.y - inset,
+            inner,
+            inner,
+            ACCENT_COLOR,
+        );
+
+        self.instance_controllers
+    }
+}
@@ -1,32 +1,90 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// An enum for enumerating log levels.
+///
+/// Logs lower than the current log level are not printed.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum LogLevel {
+    Debug = 0,
+    Info = 1,
+    Warn = 2,
+    Error = 3,
+}
+
+impl LogLevel {
+    /// A static method to parse a log level from a lowercase name.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "debug" => Ok(Self::Debug),
+            "info" => Ok(Self::Info),
+            "warn" => Ok(Self::Warn),
+            "error" => Ok(Self::Error),
+            _ => Err(format!("unknown log level '{}'", value)),
+        }
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warn => "warn",
+            Self::Error => "error",
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Debug as u8);
+#[cfg(not(debug_assertions))]
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+/// A function to set the current log level.
+pub fn set_level(level: LogLevel) {
+    LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// A function to check if logs of `level` should be printed.
+pub fn is_enabled(level: LogLevel) -> bool {
+    level as u8 >= LOG_LEVEL.load(Ordering::Relaxed)
+}
+
 #[macro_export]
 macro_rules! log_inner_println {
     ($lvl:expr, $fnn:expr, $fmt:expr) => {
-        let dt = chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string();
-        println!("{} [ {} ] {}(): {}", dt, $lvl, $fnn, $fmt);
+        if $crate::log::is_enabled($lvl) {
+            let dt = chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string();
+            println!("{} [ {} ] {}(): {}", dt, $lvl.to_str(), $fnn, $fmt);
+        }
     };
     ($lvl:expr, $fnn:expr, $fmt:expr, $($arg:tt)*) => {
-        let dt = chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string();
-        println!("{} [ {} ] {}(): {}", dt, $lvl, $fnn, format!($fmt, $($arg)*));
+        if $crate::log::is_enabled($lvl) {
+            let dt = chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string();
+            println!("{} [ {} ] {}(): {}", dt, $lvl.to_str(), $fnn, format!($fmt, $($arg)*));
+        }
     };
 }
 
 #[macro_export]
 macro_rules! log_inner_eprintln {
     ($lvl:expr, $fnn:expr, $fmt:expr) => {
-        let dt = chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string();
-        eprintln!("{} [ {} ] {}(): {}", dt, $lvl, $fnn, $fmt);
+        if $crate::log::is_enabled($lvl) {
+            let dt = chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string();
+            eprintln!("{} [ {} ] {}(): {}", dt, $lvl.to_str(), $fnn, $fmt);
+        }
     };
     ($lvl:expr, $fnn:expr, $fmt:expr, $($arg:tt)*) => {
-        let dt = chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string();
-        eprintln!("{} [ {} ] {}(): {}", dt, $lvl, $fnn, format!($fmt, $($arg)*));
+        if $crate::log::is_enabled($lvl) {
+            let dt = chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string();
+            eprintln!("{} [ {} ] {}(): {}", dt, $lvl.to_str(), $fnn, format!($fmt, $($arg)*));
+        }
     };
 }
 
 #[macro_export]
 #[cfg(debug_assertions)]
 macro_rules! debug {
-    ($fnn:expr, $fmt:expr) => (log_inner_println!("debug", $fnn, $fmt));
-    ($fnn:expr, $fmt:expr, $($arg:tt)*) => (log_inner_println!("debug", $fnn, $fmt, $($arg)*));
+    ($fnn:expr, $fmt:expr) => (log_inner_println!($crate::log::LogLevel::Debug, $fnn, $fmt));
+    ($fnn:expr, $fmt:expr, $($arg:tt)*) => (log_inner_println!($crate::log::LogLevel::Debug, $fnn, $fmt, $($arg)*));
 }
 
 #[macro_export]
@@ -38,18 +96,18 @@ macro_rules! debug {
 
 #[macro_export]
 macro_rules! info {
-    ($fnn:expr, $fmt:expr) => (log_inner_println!("info", $fnn, $fmt));
-    ($fnn:expr, $fmt:expr, $($arg:tt)*) => (log_inner_println!("info", $fnn, $fmt, $($arg)*));
+    ($fnn:expr, $fmt:expr) => (log_inner_println!($crate::log::LogLevel::Info, $fnn, $fmt));
+    ($fnn:expr, $fmt:expr, $($arg:tt)*) => (log_inner_println!($crate::log::LogLevel::Info, $fnn, $fmt, $($arg)*));
 }
 
 #[macro_export]
 macro_rules! warn {
-    ($fnn:expr, $fmt:expr) => (log_inner_eprintln!("warn", $fnn, $fmt));
-    ($fnn:expr, $fmt:expr, $($arg:tt)*) => (log_inner_eprintln!("warn", $fnn, $fmt, $($arg)*));
+    ($fnn:expr, $fmt:expr) => (log_inner_eprintln!($crate::log::LogLevel::Warn, $fnn, $fmt));
+    ($fnn:expr, $fmt:expr, $($arg:tt)*) => (log_inner_eprintln!($crate::log::LogLevel::Warn, $fnn, $fmt, $($arg)*));
 }
 
 #[macro_export]
 macro_rules! error {
-    ($fnn:expr, $fmt:expr) => (log_inner_eprintln!("error", $fnn, $fmt));
-    ($fnn:expr, $fmt:expr, $($arg:tt)*) => (log_inner_eprintln!("error", $fnn, $fmt, $($arg)*));
+    ($fnn:expr, $fmt:expr) => (log_inner_eprintln!($crate::log::LogLevel::Error, $fnn, $fmt));
+    ($fnn:expr, $fmt:expr, $($arg:tt)*) => (log_inner_eprintln!($crate::log::LogLevel::Error, $fnn, $fmt, $($arg)*));
 }
@@ -2,27 +2,59 @@
 //!
 //! This is based on winit ApplicationHandler.
 //! This creates a window with the following features:
-//! - fullscreen
-//! - unresizable
-//! - the maximize button is disabled
+//! - windowed, borderless fullscreen or exclusive fullscreen depending on the settings
+//! - alt-enter toggles windowed and fullscreen
+//! - unresizable and the maximize button is disabled in fullscreen
 //! - the cursor is invisible
+//! - F3 toggles the debug overlay
+//!
+//! Escape opens the pause menu in game and the application exits from the title menu.
 
 #[macro_use]
 mod log;
+mod benchmark;
+mod cli;
 mod game;
+mod settings;
 mod system;
 mod util;
 
-use game::scene::SceneManager;
-use std::{error::Error, process, sync::Arc};
-use system::{input::InputManager, renderer::Renderer};
+use benchmark::{Benchmark, FrameSample};
+use cli::CommandLine;
+use game::{
+    debug_overlay::DebugOverlay,
+    layout::Layout,
+    level::Level,
+    scene::{GameConfig, SceneManager, SystemRequest},
+};
+use settings::Settings;
+use std::{
+    env,
+    error::Error,
+    fs,
+    path::PathBuf,
+    process,
+    rc::Rc,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use system::{
+    asset::{AssetManager, AssetWatcher, WatchedAsset},
+    audio::{Audio, AudioBackend},
+    input::InputManager,
+    renderer::{self, error::RendererError, profiler::GPU_PASSES, Renderer},
+    replay::{InputPlayer, InputRecorder},
+    window,
+};
+use util::font::Font;
 use winit::{
     application::ApplicationHandler,
     dpi::{PhysicalPosition, PhysicalSize},
     event::WindowEvent,
     event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
-    keyboard::KeyCode,
-    window::{Fullscreen, Window, WindowButtons, WindowId},
+    keyboard::{KeyCode, ModifiersState},
+    window::{Window, WindowId},
 };
 
 fn set_cursor_center(window: &Arc<Window>) -> (f64, f64) {
@@ -38,11 +70,157 @@ fn set_cursor_center(window: &Arc<Window>) -> (f64, f64) {
     (x, y)
 }
 
+fn get_layout(window: &Window) -> Layout {
+    Layout::new(
+        window.inner_size().width as f32,
+        window.inner_size().height as f32,
+        window.scale_factor() as f32,
+    )
+}
+
 struct Application<'a> {
+    settings: Settings,
+    last_frame_time: Instant,
     window: Option<Arc<Window>>,
+    modifiers: ModifiersState,
     renderer: Option<Renderer<'a>>,
     input_manager: InputManager,
     scene_manager: SceneManager,
+    debug_overlay: DebugOverlay,
+    input_player: Option<InputPlayer>,
+    input_recorder: Option<InputRecorder>,
+    audio: Audio,
+    /// The path of the WAV file the offline audio is written to at exit.
+    audio_wav: Option<String>,
+    /// The assets the renderer is created with.
+    assets: Rc<AssetManager>,
+    /// The watcher of the asset files, only if `--hot-reload` is specified.
+    asset_watcher: Option<AssetWatcher>,
+    benchmark: Option<Benchmark>,
+    /// The error that makes the application exit.
+    error: Option<RendererError>,
+}
+
+impl<'a> Application<'a> {
+    /// A method to create a renderer.
+    fn create_renderer(&self, window: Arc<Window>) -> Result<Renderer<'a>, RendererError> {
+        Renderer::new(window, &self.settings, &self.assets)
+    }
+
+    /// A method to apply the settings edited on the settings screen and save the edited ones.
+    fn apply_settings(&mut self, event_loop: &ActiveEventLoop, settings: Settings) {
+        // apply
+        let window = self.window.as_ref().unwrap();
+        // NOTE: A change between borderless and exclusive or of the video mode is also applied.
+        let is_window_changed = settings.window_mode != self.settings.window_mode
+            || settings.monitor != self.settings.monitor
+            || settings.resolution != self.settings.resolution
+            || settings.refresh_rate != self.settings.refresh_rate;
+        if is_window_changed {
+            let monitor = if settings.monitor != self.settings.monitor {
+                window::select_monitor(event_loop, settings.monitor)
+            } else {
+                window
+                    .current_monitor()
+                    .or_else(|| window::select_monitor(event_loop, settings.monitor))
+            };
+            if let Some(n) = monitor {
+                window::apply_window_mode(window, &n, &settings);
+            }
+        }
+        if let Some(n) = &mut self.renderer {
+            n.set_present_mode(settings.present_mode);
+            n.set_post_effects(&renderer::create_post_effects(&settings));
+        }
+        self.audio.set_volume(&settings.volume);
+        // NOTE: Only the edited keys are saved not to write the command line overrides into the file.
+        let edited_keys = settings::EDITABLE_KEYS
+            .into_iter()
+            .filter(|n| settings.get(n) != self.settings.get(n))
+            .collect::<Vec<&str>>();
+        self.settings = settings;
+
+        // save
+        if edited_keys.is_empty() {
+            return;
+        }
+        if let Err(e) = self.settings.save(settings::SETTINGS_PATH, &edited_keys) {
+            error!(
+                "Application.apply_settings",
+                "failed to save the settings: {}", e
+            );
+        } else {
+            info!("Application.apply_settings", "settings saved.");
+        }
+    }
+
+    /// A method to reload the assets whose files are modified.
+    ///
+    /// If a reload fails, the error is reported and the old one is kept.
+    fn reload_assets(&mut self) {
+        let Some(watcher) = &mut self.asset_watcher else {
+            return;
+        };
+        let level = Rc::clone(self.scene_manager.get_level());
+        if let Some(n) = &level.path {
+            watcher.watch(WatchedAsset::Level, n.clone());
+        }
+        let model_paths = level
+            .get_model_paths()
+            .into_iter()
+            .filter_map(|n| self.assets.resolve(n))
+            .collect::<Vec<PathBuf>>();
+        watcher.watch_files(WatchedAsset::LevelModels, &model_paths);
+        let skybox_paths = level
+            .get_skybox()
+            .get_paths()
+            .into_iter()
+            .filter_map(|n| self.assets.resolve(n))
+            .collect::<Vec<PathBuf>>();
+        watcher.watch_files(WatchedAsset::LevelSkybox, &skybox_paths);
+        for n in watcher.poll() {
+            match n {
+                WatchedAsset::Shader(n) => {
+                    if let Some(renderer) = &mut self.renderer {
+                        renderer.reload_shader(&self.assets, n);
+                    }
+                }
+                WatchedAsset::Texture(n) => {
+                    if let Some(renderer) = &mut self.renderer {
+                        renderer.reload_texture(&self.assets, n);
+                    }
+                }
+                WatchedAsset::LevelModels => {
+                    if let Some(renderer) = &mut self.renderer {
+                        renderer.reload_models(&self.assets, level.get_models());
+                    }
+                }
+                WatchedAsset::LevelSkybox => {
+                    if let Some(renderer) = &mut self.renderer {
+                        renderer.reload_skybox(&self.assets, level.get_skybox());
+                    }
+                }
+                WatchedAsset::Level => match level.reload() {
+                    Ok(n) => {
+                        info!("Application.reload_assets", "level reloaded: {}.", n.name);
+                        self.scene_manager.reload_level(n);
+                    }
+                    Err(e) => error!(
+                        "Application.reload_assets",
+                        "failed to reload the level, the old one is kept: {}", e
+                    ),
+                },
+            }
+        }
+    }
+
+    /// A method to exit the event loop because of an error.
+    ///
+    /// The error is returned from `run()` after the event loop exits.
+    fn exit_with_error(&mut self, event_loop: &ActiveEventLoop, error: RendererError) {
+        self.error = Some(error);
+        event_loop.exit();
+    }
 }
 
 impl<'a> ApplicationHandler for Application<'a> {
@@ -52,20 +230,16 @@ impl<'a> ApplicationHandler for Application<'a> {
             return;
         }
 
-        // get a primary monitor
-        let primary_monitor = if let Some(n) = event_loop.primary_monitor() {
+        // select a monitor
+        let monitor = if let Some(n) = window::select_monitor(event_loop, self.settings.monitor) {
             n
         } else {
-            error!("Application.resumed", "no primary monitor is found.");
+            error!("Application.resumed", "no monitor is found.");
             process::exit(1);
         };
 
         // create a window
-        let window_attributes = Window::default_attributes()
-            .with_title("WebGPU Simple FPS")
-            .with_resizable(false)
-            .with_inner_size(primary_monitor.size())
-            .with_fullscreen(Some(Fullscreen::Borderless(Some(primary_monitor))));
+        let window_attributes = window::create_window_attributes(&monitor, &self.settings);
         let window = match event_loop.create_window(window_attributes) {
             Ok(n) => n,
             Err(e) => {
@@ -80,26 +254,28 @@ impl<'a> ApplicationHandler for Application<'a> {
         let window = Arc::new(window);
 
         // configure the window
-        window.set_enabled_buttons(WindowButtons::CLOSE | WindowButtons::MINIMIZE);
+        window::configure_window(&window);
         window.set_cursor_visible(false);
         info!("Application.resumed", "window created.");
 
         // create a renderer
-        let renderer = Renderer::new(window.clone());
+        let renderer = match self.create_renderer(window.clone()) {
+            Ok(n) => n,
+            Err(e) => {
+                self.exit_with_error(event_loop, e);
+                return;
+            }
+        };
 
         // move cursor center
         self.input_manager
             .set_cursor_position(set_cursor_center(&window));
 
-        // move on to title scene and resize
-        self.scene_manager.on_window_created(
-            window.inner_size().width as f32,
-            window.inner_size().height as f32,
-        );
-        self.scene_manager.resize(
-            window.inner_size().width as f32,
-            window.inner_size().height as f32,
-        );
+        // move on to title scene and lay out
+        let layout = get_layout(&window);
+        self.scene_manager.on_window_created(layout);
+        self.scene_manager.resize(layout);
+        self.debug_overlay.resize(layout);
 
         // finish
         info!("Application.resumed", "initialization done.");
@@ -108,8 +284,8 @@ impl<'a> ApplicationHandler for Application<'a> {
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
-        if self.window.is_none() {
-            warn!("Application.window_event", "window is none.");
+        if self.window.is_none() || self.renderer.is_none() {
+            warn!("Application.window_event", "window or renderer is none.");
             return;
         }
 
@@ -117,17 +293,51 @@ impl<'a> ApplicationHandler for Application<'a> {
             WindowEvent::CloseRequested => event_loop.exit(),
             WindowEvent::Destroyed => event_loop.exit(),
             WindowEvent::Resized(PhysicalSize { width, height }) => {
+                // NOTE: The size is zero while the window is minimized.
+                if width == 0 || height == 0 {
+                    return;
+                }
                 self.renderer.as_mut().unwrap().resize(width, height);
-                self.scene_manager.resize(width as f32, height as f32);
+                let layout = get_layout(self.window.as_ref().unwrap());
+                self.scene_manager.resize(layout);
+                self.debug_overlay.resize(layout);
                 info!("Application.window_event", "resized: {}x{}.", width, height);
             }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                // NOTE: `Resized` follows if the window size changes too.
+                let layout = get_layout(self.window.as_ref().unwrap());
+                if layout.width > 0.0 && layout.height > 0.0 {
+                    self.scene_manager.resize(layout);
+                    self.debug_overlay.resize(layout);
+                }
+                info!(
+                    "Application.window_event",
+                    "scale factor changed: {}.", scale_factor
+                );
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+            }
             WindowEvent::KeyboardInput {
                 device_id: _,
                 event,
                 is_synthetic: _,
             } => {
-                if event.physical_key == KeyCode::Escape {
-                    event_loop.exit();
+                if event.physical_key == KeyCode::Enter
+                    && event.state.is_pressed()
+                    && !event.repeat
+                    && self.modifiers.alt_key()
+                {
+                    let window = self.window.as_ref().unwrap();
+                    let monitor = window
+                        .current_monitor()
+                        .or_else(|| window::select_monitor(event_loop, self.settings.monitor));
+                    if let Some(n) = monitor {
+                        window::toggle_fullscreen(window, &n, &self.settings);
+                    }
+                }
+                if event.physical_key == KeyCode::F3 && event.state.is_pressed() && !event.repeat {
+                    self.debug_overlay.toggle();
                 }
                 self.input_manager.update_key_state(event);
             }
@@ -150,36 +360,272 @@ impl<'a> ApplicationHandler for Application<'a> {
         }
     }
 
-    fn about_to_wait(&mut self, _: &ActiveEventLoop) {
-        if self.window.is_none() {
-            warn!("Application.about_to_wait", "window is none.");
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_none() || self.renderer.is_none() {
+            warn!("Application.about_to_wait", "window or renderer is none.");
             return;
         }
 
+        // limit the frame rate
+        if let Some(n) = self.settings.frame_rate_cap {
+            let frame_duration = Duration::from_secs_f64(1.0 / n as f64);
+            let elapsed = self.last_frame_time.elapsed();
+            if elapsed < frame_duration {
+                thread::sleep(frame_duration - elapsed);
+            }
+        }
+        let frame_time = self.last_frame_time.elapsed();
+        self.last_frame_time = Instant::now();
+
+        self.reload_assets();
+
+        // get inputs from the replay file or the window
+        let input_states = if let Some(n) = &mut self.input_player {
+            match n.next_frame() {
+                Some(n) => n,
+                None => {
+                    info!("Application.about_to_wait", "replay finished.");
+                    event_loop.exit();
+                    return;
+                }
+            }
+        } else {
+            self.input_manager.get()
+        };
+        if let Some(n) = &mut self.input_recorder {
+            n.push(input_states);
+        }
+
         let mut render_requests = Vec::new();
+        let mut system_requests = Vec::new();
+        let mut audio_requests = Vec::new();
 
-        self.scene_manager
-            .update(self.input_manager.get(), &mut render_requests);
+        let update_start_time = Instant::now();
+        self.scene_manager.update(
+            input_states,
+            &mut render_requests,
+            &mut system_requests,
+            &mut audio_requests,
+        );
+        let update_time = update_start_time.elapsed();
+        self.debug_overlay.update(
+            frame_time,
+            self.scene_manager.get_scene_name(),
+            self.renderer.as_ref().unwrap().get_gpu_profiler(),
+            &mut render_requests,
+        );
+
+        // NOTE: The glTF models and the skybox are loaded only when the level changes.
+        let renderer = self.renderer.as_mut().unwrap();
+        let level = self.scene_manager.get_level();
+        renderer.load_models(&self.assets, level.get_models());
+        renderer.load_skybox(&self.assets, level.get_skybox());
 
         self.input_manager.go_next();
-        self.renderer.as_ref().unwrap().render(render_requests);
+        self.audio.process(audio_requests);
+        for n in system_requests {
+            match n {
+                SystemRequest::ApplySettings(n) => self.apply_settings(event_loop, n),
+                SystemRequest::Exit => event_loop.exit(),
+            }
+        }
+        let renderer = self.renderer.as_mut().unwrap();
+        let result = renderer.render(render_requests);
+        let gpu_times = renderer.take_gpu_frame_times();
+        match result {
+            Ok(Some(timings)) => {
+                if let Some(n) = &mut self.benchmark {
+                    n.push(FrameSample {
+                        frame: frame_time,
+                        update: update_time,
+                        encode: timings.encode,
+                    });
+                    n.push_gpu_times(gpu_times);
+                }
+            }
+            Ok(None) => (),
+            Err(RendererError::DeviceLost(n)) => {
+                warn!(
+                    "Application.about_to_wait",
+                    "the device was lost. the renderer is recreated: {}", n
+                );
+                // NOTE: The old surface must be dropped before the new one is created.
+                self.renderer = None;
+                let window = Arc::clone(self.window.as_ref().unwrap());
+                match self.create_renderer(window) {
+                    Ok(n) => {
+                        self.renderer = Some(n);
+                        // NOTE: The overlay creates its instances every frame, so only the scene is invalidated.
+                        self.scene_manager.invalidate();
+                    }
+                    Err(e) => self.exit_with_error(event_loop, e),
+                }
+            }
+            Err(e) => self.exit_with_error(event_loop, e),
+        }
+
+        // finish benchmark
+        if let (Some(n), Some(renderer)) = (&self.benchmark, &self.renderer) {
+            if n.is_finished() {
+                let adapter_info = renderer.get_adapter_info();
+                let adapter = format!("{} ({})", adapter_info.name, adapter_info.backend.to_str());
+                let size = self.window.as_ref().unwrap().inner_size();
+                let pass_times = renderer
+                    .get_gpu_profiler()
+                    .map(|n| {
+                        GPU_PASSES
+                            .iter()
+                            .filter_map(|pass| Some((pass.to_str(), n.get_average(*pass)?)))
+                            .collect::<Vec<(&str, Duration)>>()
+                    })
+                    .unwrap_or_default();
+                if let Err(e) = n.save(&adapter, (size.width, size.height), &pass_times) {
+                    error!(
+                        "Application.about_to_wait",
+                        "failed to save the benchmark report: {}", e
+                    );
+                }
+                event_loop.exit();
+            }
+        }
+    }
+
+    fn exiting(&mut self, _: &ActiveEventLoop) {
+        if let Some(n) = &self.input_recorder {
+            if let Err(e) = n.save() {
+                error!("Application.exiting", "failed to save inputs: {}", e);
+            }
+        }
+        if let (Some(path), Some(wav)) = (&self.audio_wav, self.audio.get_wav()) {
+            if let Err(e) = fs::write(path, wav) {
+                error!("Application.exiting", "failed to save the audio: {}", e);
+            }
+        }
     }
 }
 
-fn run() -> Result<(), Box<dyn Error>> {
+fn run(command_line: CommandLine, settings: Settings) -> Result<(), Box<dyn Error>> {
+    // load the level
+    let assets = Rc::new(AssetManager::new(command_line.asset_directory.as_deref()));
+    let level = match &command_line.level {
+        Some(n) => Level::load(n)?,
+        None => Level::default_level(&assets)?,
+    };
+    let seed = command_line.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|n| n.as_nanos() as u64)
+            .unwrap_or_default()
+    });
+    info!("run", "seed: {}.", seed);
+
+    // load the replay file
+    let input_player = match &command_line.replay {
+        Some(n) => Some(InputPlayer::load(n)?),
+        None => None,
+    };
+
+    // create a game config
+    let font = Rc::new(Font::default_font(&assets)?);
+    let game_config = GameConfig {
+        level: Rc::new(level),
+        assets: Rc::clone(&assets),
+        font: Rc::clone(&font),
+        settings: settings.clone(),
+        seed,
+        benchmark_frames: command_line.benchmark,
+    };
+
+    // NOTE: The audio isn't played in benchmark mode not to affect the frame times.
+    let mut audio = Audio::new(if command_line.audio_wav.is_some() {
+        AudioBackend::Offline
+    } else if command_line.benchmark.is_some() {
+        AudioBackend::Null
+    } else {
+        AudioBackend::Device
+    });
+    audio.set_volume(&settings.volume);
+
     let event_loop = EventLoop::new()?;
     event_loop.set_control_flow(ControlFlow::Poll);
-    event_loop.run_app(&mut Application {
+    let mut application = Application {
+        settings,
+        last_frame_time: Instant::now(),
         window: None,
+        modifiers: ModifiersState::empty(),
         renderer: None,
         input_manager: InputManager::new((0.0, 0.0)),
-        scene_manager: SceneManager::new(),
-    })?;
+        scene_manager: SceneManager::new(game_config),
+        debug_overlay: DebugOverlay::new(font, Layout::new(0.0, 0.0, 1.0)),
+        input_player,
+        input_recorder: command_line.record.as_deref().map(InputRecorder::new),
+        audio,
+        audio_wav: command_line.audio_wav.clone(),
+        asset_watcher: command_line.is_hot_reload.then(|| {
+            let mut watcher = AssetWatcher::new();
+            watcher.watch_all(&assets);
+            watcher
+        }),
+        assets,
+        benchmark: command_line.benchmark.map(|n| {
+            Benchmark::new(
+                n,
+                command_line
+                    .benchmark_report
+                    .as_deref()
+                    .unwrap_or(benchmark::DEFAULT_REPORT_PATH),
+            )
+        }),
+        error: None,
+    };
+    event_loop.run_app(&mut application)?;
+    if let Some(e) = application.error {
+        return Err(e.into());
+    }
     Ok(())
 }
 
 fn main() {
-    if let Err(e) = run() {
+    // parse the command line
+    let command_line = match CommandLine::parse(env::args().skip(1)) {
+        Ok(n) => n,
+        Err(e) => {
+            eprintln!("error: {}\n\nFor more information, try '--help'.", e);
+            process::exit(2);
+        }
+    };
+    if command_line.is_help {
+        print!("{}", cli::HELP);
+        return;
+    }
+    if let Some(n) = command_line.log_level {
+        log::set_level(n);
+    }
+    if let Some(shape) = &command_line.export_mesh {
+        let path = command_line
+            .export_mesh_path
+            .clone()
+            .unwrap_or_else(|| format!("{}.obj", shape.get_name()));
+        if let Err(e) = fs::write(&path, shape.create_mesh().to_obj()) {
+            error!("main", "failed to export the mesh to {}: {}", path, e);
+            process::exit(1);
+        }
+        info!("main", "exported: {} to {}.", shape, path);
+        return;
+    }
+
+    // load the settings
+    let mut settings = Settings::load(settings::SETTINGS_PATH);
+    if let Err(e) = command_line.apply(&mut settings) {
+        eprintln!("error: {}\n\nFor more information, try '--help'.", e);
+        process::exit(2);
+    }
+    if command_line.is_list_adapters {
+        renderer::print_adapters(&settings);
+        return;
+    }
+
+    if let Err(e) = run(command_line, settings) {
         error!("main", "crashed: {}", e.to_string());
         process::exit(1);
     }
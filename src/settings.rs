@@ -0,0 +1,521 @@
+//! A module for user settings.
+//!
+//! The settings are loaded from a text file that consists of `key = value` lines.
+//! Empty lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! # fifo, mailbox or immediate
+//! present_mode = mailbox
+//! # 0 means no limit
+//! frame_rate_cap = 144
+//! # windowed, borderless or exclusive
+//! window_mode = exclusive
+//! # the index of the monitor, omit it to use the primary monitor
+//! monitor = 1
+//! resolution = 1920x1080
+//! refresh_rate = 144
+//! # auto, vulkan, gl, dx12 or metal
+//! backend = vulkan
+//! # the index printed by `--list-adapters` or a part of the adapter name
+//! adapter = NVIDIA
+//! # default, low or high
+//! power_preference = high
+//! # the multiplier of the camera rotation by the mouse
+//! mouse_sensitivity = 1.5
+//! # the shapes of the crosshair
+//! crosshair_lines = true
+//! crosshair_dot = false
+//! crosshair_circle = false
+//! # the sizes (logical px) of the crosshair
+//! crosshair_gap = 6
+//! crosshair_thickness = 2
+//! crosshair_length = 8
+//! # the width (logical px) of the black outline, 0 means no outline
+//! crosshair_outline = 1
+//! crosshair_color = #00ff00
+//! crosshair_opacity = 0.9
+//! # whether the gap spreads while moving
+//! crosshair_spread = true
+//! # the multiplier of the exposure of the scene
+//! brightness = 1.2
+//! # whether bright areas bloom
+//! bloom = false
+//! # the volumes from 0 to 1, the music and the sound effects are multiplied by the master
+//! master_volume = 0.8
+//! music_volume = 0.5
+//! sfx_volume = 1
+//! ```
+//!
+//! Each setting can be overridden by a command line option such as `--backend gl`.
+//! The settings edited on the settings screen are written back to the file.
+
+use std::fs;
+use wgpu::{Backends, PowerPreference, PresentMode};
+
+/// A constant for the path of the settings file.
+pub const SETTINGS_PATH: &str = "settings.txt";
+/// The keys of the settings edited on the settings screen.
+///
+/// Only these keys are written to the settings file when they are applied.
+pub const EDITABLE_KEYS: [&str; 19] = [
+    "window_mode",
+    "present_mode",
+    "frame_rate_cap",
+    "mouse_sensitivity",
+    "brightness",
+    "bloom",
+    "crosshair_lines",
+    "crosshair_dot",
+    "crosshair_circle",
+    "crosshair_gap",
+    "crosshair_thickness",
+    "crosshair_length",
+    "crosshair_outline",
+    "crosshair_color",
+    "crosshair_opacity",
+    "crosshair_spread",
+    "master_volume",
+    "music_volume",
+    "sfx_volume",
+];
+
+/// An enum for enumerating window modes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WindowMode {
+    /// A resizable window with decorations.
+    Windowed,
+    /// A borderless window covering a whole monitor.
+    Borderless,
+    /// An exclusive fullscreen window that changes the video mode of a monitor.
+    Exclusive,
+}
+
+fn parse_window_mode(value: &str) -> Result<WindowMode, String> {
+    match value {
+        "windowed" => Ok(WindowMode::Windowed),
+        "borderless" => Ok(WindowMode::Borderless),
+        "exclusive" => Ok(WindowMode::Exclusive),
+        _ => Err(format!("unknown window mode '{}'", value)),
+    }
+}
+
+fn parse_resolution(value: &str) -> Result<(u32, u32), String> {
+    let Some((width, height)) = value.split_once('x') else {
+        return Err(format!("resolution '{}' isn't WIDTHxHEIGHT", value));
+    };
+    match (width.trim().parse::<u32>(), height.trim().parse::<u32>()) {
+        (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err(format!("invalid resolution '{}'", value)),
+    }
+}
+
+fn parse_u32(value: &str) -> Result<u32, String> {
+    value
+        .parse::<u32>()
+        .map_err(|e| format!("invalid number '{}': {}", value, e))
+}
+
+/// An enum for enumerating the ways to select an adapter.
+#[derive(Clone, Debug)]
+pub enum AdapterSelection {
+    /// The index in the adapters enumerated on the backend.
+    Index(usize),
+    /// A case-insensitive part of the adapter name.
+    Name(String),
+}
+
+fn parse_adapter_selection(value: &str) -> Result<Option<AdapterSelection>, String> {
+    if value.is_empty() || value == "auto" {
+        Ok(None)
+    } else if let Ok(n) = value.parse::<usize>() {
+        Ok(Some(AdapterSelection::Index(n)))
+    } else {
+        Ok(Some(AdapterSelection::Name(value.to_string())))
+    }
+}
+
+fn parse_backend(value: &str) -> Result<Option<Backends>, String> {
+    match value {
+        "auto" => Ok(None),
+        "vulkan" => Ok(Some(Backends::VULKAN)),
+        "gl" => Ok(Some(Backends::GL)),
+        "dx12" => Ok(Some(Backends::DX12)),
+        "metal" => Ok(Some(Backends::METAL)),
+        _ => Err(format!("unknown backend '{}'", value)),
+    }
+}
+
+fn parse_power_preference(value: &str) -> Result<PowerPreference, String> {
+    match value {
+        "default" => Ok(PowerPreference::None),
+        "low" => Ok(PowerPreference::LowPower),
+        "high" => Ok(PowerPreference::HighPerformance),
+        _ => Err(format!("unknown power preference '{}'", value)),
+    }
+}
+
+fn parse_present_mode(value: &str) -> Result<PresentMode, String> {
+    match value {
+        "fifo" => Ok(PresentMode::Fifo),
+        "mailbox" => Ok(PresentMode::Mailbox),
+        "immediate" => Ok(PresentMode::Immediate),
+        _ => Err(format!("unknown present mode '{}'", value)),
+    }
+}
+
+fn parse_mouse_sensitivity(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(n) if n > 0.0 => Ok(n),
+        Ok(_) => Err(format!("mouse sensitivity '{}' isn't positive", value)),
+        Err(e) => Err(format!("invalid mouse sensitivity '{}': {}", value, e)),
+    }
+}
+
+fn parse_brightness(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(n) if n > 0.0 => Ok(n),
+        Ok(_) => Err(format!("brightness '{}' isn't positive", value)),
+        Err(e) => Err(format!("invalid brightness '{}': {}", value, e)),
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("'{}' isn't true or false", value)),
+    }
+}
+
+fn parse_size(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(n) if n >= 0.0 => Ok(n),
+        Ok(_) => Err(format!("size '{}' is negative", value)),
+        Err(e) => Err(format!("invalid size '{}': {}", value, e)),
+    }
+}
+
+fn parse_opacity(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(n) if (0.0..=1.0).contains(&n) => Ok(n),
+        Ok(_) => Err(format!("opacity '{}' isn't from 0 to 1", value)),
+        Err(e) => Err(format!("invalid opacity '{}': {}", value, e)),
+    }
+}
+
+fn parse_volume(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(n) if (0.0..=1.0).contains(&n) => Ok(n),
+        Ok(_) => Err(format!("volume '{}' isn't from 0 to 1", value)),
+        Err(e) => Err(format!("invalid volume '{}': {}", value, e)),
+    }
+}
+
+fn parse_color(value: &str) -> Result<(u8, u8, u8), String> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(format!("color '{}' isn't #RRGGBB", value));
+    }
+    let parse = |range| {
+        u8::from_str_radix(&hex[range], 16).map_err(|_| format!("invalid color '{}'", value))
+    };
+    Ok((parse(0..2)?, parse(2..4)?, parse(4..6)?))
+}
+
+fn parse_frame_rate_cap(value: &str) -> Result<Option<u32>, String> {
+    match value.parse::<u32>() {
+        Ok(0) => Ok(None),
+        Ok(n) => Ok(Some(n)),
+        Err(e) => Err(format!("invalid frame rate cap '{}': {}", value, e)),
+    }
+}
+
+/// A struct for the settings of the crosshair.
+///
+/// The sizes are in logical px.
+#[derive(Clone, Debug)]
+pub struct CrosshairSettings {
+    pub has_lines: bool,
+    pub has_dot: bool,
+    pub has_circle: bool,
+    /// The distance from the center to the lines and the radius of the circle.
+    pub gap: f32,
+    pub thickness: f32,
+    /// The length of each line.
+    pub length: f32,
+    /// The width of the black outline. 0.0 means no outline.
+    pub outline: f32,
+    /// The color in sRGB.
+    pub color: (u8, u8, u8),
+    pub opacity: f32,
+    /// Whether the gap spreads while the player is moving.
+    pub is_spread: bool,
+}
+
+impl Default for CrosshairSettings {
+    fn default() -> Self {
+        Self {
+            has_lines: true,
+            has_dot: false,
+            has_circle: false,
+            gap: 6.0,
+            thickness: 2.0,
+            length: 8.0,
+            outline: 1.0,
+            color: (0, 255, 0),
+            opacity: 0.9,
+            is_spread: true,
+        }
+    }
+}
+
+/// A struct for the settings of the volume buses.
+///
+/// The volumes are from 0 to 1.
+#[derive(Clone, Debug)]
+pub struct VolumeSettings {
+    /// The volume multiplied to all the sounds.
+    pub master: f32,
+    pub music: f32,
+    /// The volume of the sound effects.
+    pub sfx: f32,
+}
+
+impl Default for VolumeSettings {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            music: 0.5,
+            sfx: 1.0,
+        }
+    }
+}
+
+/// A struct for user settings.
+#[derive(Clone)]
+pub struct Settings {
+    /// The requested present mode.
+    /// If the surface doesn't support it, `Fifo` is used instead.
+    pub present_mode: PresentMode,
+    /// The maximum frames per second.
+    /// If it's `None`, the frame rate is limited only by the present mode.
+    pub frame_rate_cap: Option<u32>,
+    pub window_mode: WindowMode,
+    /// The index of the monitor to create the window on.
+    /// If it's `None`, the primary monitor is used.
+    pub monitor: Option<usize>,
+    /// The inner size of the window in windowed mode or the video mode size in exclusive mode.
+    /// If it's `None`, a suitable size is chosen from the monitor.
+    pub resolution: Option<(u32, u32)>,
+    /// The refresh rate (Hz) of the video mode in exclusive mode.
+    /// If it's `None`, the highest refresh rate is chosen.
+    pub refresh_rate: Option<u32>,
+    /// The graphics backend.
+    /// If it's `None`, the default backend of the platform is used.
+    pub backend: Option<Backends>,
+    /// The way to select an adapter.
+    /// If it's `None`, an adapter is requested with `power_preference`.
+    pub adapter: Option<AdapterSelection>,
+    pub power_preference: PowerPreference,
+    /// The multiplier of the camera rotation by the mouse.
+    pub mouse_sensitivity: f32,
+    /// The multiplier of the exposure of the scene.
+    pub brightness: f32,
+    pub has_bloom: bool,
+    pub crosshair: CrosshairSettings,
+    pub volume: VolumeSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            present_mode: PresentMode::Fifo,
+            frame_rate_cap: None,
+            window_mode: WindowMode::Borderless,
+            monitor: None,
+            resolution: None,
+            refresh_rate: None,
+            backend: None,
+            adapter: None,
+            power_preference: PowerPreference::None,
+            mouse_sensitivity: 1.0,
+            brightness: 1.0,
+            has_bloom: true,
+            crosshair: CrosshairSettings::default(),
+            volume: VolumeSettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// A static method to load settings from a file.
+    ///
+    /// WARN: It never fails.
+    ///       If the file doesn't exist, it returns the default settings.
+    ///       Invalid lines are reported and ignored.
+    pub fn load(path: &str) -> Self {
+        let mut settings = Self::default();
+        let text = match fs::read_to_string(path) {
+            Ok(n) => n,
+            Err(e) => {
+                info!(
+                    "Settings.load",
+                    "default settings are used: {}: {}",
+                    path,
+                    e.to_string()
+                );
+                return settings;
+            }
+        };
+        for (i, n) in text.lines().enumerate() {
+            if let Err(e) = settings.apply_line(n) {
+                warn!("Settings.load", "{}:{}: {}", path, i + 1, e);
+            }
+        }
+        info!("Settings.load", "settings loaded: {}", path);
+        settings
+    }
+
+    fn apply_line(&mut self, line: &str) -> Result<(), String> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("'=' is not found in '{}'", line));
+        };
+        self.set(key.trim(), value.trim())
+    }
+
+    /// A method to set a value by a key.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "present_mode" => self.present_mode = parse_present_mode(value)?,
+            "frame_rate_cap" => self.frame_rate_cap = parse_frame_rate_cap(value)?,
+            "window_mode" => self.window_mode = parse_window_mode(value)?,
+            "monitor" => self.monitor = Some(parse_u32(value)? as usize),
+            "resolution" => self.resolution = Some(parse_resolution(value)?),
+            "refresh_rate" => self.refresh_rate = Some(parse_u32(value)?),
+            "backend" => self.backend = parse_backend(value)?,
+            "adapter" => self.adapter = parse_adapter_selection(value)?,
+            "power_preference" => self.power_preference = parse_power_preference(value)?,
+            "mouse_sensitivity" => self.mouse_sensitivity = parse_mouse_sensitivity(value)?,
+            "brightness" => self.brightness = parse_brightness(value)?,
+            "bloom" => self.has_bloom = parse_bool(value)?,
+            "crosshair_lines" => self.crosshair.has_lines = parse_bool(value)?,
+            "crosshair_dot" => self.crosshair.has_dot = parse_bool(value)?,
+            "crosshair_circle" => self.crosshair.has_circle = parse_bool(value)?,
+            "crosshair_gap" => self.crosshair.gap = parse_size(value)?,
+            "crosshair_thickness" => self.crosshair.thickness = parse_size(value)?,
+            "crosshair_length" => self.crosshair.length = parse_size(value)?,
+            "crosshair_outline" => self.crosshair.outline = parse_size(value)?,
+            "crosshair_color" => self.crosshair.color = parse_color(value)?,
+            "crosshair_opacity" => self.crosshair.opacity = parse_opacity(value)?,
+            "crosshair_spread" => self.crosshair.is_spread = parse_bool(value)?,
+            "master_volume" => self.volume.master = parse_volume(value)?,
+            "music_volume" => self.volume.music = parse_volume(value)?,
+            "sfx_volume" => self.volume.sfx = parse_volume(value)?,
+            _ => return Err(format!("unknown key '{}'", key)),
+        }
+        Ok(())
+    }
+
+    /// A method to get a value by a key in the format of the settings file.
+    ///
+    /// If the value isn't specified (e.g. `monitor` is `None`), it returns `None`.
+    pub fn get(&self, key: &str) -> Option<String> {
+        let value = match key {
+            "present_mode" => match self.present_mode {
+                PresentMode::Mailbox => "mailbox".to_string(),
+                PresentMode::Immediate => "immediate".to_string(),
+                _ => "fifo".to_string(),
+            },
+            "frame_rate_cap" => self.frame_rate_cap.unwrap_or(0).to_string(),
+            "window_mode" => match self.window_mode {
+                WindowMode::Windowed => "windowed".to_string(),
+                WindowMode::Borderless => "borderless".to_string(),
+                WindowMode::Exclusive => "exclusive".to_string(),
+            },
+            "monitor" => self.monitor?.to_string(),
+            "resolution" => {
+                let (width, height) = self.resolution?;
+                format!("{}x{}", width, height)
+            }
+            "refresh_rate" => self.refresh_rate?.to_string(),
+            "backend" => match self.backend {
+                Some(Backends::VULKAN) => "vulkan".to_string(),
+                Some(Backends::GL) => "gl".to_string(),
+                Some(Backends::DX12) => "dx12".to_string(),
+                Some(Backends::METAL) => "metal".to_string(),
+                _ => "auto".to_string(),
+            },
+            "adapter" => match &self.adapter {
+                Some(AdapterSelection::Index(n)) => n.to_string(),
+                Some(AdapterSelection::Name(n)) => n.clone(),
+                None => "auto".to_string(),
+            },
+            "power_preference" => match self.power_preference {
+                PowerPreference::LowPower => "low".to_string(),
+                PowerPreference::HighPerformance => "high".to_string(),
+                _ => "default".to_string(),
+            },
+            "mouse_sensitivity" => self.mouse_sensitivity.to_string(),
+            "brightness" => self.brightness.to_string(),
+            "bloom" => self.has_bloom.to_string(),
+            "crosshair_lines" => self.crosshair.has_lines.to_string(),
+            "crosshair_dot" => self.crosshair.has_dot.to_string(),
+            "crosshair_circle" => self.crosshair.has_circle.to_string(),
+            "crosshair_gap" => self.crosshair.gap.to_string(),
+            "crosshair_thickness" => self.crosshair.thickness.to_string(),
+            "crosshair_length" => self.crosshair.length.to_string(),
+            "crosshair_outline" => self.crosshair.outline.to_string(),
+            "crosshair_color" => {
+                let (r, g, b) = self.crosshair.color;
+                format!("#{:02x}{:02x}{:02x}", r, g, b)
+            }
+            "crosshair_opacity" => self.crosshair.opacity.to_string(),
+            "crosshair_spread" => self.crosshair.is_spread.to_string(),
+            "master_volume" => self.volume.master.to_string(),
+            "music_volume" => self.volume.music.to_string(),
+            "sfx_volume" => self.volume.sfx.to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// A method to write some settings to a file.
+    ///
+    /// The lines of `keys` are replaced or appended and the other lines are kept as they are.
+    ///
+    /// WARN: The values overridden by command line options are also written,
+    ///       so `keys` should be only the ones the user edited.
+    pub fn save(&self, path: &str, keys: &[&str]) -> Result<(), String> {
+        // NOTE: If the file doesn't exist, it's created.
+        let text = fs::read_to_string(path).unwrap_or_default();
+        let mut rest = keys.to_vec();
+        let mut lines = Vec::new();
+        for line in text.lines() {
+            let key = line.split_once('=').map(|(n, _)| n.trim());
+            match key {
+                Some(key) if !line.trim_start().starts_with('#') && keys.contains(&key) => {
+                    // NOTE: The duplicated lines are removed not to override the value.
+                    if !rest.contains(&key) {
+                        continue;
+                    }
+                    rest.retain(|n| *n != key);
+                    if let Some(value) = self.get(key) {
+                        lines.push(format!("{} = {}", key, value));
+                    }
+                }
+                _ => lines.push(line.to_string()),
+            }
+        }
+        for key in rest {
+            if let Some(value) = self.get(key) {
+                lines.push(format!("{} = {}", key, value));
+            }
+        }
+        lines.push(String::new());
+        fs::write(path, lines.join("\n")).map_err(|e| format!("{}: {}", path, e))
+    }
+}
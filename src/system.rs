@@ -1,2 +1,6 @@
+pub mod asset;
+pub mod audio;
 pub mod input;
 pub mod renderer;
+pub mod replay;
+pub mod window;
@@ -0,0 +1,467 @@
+//! A module for loading assets at runtime.
+//!
+//! An asset is specified by a path relative to the asset directory, e.g. `shader/world.wgsl`.
+//! It's searched in the following order and the first one found is used.
+//!
+//! 1. The directory specified by `--asset-dir`
+//! 2. The assets embedded in the binary if the `embed-assets` feature is enabled
+//! 3. The directory of the executable
+//! 4. The current directory
+//!
+//! NOTE: The shaders and the textures are specified by typed handles
+//!       so that an asset can't be passed to a wrong loader.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    env,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// The interval between polls of the watched files.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A macro to define the assets embedded in the binary by the paths relative to the asset directory.
+///
+/// NOTE: The paths are also defined in tests to check that the embedded assets are enough.
+macro_rules! embed {
+    ($($path:literal),* $(,)?) => {
+        /// The paths of all the assets embedded in the binary.
+        #[cfg(test)]
+        const EMBEDDED_ASSET_PATHS: &[&str] = &[$($path),*];
+
+        /// All the assets embedded in the binary.
+        #[cfg(feature = "embed-assets")]
+        const EMBEDDED_ASSETS: &[(&str, &[u8])] = &[$((
+            $path,
+            include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path)) as &[u8],
+        )),*];
+    };
+}
+
+embed!(
+    "shader/world.wgsl",
+    "shader/skybox.wgsl",
+    "shader/ui.wgsl",
+    "shader/crosshair.wgsl",
+    "shader/particle.wgsl",
+    "shader/post.wgsl",
+    "shader/mipmap.wgsl",
+    "image/skybox.png",
+    "image/ui.png",
+    "image/font.png",
+    "image/font.fnt",
+    "image/warehouse.hdr",
+    "model/crate.glb",
+    "level/default.txt",
+    "level/warehouse.txt",
+);
+
+/// A trait for typed handles of assets.
+pub trait AssetHandle: Copy {
+    /// A method to get the path relative to the asset directory.
+    fn get_path(self) -> &'static str;
+}
+
+/// A enum for the handles of WGSL shaders.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Debug)]
+pub enum ShaderHandle {
+    World,
+    Skybox,
+    Ui,
+    Crosshair,
+    Particle,
+    Post,
+    /// The shader generating the mip levels of textures.
+    Mipmap,
+}
+
+/// All the shaders.
+pub const SHADER_HANDLES: [ShaderHandle; 7] = [
+    ShaderHandle::World,
+    ShaderHandle::Skybox,
+    ShaderHandle::Ui,
+    ShaderHandle::Crosshair,
+    ShaderHandle::Particle,
+    ShaderHandle::Post,
+    ShaderHandle::Mipmap,
+];
+
+impl AssetHandle for ShaderHandle {
+    fn get_path(self) -> &'static str {
+        match self {
+            Self::World => "shader/world.wgsl",
+            Self::Skybox => "shader/skybox.wgsl",
+            Self::Ui => "shader/ui.wgsl",
+            Self::Crosshair => "shader/crosshair.wgsl",
+            Self::Particle => "shader/particle.wgsl",
+            Self::Post => "shader/post.wgsl",
+            Self::Mipmap => "shader/mipmap.wgsl",
+        }
+    }
+}
+
+/// A enum for the handles of PNG textures.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Debug)]
+pub enum TextureHandle {
+    Skybox,
+    Ui,
+    /// The page image of the font.
+    Font,
+}
+
+/// All the textures.
+pub const TEXTURE_HANDLES: [TextureHandle; 3] = [
+    TextureHandle::Skybox,
+    TextureHandle::Ui,
+    TextureHandle::Font,
+];
+
+impl AssetHandle for TextureHandle {
+    fn get_path(self) -> &'static str {
+        match self {
+            Self::Skybox => "image/skybox.png",
+            Self::Ui => "image/ui.png",
+            Self::Font => "image/font.png",
+        }
+    }
+}
+
+/// An enum for enumerating errors of loading an asset.
+#[derive(Debug)]
+pub enum AssetError {
+    /// The asset isn't found in any asset directory.
+    NotFound(String),
+    /// Failed to read the asset file.
+    Read(PathBuf, io::Error),
+    /// The text asset isn't valid UTF-8.
+    InvalidText(String),
+}
+
+impl Display for AssetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(path) => write!(f, "the asset isn't found: {}", path),
+            Self::Read(path, e) => write!(f, "failed to read {}: {}", path.display(), e),
+            Self::InvalidText(path) => write!(f, "the asset isn't UTF-8: {}", path),
+        }
+    }
+}
+
+impl Error for AssetError {}
+
+/// An asset manager that resolves the paths of assets and reads them.
+pub struct AssetManager {
+    /// The directory specified by the user, which takes priority over the others.
+    override_directory: Option<PathBuf>,
+    /// The directories searched after the embedded assets.
+    directories: Vec<PathBuf>,
+}
+
+impl AssetManager {
+    /// A constructor.
+    ///
+    /// * `override_directory` - the directory searched first, e.g. to try modified assets
+    pub fn new(override_directory: Option<&str>) -> Self {
+        let mut directories = Vec::new();
+        if let Some(n) = env::current_exe()
+            .ok()
+            .and_then(|n| Some(n.parent()?.to_path_buf()))
+        {
+            directories.push(n);
+        }
+        if let Ok(n) = env::current_dir() {
+            directories.push(n);
+        }
+        let asset_manager = Self {
+            override_directory: override_directory.map(PathBuf::from),
+            directories,
+        };
+        info!(
+            "AssetManager.new",
+            "assets are searched in: {}.",
+            asset_manager.describe_search_order()
+        );
+        asset_manager
+    }
+
+    fn describe_search_order(&self) -> String {
+        let mut sources = Vec::new();
+        if let Some(n) = &self.override_directory {
+            sources.push(n.display().to_string());
+        }
+        if cfg!(feature = "embed-assets") {
+            sources.push("(embedded)".to_string());
+        }
+        sources.extend(self.directories.iter().map(|n| n.display().to_string()));
+        sources.join(", ")
+    }
+
+    /// A method to get the path of an asset file or a directory of assets on the file system.
+    ///
+    /// It follows the same order as `read()`, so it's the file that is actually read.
+    /// If the asset is embedded and not overridden, or it isn't found, it returns `None`.
+    pub fn resolve(&self, path: &str) -> Option<PathBuf> {
+        let overridden = self
+            .override_directory
+            .as_ref()
+            .map(|n| n.join(path))
+            .filter(|n| n.exists());
+        if overridden.is_some() || get_embedded(path).is_some() {
+            return overridden;
+        }
+        self.directories
+            .iter()
+            .map(|n| n.join(path))
+            .find(|n| n.exists())
+    }
+
+    /// A method to list the assets in a directory with an extension, e.g. the level files.
+    ///
+    /// The assets of all the sources, including the embedded ones, are listed without duplicates.
+    /// It returns the paths relative to the asset directory sorted by the path.
+    pub fn list(&self, directory: &str, extension: &str) -> Vec<String> {
+        let is_listed = |n: &Path| n.extension().is_some_and(|n| n == extension);
+        let mut paths = get_embedded_paths()
+            .into_iter()
+            .filter(|n| {
+                n.strip_prefix(directory)
+                    .and_then(|n| n.strip_prefix('/'))
+                    .is_some_and(|n| !n.contains('/') && is_listed(Path::new(n)))
+            })
+            .map(|n| n.to_string())
+            .collect::<Vec<String>>();
+        // NOTE: A directory that doesn't exist is skipped since the assets may be embedded.
+        for n in self.override_directory.iter().chain(&self.directories) {
+            let Ok(entries) = fs::read_dir(n.join(directory)) else {
+                continue;
+            };
+            paths.extend(
+                entries
+                    .filter_map(|n| n.ok())
+                    .map(|n| n.path())
+                    .filter(|n| n.is_file() && is_listed(n))
+                    .filter_map(|n| Some(format!("{}/{}", directory, n.file_name()?.to_str()?))),
+            );
+        }
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    /// A method to read an asset by a path relative to the asset directory.
+    pub fn read(&self, path: &str) -> Result<Cow<'static, [u8]>, AssetError> {
+        match (self.resolve(path), get_embedded(path)) {
+            (Some(n), _) => read_file(n),
+            (None, Some(n)) => Ok(Cow::Borrowed(n)),
+            (None, None) => Err(AssetError::NotFound(path.to_string())),
+        }
+    }
+
+    /// A method to read a text asset by a path relative to the asset directory.
+    pub fn read_text(&self, path: &str) -> Result<String, AssetError> {
+        String::from_utf8(self.read(path)?.into_owned())
+            .map_err(|_| AssetError::InvalidText(path.to_string()))
+    }
+
+    /// A method to load the source of a shader.
+    pub fn load_shader(&self, handle: ShaderHandle) -> Result<String, AssetError> {
+        self.read_text(handle.get_path())
+    }
+
+    /// A method to load the encoded PNG data of a texture.
+    pub fn load_texture(&self, handle: TextureHandle) -> Result<Cow<'static, [u8]>, AssetError> {
+        self.read(handle.get_path())
+    }
+}
+
+/// A enum for the assets reloaded when their files are modified.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Debug)]
+pub enum WatchedAsset {
+    Shader(ShaderHandle),
+    Texture(TextureHandle),
+    /// The level being played or selected.
+    Level,
+    /// The model files used by the props of the level.
+    LevelModels,
+    /// The image files of the skybox of the level.
+    LevelSkybox,
+}
+
+/// A watcher that polls the modification times of asset files for hot-reload.
+///
+/// NOTE: The files are polled instead of being notified by the OS to avoid a dependency.
+///       It's cheap because only a few files are watched.
+pub struct AssetWatcher {
+    files: HashMap<PathBuf, (WatchedAsset, Option<SystemTime>)>,
+    last_poll_time: Instant,
+}
+
+impl AssetWatcher {
+    /// A constructor.
+    pub fn new() -> Self {
+        Self {
+            files: HashMap::new(),
+            last_poll_time: Instant::now(),
+        }
+    }
+
+    /// A method to start watching the file of an asset.
+    ///
+    /// If the asset is already watched at another path, the old path isn't watched anymore.
+    pub fn watch(&mut self, asset: WatchedAsset, path: PathBuf) {
+        self.watch_files(asset, &[path]);
+    }
+
+    /// A method to start watching the files of an asset.
+    ///
+    /// The files watched for the asset but not in `paths` aren't watched anymore.
+    ///
+    /// NOTE: If a file is already watched for another asset, it isn't watched for this one.
+    pub fn watch_files(&mut self, asset: WatchedAsset, paths: &[PathBuf]) {
+        self.files
+            .retain(|path, (n, _)| *n != asset || paths.contains(path));
+        for path in paths {
+            if !self.files.contains_key(path) {
+                let modified = get_modified_time(path);
+                self.files.insert(path.clone(), (asset, modified));
+            }
+        }
+    }
+
+    /// A method to start watching the files of all the shaders and the textures.
+    ///
+    /// NOTE: The embedded assets can't be modified, so they aren't watched.
+    pub fn watch_all(&mut self, assets: &AssetManager) {
+        let shaders = SHADER_HANDLES
+            .iter()
+            .map(|n| (WatchedAsset::Shader(*n), n.get_path()));
+        let textures = TEXTURE_HANDLES
+            .iter()
+            .map(|n| (WatchedAsset::Texture(*n), n.get_path()));
+        for (asset, path) in shaders.chain(textures) {
+            if let Some(n) = assets.resolve(path) {
+                self.watch(asset, n);
+            }
+        }
+    }
+
+    /// A method to get the assets whose files are modified since the last poll.
+    ///
+    /// NOTE: The files are checked at most once per `POLL_INTERVAL`.
+    pub fn poll(&mut self) -> Vec<WatchedAsset> {
+        if self.last_poll_time.elapsed() < POLL_INTERVAL {
+            return Vec::new();
+        }
+        self.last_poll_time = Instant::now();
+        let mut assets = Vec::new();
+        for (path, (asset, modified)) in &mut self.files {
+            let n = get_modified_time(path);
+            if n != *modified {
+                *modified = n;
+                assets.push(*asset);
+            }
+        }
+        assets
+    }
+}
+
+/// A function to get the modification time of a file.
+///
+/// While a file is being replaced, it may not exist, so it returns `None`.
+fn get_modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|n| n.modified()).ok()
+}
+
+fn read_file(path: PathBuf) -> Result<Cow<'static, [u8]>, AssetError> {
+    match fs::read(&path) {
+        Ok(n) => Ok(Cow::Owned(n)),
+        Err(e) => Err(AssetError::Read(path, e)),
+    }
+}
+
+/// A function to get the paths of all the assets embedded in the binary.
+#[cfg(feature = "embed-assets")]
+fn get_embedded_paths() -> Vec<&'static str> {
+    EMBEDDED_ASSETS.iter().map(|(n, _)| *n).collect()
+}
+
+/// A function to get the paths of all the assets embedded in the binary.
+///
+/// NOTE: Nothing is embedded without the `embed-assets` feature.
+#[cfg(not(feature = "embed-assets"))]
+fn get_embedded_paths() -> Vec<&'static str> {
+    Vec::new()
+}
+
+/// A function to get an asset embedded in the binary.
+#[cfg(feature = "embed-assets")]
+fn get_embedded(path: &str) -> Option<&'static [u8]> {
+    EMBEDDED_ASSETS
+        .iter()
+        .find(|(n, _)| *n == path)
+        .map(|(_, n)| *n)
+}
+
+/// A function to get an asset embedded in the binary.
+///
+/// NOTE: Nothing is embedded without the `embed-assets` feature.
+#[cfg(not(feature = "embed-assets"))]
+fn get_embedded(_path: &str) -> Option<&'static [u8]> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::level::{self, Level};
+
+    #[test]
+    fn bundled_assets_are_embedded() {
+        for n in SHADER_HANDLES.map(|n| n.get_path()) {
+            assert!(EMBEDDED_ASSET_PATHS.contains(&n), "{}", n);
+        }
+        for n in TEXTURE_HANDLES.map(|n| n.get_path()) {
+            assert!(EMBEDDED_ASSET_PATHS.contains(&n), "{}", n);
+        }
+
+        // the bundled levels listed as on the level select screen and the assets they reference
+        let assets = AssetManager::new(None);
+        let levels = level::list_levels(&assets);
+        assert!(!levels.is_empty());
+        for (_, path) in &levels {
+            assert!(EMBEDDED_ASSET_PATHS.contains(&path.as_str()), "{}", path);
+            let level = Level::load_asset(&assets, path).unwrap();
+            let mut paths = level.get_model_paths();
+            paths.extend(level.get_skybox().get_paths());
+            for n in paths {
+                assert!(EMBEDDED_ASSET_PATHS.contains(&n), "{} in {}", n, path);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "embed-assets")]
+    fn embedded_levels_are_selectable_without_asset_directory() {
+        let mut assets = AssetManager::new(None);
+        assets.directories.clear();
+        let levels = level::list_levels(&assets);
+        let Some((_, path)) = levels.iter().find(|(n, _)| n == "warehouse") else {
+            panic!("warehouse isn't listed in {:?}", levels);
+        };
+        let level = Level::load_asset(&assets, path).unwrap();
+        assert_eq!(level.name, "warehouse");
+        assert!(level.path.is_none());
+        let mut paths = level.get_model_paths();
+        paths.extend(level.get_skybox().get_paths());
+        assert!(paths.contains(&"model/crate.glb"));
+        assert!(paths.contains(&"image/warehouse.hdr"));
+        for n in paths {
+            assert!(assets.read(n).is_ok(), "{}", n);
+        }
+    }
+}
@@ -0,0 +1,310 @@
+#[cfg(feature = "audio")]
+mod device;
+mod mixer;
+pub mod music;
+pub mod sound;
+pub mod wav;
+
+use crate::{settings::VolumeSettings, util::camera::CameraController};
+use glam::Vec3;
+use mixer::Mixer;
+use music::MusicId;
+use sound::SoundId;
+use std::{
+    f32::consts::{FRAC_PI_4, SQRT_2},
+    sync::{Arc, Mutex},
+};
+
+/// The sample rate of the offline output.
+const OFFLINE_SAMPLE_RATE: u32 = 48000;
+/// The count of stereo samples rendered by the offline output in a frame.
+///
+/// NOTE: The offline output assumes 60 frames per second.
+const OFFLINE_SAMPLES_PER_FRAME: usize = OFFLINE_SAMPLE_RATE as usize / 60;
+/// The distance within which a sound isn't attenuated.
+const REFERENCE_DISTANCE: f32 = 2.0;
+/// How fast a sound is attenuated beyond `REFERENCE_DISTANCE`.
+const ROLLOFF: f32 = 0.5;
+
+/// A function to get the gains of the left and the right channels of a sound on the world.
+///
+/// The sound is panned with the equal-power law by its direction from the listener
+/// and attenuated by the inverse of its distance.
+/// NOTE: The gains are normalized so that a sound at the listener plays at the full volume on both sides.
+pub fn get_gains(listener: &CameraController, position: Vec3) -> (f32, f32) {
+    let relative = listener
+        .rotation
+        .inverse()
+        .mul_vec3(position - listener.position);
+    let distance = relative.length();
+    let pan = if distance > f32::EPSILON {
+        relative.x / distance
+    } else {
+        0.0
+    };
+    let attenuation = REFERENCE_DISTANCE
+        / (REFERENCE_DISTANCE + (distance - REFERENCE_DISTANCE).max(0.0) * ROLLOFF);
+    let angle = (pan + 1.0) * FRAC_PI_4;
+    (
+        angle.cos() * SQRT_2 * attenuation,
+        angle.sin() * SQRT_2 * attenuation,
+    )
+}
+
+/// A enum for enumerating requests for the audio.
+pub enum AudioRequest {
+    /// Move the listener, usually the camera of the player.
+    UpdateListener(CameraController),
+    /// Play a sound at the listener, e.g. an UI sound.
+    Play(SoundId),
+    /// Play a sound at a position on the world.
+    PlayAt(SoundId, Vec3),
+    /// Crossfade into music played in a loop, or fade out the music if it's `None`.
+    PlayMusic(Option<MusicId>),
+}
+
+/// An enum for enumerating the outputs of the audio.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AudioBackend {
+    /// The sounds are discarded.
+    Null,
+    /// The sounds are rendered into a buffer every frame instead of a device, e.g. for tests.
+    Offline,
+    /// The sounds are played on the default output device.
+    ///
+    /// NOTE: It requires the `audio` feature. Otherwise, or if no device is available, `Null` is used instead.
+    Device,
+}
+
+enum Output {
+    Null,
+    /// The interleaved stereo samples rendered so far.
+    Offline(Vec<f32>),
+    /// NOTE: The stream is never read, but it must be kept alive while playing.
+    #[cfg(feature = "audio")]
+    Device {
+        _stream: cpal::Stream,
+    },
+}
+
+/// An audio system playing sound effects and music requested by scenes.
+pub struct Audio {
+    output: Output,
+    mixer: Arc<Mutex<Mixer>>,
+    listener: CameraController,
+}
+
+impl Audio {
+    /// A constructor.
+    pub fn new(backend: AudioBackend) -> Self {
+        let (output, mixer) = match backend {
+            AudioBackend::Null => (Output::Null, Mixer::new(OFFLINE_SAMPLE_RATE)),
+            AudioBackend::Offline => (Output::Offline(Vec::new()), Mixer::new(OFFLINE_SAMPLE_RATE)),
+            AudioBackend::Device => return Self::open_device(),
+        };
+        Self {
+            output,
+            mixer: Arc::new(Mutex::new(mixer)),
+            listener: CameraController::default(),
+        }
+    }
+
+    /// A static method to create the audio on the default output device.
+    #[cfg(feature = "audio")]
+    fn open_device() -> Self {
+        match device::open() {
+            Ok(device::DeviceOutput { stream, mixer }) => Self {
+                output: Output::Device { _stream: stream },
+                mixer,
+                listener: CameraController::default(),
+            },
+            Err(e) => {
+                warn!("Audio.new", "the audio is disabled: {}", e);
+                Self::new(AudioBackend::Null)
+            }
+        }
+    }
+
+    /// A static method to create the audio on the default output device.
+    #[cfg(not(feature = "audio"))]
+    fn open_device() -> Self {
+        info!(
+            "Audio.new",
+            "the audio is disabled because the audio feature isn't enabled."
+        );
+        Self::new(AudioBackend::Null)
+    }
+
+    /// A method to process the requests in a frame.
+    ///
+    /// The offline output renders the samples of a frame after the requests are processed.
+    pub fn process(&mut self, audio_requests: Vec<AudioRequest>) {
+        let Ok(mut mixer) = self.mixer.lock() else {
+            return;
+        };
+        let is_null = matches!(self.output, Output::Null);
+        for request in audio_requests {
+            match request {
+                AudioRequest::UpdateListener(n) => self.listener = n,
+                AudioRequest::Play(n) if !is_null => mixer.play(n, (1.0, 1.0)),
+                AudioRequest::PlayAt(n, position) if !is_null => {
+                    mixer.play(n, get_gains(&self.listener, position))
+                }
+                AudioRequest::PlayMusic(n) if !is_null => mixer.play_music(n),
+                _ => (),
+            }
+        }
+        if let Output::Offline(samples) = &mut self.output {
+            let start = samples.len();
+            samples.resize(start + OFFLINE_SAMPLES_PER_FRAME * 2, 0.0);
+            mixer.render(&mut samples[start..]);
+        }
+    }
+
+    /// A method to set the volumes of the buses.
+    ///
+    /// NOTE: The master volume is multiplied to the music and the sound effects.
+    pub fn set_volume(&mut self, volume: &VolumeSettings) {
+        if let Ok(mut n) = self.mixer.lock() {
+            n.set_gains(volume.master * volume.music, volume.master * volume.sfx);
+        }
+    }
+
+    /// A method to get the samples rendered so far as a WAV file.
+    ///
+    /// If the output isn't offline, it returns `None`.
+    pub fn get_wav(&self) -> Option<Vec<u8>> {
+        match &self.output {
+            Output::Offline(samples) => Some(wav::encode(samples, OFFLINE_SAMPLE_RATE, 2)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Quat;
+
+    /// A function to get the sums of the squares of the left and the right samples in a WAV file.
+    fn get_energies(wav: &[u8]) -> (f64, f64) {
+        let mut energies = (0.0, 0.0);
+        for (i, n) in wav[44..].chunks_exact(2).enumerate() {
+            let sample = i16::from_le_bytes([n[0], n[1]]) as f64 / i16::MAX as f64;
+            if i.is_multiple_of(2) {
+                energies.0 += sample * sample;
+            } else {
+                energies.1 += sample * sample;
+            }
+        }
+        energies
+    }
+
+    #[test]
+    fn offline_renders_wav_every_frame() {
+        let mut audio = Audio::new(AudioBackend::Offline);
+        audio.process(Vec::from([AudioRequest::Play(SoundId::Shot)]));
+        for _ in 0..9 {
+            audio.process(Vec::new());
+        }
+        let wav = audio.get_wav().unwrap();
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), 2);
+        assert_eq!(
+            u32::from_le_bytes([wav[24], wav[25], wav[26], wav[27]]),
+            OFFLINE_SAMPLE_RATE
+        );
+        let data_size = u32::from_le_bytes([wav[40], wav[41], wav[42], wav[43]]) as usize;
+        assert_eq!(data_size, OFFLINE_SAMPLES_PER_FRAME * 10 * 2 * 2);
+        assert_eq!(wav.len(), 44 + data_size);
+        let (left, right) = get_energies(&wav);
+        assert!(left > 0.0);
+        assert!((left - right).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn offline_is_silent_without_requests() {
+        let mut audio = Audio::new(AudioBackend::Offline);
+        audio.process(Vec::new());
+        assert_eq!(get_energies(&audio.get_wav().unwrap()), (0.0, 0.0));
+    }
+
+    #[test]
+    fn sound_is_panned_to_its_side() {
+        // NOTE: The camera turned right by 90 degrees faces +x, so +z is on the left.
+        let listener = CameraController {
+            rotation: Quat::from_rotation_y(90.0f32.to_radians()),
+            ..Default::default()
+        };
+        let mut audio = Audio::new(AudioBackend::Offline);
+        audio.process(Vec::from([
+            AudioRequest::UpdateListener(listener),
+            AudioRequest::PlayAt(SoundId::Hit, Vec3::new(0.0, 0.0, 1.0)),
+        ]));
+        let (left, right) = get_energies(&audio.get_wav().unwrap());
+        assert!(left > right * 10.0);
+    }
+
+    #[test]
+    fn far_sound_is_attenuated() {
+        let listener = CameraController::default();
+        let (near_left, near_right) = get_gains(&listener, Vec3::new(0.0, 0.0, 1.0));
+        let (far_left, far_right) = get_gains(&listener, Vec3::new(0.0, 0.0, 20.0));
+        assert!((near_left - 1.0).abs() < 1.0e-6 && (near_right - 1.0).abs() < 1.0e-6);
+        assert!(far_left < near_left * 0.5 && far_right < near_right * 0.5);
+    }
+
+    #[test]
+    fn music_crossfades_into_next_music() {
+        let mut audio = Audio::new(AudioBackend::Offline);
+        let frames = (mixer::CROSSFADE_DURATION * 60.0) as usize + 1;
+        audio.process(Vec::from([AudioRequest::PlayMusic(Some(MusicId::Title))]));
+        for _ in 0..frames {
+            audio.process(Vec::new());
+        }
+        audio.process(Vec::from([AudioRequest::PlayMusic(Some(MusicId::Game))]));
+        assert_eq!(audio.mixer.lock().unwrap().get_music_count(), 2);
+        for _ in 0..frames {
+            audio.process(Vec::new());
+        }
+        assert_eq!(audio.mixer.lock().unwrap().get_music_count(), 1);
+        let (left, right) = get_energies(&audio.get_wav().unwrap());
+        assert!(left > 0.0);
+        assert!((left - right).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn same_music_keeps_playing() {
+        let mut audio = Audio::new(AudioBackend::Offline);
+        audio.process(Vec::from([AudioRequest::PlayMusic(Some(MusicId::End))]));
+        audio.process(Vec::from([AudioRequest::PlayMusic(Some(MusicId::End))]));
+        assert_eq!(audio.mixer.lock().unwrap().get_music_count(), 1);
+    }
+
+    #[test]
+    fn muted_bus_is_silent() {
+        let mut audio = Audio::new(AudioBackend::Offline);
+        audio.set_volume(&VolumeSettings {
+            master: 1.0,
+            music: 0.0,
+            sfx: 1.0,
+        });
+        audio.process(Vec::from([AudioRequest::PlayMusic(Some(MusicId::Game))]));
+        assert_eq!(get_energies(&audio.get_wav().unwrap()), (0.0, 0.0));
+        audio.set_volume(&VolumeSettings {
+            master: 0.0,
+            music: 1.0,
+            sfx: 1.0,
+        });
+        audio.process(Vec::from([AudioRequest::Play(SoundId::Shot)]));
+        assert_eq!(get_energies(&audio.get_wav().unwrap()), (0.0, 0.0));
+    }
+
+    #[test]
+    fn null_output_has_no_wav() {
+        let mut audio = Audio::new(AudioBackend::Null);
+        audio.process(Vec::from([AudioRequest::Play(SoundId::Click)]));
+        assert!(audio.get_wav().is_none());
+    }
+}
@@ -0,0 +1,81 @@
+use super::mixer::Mixer;
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    BuildStreamError, Device, FromSample, SampleFormat, SizedSample, Stream, StreamConfig,
+};
+use std::{
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+/// A function to create an output stream pulling the samples from the mixer.
+///
+/// The stereo samples are downmixed for a mono device and the other channels are silent.
+fn build_stream<T>(
+    device: &Device,
+    config: &StreamConfig,
+    mixer: Arc<Mutex<Mixer>>,
+) -> Result<Stream, BuildStreamError>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let mut buffer = Vec::new();
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _| {
+            buffer.resize(data.len() / channels * 2, 0.0);
+            match mixer.lock() {
+                Ok(mut n) => n.render(&mut buffer),
+                Err(_) => buffer.fill(0.0),
+            }
+            for (frame, stereo) in data.chunks_exact_mut(channels).zip(buffer.chunks_exact(2)) {
+                for (i, n) in frame.iter_mut().enumerate() {
+                    let sample = match (channels, i) {
+                        (1, _) => (stereo[0] + stereo[1]) / 2.0,
+                        (_, 0) => stereo[0],
+                        (_, 1) => stereo[1],
+                        _ => 0.0,
+                    };
+                    *n = T::from_sample(sample);
+                }
+            }
+        },
+        |e| error!("Audio", "stream error: {}", e),
+        None,
+    )
+}
+
+/// An output stream on a device and the mixer it pulls the samples from.
+pub struct DeviceOutput {
+    /// WARN: The sound stops when the stream is dropped.
+    pub stream: Stream,
+    /// The mixer synthesized at the sample rate of the device.
+    pub mixer: Arc<Mutex<Mixer>>,
+}
+
+/// A function to start playing on the default output device.
+pub fn open() -> Result<DeviceOutput, Box<dyn Error>> {
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or("no output device is found.")?;
+    let supported_config = device.default_output_config()?;
+    let config = supported_config.config();
+    let mixer = Arc::new(Mutex::new(Mixer::new(config.sample_rate.0)));
+    let mixer_cloned = Arc::clone(&mixer);
+    let stream = match supported_config.sample_format() {
+        SampleFormat::F32 => build_stream::<f32>(&device, &config, mixer_cloned)?,
+        SampleFormat::I16 => build_stream::<i16>(&device, &config, mixer_cloned)?,
+        SampleFormat::U16 => build_stream::<u16>(&device, &config, mixer_cloned)?,
+        n => return Err(format!("the sample format isn't supported: {}", n).into()),
+    };
+    stream.play()?;
+    info!(
+        "Audio",
+        "the output device selected: {} ({} Hz, {} channels).",
+        device.name().unwrap_or_default(),
+        config.sample_rate.0,
+        config.channels
+    );
+    Ok(DeviceOutput { stream, mixer })
+}
@@ -0,0 +1,161 @@
+use super::{
+    music::{self, Music, MusicId},
+    sound::{self, Sound, SoundId},
+};
+use std::collections::HashMap;
+
+/// The max count of sounds played at the same time.
+///
+/// NOTE: If it's exceeded, the oldest sound is stopped.
+const MAX_VOICE_COUNT: usize = 32;
+/// The duration (seconds) of a crossfade between music.
+pub const CROSSFADE_DURATION: f32 = 1.5;
+
+/// A sound being played.
+struct Voice {
+    sound: SoundId,
+    /// The index of the next sample.
+    cursor: usize,
+    /// The gains of the left and the right channels.
+    gains: (f32, f32),
+}
+
+/// Music being played in a loop.
+struct MusicVoice {
+    music: MusicId,
+    cursor: usize,
+    /// The gain of the crossfade from 0 to 1.
+    gain: f32,
+    /// Whether the music fades out and stops.
+    is_fading_out: bool,
+}
+
+/// A mixer that sums the sounds being played into stereo samples.
+///
+/// It's shared by all the outputs, so the result of the offline output is the same as the device.
+pub struct Mixer {
+    sounds: HashMap<SoundId, Sound>,
+    voices: Vec<Voice>,
+    musics: HashMap<MusicId, Music>,
+    /// The music fading in or playing and the music fading out.
+    music_voices: Vec<MusicVoice>,
+    /// The change of the crossfade gain in a sample.
+    fade_step: f32,
+    /// The gain of the music bus.
+    music_gain: f32,
+    /// The gain of the sound effects bus.
+    sfx_gain: f32,
+}
+
+impl Mixer {
+    /// A constructor.
+    ///
+    /// All the sounds and the music are synthesized at `sample_rate`.
+    pub fn new(sample_rate: u32) -> Self {
+        let sounds = sound::SOUND_IDS
+            .iter()
+            .map(|n| (*n, Sound::synthesize(*n, sample_rate)))
+            .collect();
+        let musics = music::MUSIC_IDS
+            .iter()
+            .map(|n| (*n, Music::synthesize(*n, sample_rate)))
+            .collect();
+        Self {
+            sounds,
+            voices: Vec::new(),
+            musics,
+            music_voices: Vec::new(),
+            fade_step: 1.0 / (CROSSFADE_DURATION * sample_rate as f32),
+            music_gain: 1.0,
+            sfx_gain: 1.0,
+        }
+    }
+
+    /// A method to set the gains of the buses.
+    pub fn set_gains(&mut self, music_gain: f32, sfx_gain: f32) {
+        self.music_gain = music_gain;
+        self.sfx_gain = sfx_gain;
+    }
+
+    /// A method to crossfade into music.
+    ///
+    /// If `music` is `None`, the music fades out.
+    /// If the music is already playing, it keeps playing without restarting.
+    pub fn play_music(&mut self, music: Option<MusicId>) {
+        for n in &mut self.music_voices {
+            n.is_fading_out = Some(n.music) != music;
+        }
+        let Some(music) = music else {
+            return;
+        };
+        if !self.music_voices.iter().any(|n| n.music == music) {
+            self.music_voices.push(MusicVoice {
+                music,
+                cursor: 0,
+                gain: 0.0,
+                is_fading_out: false,
+            });
+        }
+    }
+
+    /// A method to get the count of the music being played, including the music fading out.
+    #[cfg(test)]
+    pub fn get_music_count(&self) -> usize {
+        self.music_voices.len()
+    }
+
+    /// A method to start playing a sound.
+    ///
+    /// * `gains` - the gains of the left and the right channels
+    pub fn play(&mut self, sound: SoundId, gains: (f32, f32)) {
+        if self.voices.len() >= MAX_VOICE_COUNT {
+            self.voices.remove(0);
+        }
+        self.voices.push(Voice {
+            sound,
+            cursor: 0,
+            gains,
+        });
+    }
+
+    /// A method to mix the sounds being played into interleaved stereo samples.
+    ///
+    /// The samples are overwritten and the finished sounds are removed.
+    pub fn render(&mut self, samples: &mut [f32]) {
+        samples.fill(0.0);
+        for voice in &mut self.voices {
+            let sound = &self.sounds[&voice.sound].samples;
+            for frame in samples.chunks_exact_mut(2) {
+                let Some(n) = sound.get(voice.cursor) else {
+                    break;
+                };
+                frame[0] += n * voice.gains.0 * self.sfx_gain;
+                frame[1] += n * voice.gains.1 * self.sfx_gain;
+                voice.cursor += 1;
+            }
+        }
+        self.voices
+            .retain(|n| n.cursor < self.sounds[&n.sound].samples.len());
+        for voice in &mut self.music_voices {
+            let music = &self.musics[&voice.music].samples;
+            for frame in samples.chunks_exact_mut(2) {
+                voice.gain = if voice.is_fading_out {
+                    (voice.gain - self.fade_step).max(0.0)
+                } else {
+                    (voice.gain + self.fade_step).min(1.0)
+                };
+                let n = music[voice.cursor] * voice.gain * self.music_gain;
+                frame[0] += n;
+                frame[1] += n;
+                voice.cursor = (voice.cursor + 1) % music.len();
+            }
+        }
+        self.music_voices
+            .retain(|n| !n.is_fading_out || n.gain > 0.0);
+
+        // NOTE: Clip the samples not to wrap around when converted into integers.
+        for n in samples.iter_mut() {
+            *n = n.clamp(-1.0, 1.0);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::f32::consts::PI;
+
+/// A enum for music id.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Debug)]
+pub enum MusicId {
+    Title,
+    Game,
+    /// The result of a game.
+    End,
+}
+
+/// All the music synthesized when the audio is created.
+pub const MUSIC_IDS: [MusicId; 3] = [MusicId::Title, MusicId::Game, MusicId::End];
+
+/// A struct for the score of a loop.
+///
+/// Each chord lasts a bar of 4 beats and is played as a pad, a bass and an arpeggio.
+struct Score {
+    /// The beats per minute.
+    tempo: f32,
+    /// The MIDI note numbers of the chords.
+    chords: &'static [[u8; 3]],
+    /// The count of the arpeggio notes in a beat.
+    arpeggio_division: u32,
+    /// The gains of the pad, the bass and the arpeggio.
+    gains: (f32, f32, f32),
+}
+
+const BEATS_PER_CHORD: u32 = 4;
+/// The duration (seconds) of the fade in and out of each chord of the pad.
+///
+/// NOTE: The fades prevent clicks where the chords change and where the loop wraps around.
+const PAD_FADE: f32 = 0.05;
+
+fn get_score(id: MusicId) -> Score {
+    match id {
+        // a calm Am - F - C - G
+        MusicId::Title => Score {
+            tempo: 80.0,
+            chords: &[[57, 60, 64], [53, 57, 60], [48, 52, 55], [55, 59, 62]],
+            arpeggio_division: 2,
+            gains: (0.06, 0.15, 0.08),
+        },
+        // a driving Dm - Bb - C - A
+        MusicId::Game => Score {
+            tempo: 120.0,
+            chords: &[[50, 53, 57], [46, 50, 53], [48, 52, 55], [45, 49, 52]],
+            arpeggio_division: 4,
+            gains: (0.04, 0.2, 0.07),
+        },
+        // a slow C - G - Am - F
+        MusicId::End => Score {
+            tempo: 70.0,
+            chords: &[[48, 52, 55], [55, 59, 62], [57, 60, 64], [53, 57, 60]],
+            arpeggio_division: 1,
+            gains: (0.07, 0.12, 0.09),
+        },
+    }
+}
+
+/// A function to get the frequency (Hz) of a MIDI note number.
+fn get_frequency(note: u8) -> f32 {
+    440.0 * 2.0f32.powf((note as f32 - 69.0) / 12.0)
+}
+
+/// A function to get a triangle wave in [-1, 1] at a phase (cycles).
+fn triangle(phase: f32) -> f32 {
+    4.0 * (phase - (phase + 0.5).floor()).abs() - 1.0
+}
+
+/// A mono loop of music.
+///
+/// The music is synthesized procedurally like the sounds, so no audio file is needed.
+pub struct Music {
+    /// The samples of a loop. The last sample continues to the first one.
+    pub samples: Vec<f32>,
+}
+
+impl Music {
+    /// A static method to synthesize music.
+    pub fn synthesize(id: MusicId, sample_rate: u32) -> Self {
+        let score = get_score(id);
+        let beat_duration = 60.0 / score.tempo;
+        let chord_duration = beat_duration * BEATS_PER_CHORD as f32;
+        let note_duration = beat_duration / score.arpeggio_division as f32;
+        let count = (chord_duration * score.chords.len() as f32 * sample_rate as f32) as usize;
+        let samples = (0..count)
+            .map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                let chord_index = ((t / chord_duration) as usize).min(score.chords.len() - 1);
+                let chord = score.chords[chord_index];
+                let chord_time = t - chord_index as f32 * chord_duration;
+                let beat_time = chord_time % beat_duration;
+                let note_index = (chord_time / note_duration) as usize;
+                let note_time = chord_time % note_duration;
+
+                // a sustained chord
+                let pad_envelope = (chord_time / PAD_FADE)
+                    .min((chord_duration - chord_time) / PAD_FADE)
+                    .clamp(0.0, 1.0);
+                let pad = chord
+                    .iter()
+                    .map(|n| (2.0 * PI * get_frequency(*n) * t).sin())
+                    .sum::<f32>()
+                    * pad_envelope;
+
+                // the root an octave lower plucked every beat
+                let bass = triangle(get_frequency(chord[0] - 12) * t)
+                    * (-beat_time * 6.0).exp()
+                    * (beat_time / 0.005).min(1.0);
+
+                // the chord tones an octave higher in turn
+                let arpeggio = (2.0 * PI * get_frequency(chord[note_index % 3] + 12) * t).sin()
+                    * (-note_time * 10.0).exp()
+                    * (note_time / 0.005).min(1.0);
+
+                pad * score.gains.0 + bass * score.gains.1 + arpeggio * score.gains.2
+            })
+            .collect();
+        Self { samples }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::util::random::Random;
+use std::f32::consts::PI;
+
+/// A enum for sound id.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Debug)]
+pub enum SoundId {
+    Shot,
+    Hit,
+    Footstep,
+    /// A widget is activated.
+    Click,
+    /// The focus moves to another widget.
+    Focus,
+}
+
+/// All the sounds synthesized when the audio is created.
+pub const SOUND_IDS: [SoundId; 5] = [
+    SoundId::Shot,
+    SoundId::Hit,
+    SoundId::Footstep,
+    SoundId::Click,
+    SoundId::Focus,
+];
+
+/// A function to get the samples of a decaying sine wave.
+///
+/// * `frequency` - the frequency (Hz), which can change over time
+/// * `decay` - the rate of the exponential decay per second
+fn sine(sample_rate: u32, duration: f32, frequency: impl Fn(f32) -> f32, decay: f32) -> Vec<f32> {
+    let count = (duration * sample_rate as f32) as usize;
+    let mut phase = 0.0;
+    (0..count)
+        .map(|i| {
+            let t = i as f32 / sample_rate as f32;
+            phase += 2.0 * PI * frequency(t) / sample_rate as f32;
+            phase.sin() * (-t * decay).exp()
+        })
+        .collect()
+}
+
+/// A function to get the samples of decaying white noise.
+///
+/// * `smoothness` - the coefficient of the one-pole low-pass filter in [0, 1); higher is darker
+fn noise(
+    sample_rate: u32,
+    duration: f32,
+    decay: f32,
+    smoothness: f32,
+    random: &mut Random,
+) -> Vec<f32> {
+    let count = (duration * sample_rate as f32) as usize;
+    let mut previous = 0.0;
+    (0..count)
+        .map(|i| {
+            let t = i as f32 / sample_rate as f32;
+            previous = previous * smoothness + random.range(-1.0, 1.0) * (1.0 - smoothness);
+            previous * (-t * decay).exp()
+        })
+        .collect()
+}
+
+/// A function to mix some samples with gains.
+fn mix(layers: &[(Vec<f32>, f32)]) -> Vec<f32> {
+    let length = layers.iter().map(|(n, _)| n.len()).max().unwrap_or(0);
+    let mut result = vec![0.0; length];
+    for (samples, gain) in layers {
+        for (i, n) in samples.iter().enumerate() {
+            result[i] += n * gain;
+        }
+    }
+    result
+}
+
+/// A mono sound.
+///
+/// The sounds are synthesized procedurally, so no audio file is needed.
+pub struct Sound {
+    pub samples: Vec<f32>,
+}
+
+impl Sound {
+    /// A static method to synthesize a sound.
+    ///
+    /// NOTE: The noise is generated with a fixed seed, so the sounds are the same in every run.
+    pub fn synthesize(id: SoundId, sample_rate: u32) -> Self {
+        let mut random = Random::new(id as u64);
+        let samples = match id {
+            // a noise burst with a low thump falling in pitch
+            SoundId::Shot => mix(&[
+                (noise(sample_rate, 0.25, 30.0, 0.3, &mut random), 0.6),
+                (sine(sample_rate, 0.25, |t| 120.0 - t * 280.0, 18.0), 0.8),
+            ]),
+            // a bright two-tone ding
+            SoundId::Hit => mix(&[
+                (sine(sample_rate, 0.18, |_| 1320.0, 25.0), 0.35),
+                (sine(sample_rate, 0.18, |_| 1980.0, 30.0), 0.2),
+            ]),
+            // a short dull noise
+            SoundId::Footstep => mix(&[(noise(sample_rate, 0.09, 50.0, 0.9, &mut random), 0.8)]),
+            SoundId::Click => mix(&[(sine(sample_rate, 0.03, |_| 2000.0, 150.0), 0.3)]),
+            SoundId::Focus => mix(&[(sine(sample_rate, 0.02, |_| 1200.0, 200.0), 0.15)]),
+        };
+        Self { samples }
+    }
+}
@@ -0,0 +1,32 @@
+/// A function to encode samples into a 16-bit PCM WAV file.
+///
+/// * `samples` - the interleaved samples in [-1, 1]
+pub fn encode(samples: &[f32], sample_rate: u32, channels: u16) -> Vec<u8> {
+    let data_size = samples.len() as u32 * 2;
+    let block_align = channels * 2;
+    let mut result = Vec::with_capacity(44 + data_size as usize);
+
+    // write the RIFF header
+    result.extend_from_slice(b"RIFF");
+    result.extend_from_slice(&(36 + data_size).to_le_bytes());
+    result.extend_from_slice(b"WAVE");
+
+    // write the format chunk
+    result.extend_from_slice(b"fmt ");
+    result.extend_from_slice(&16u32.to_le_bytes());
+    result.extend_from_slice(&1u16.to_le_bytes());
+    result.extend_from_slice(&channels.to_le_bytes());
+    result.extend_from_slice(&sample_rate.to_le_bytes());
+    result.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    result.extend_from_slice(&block_align.to_le_bytes());
+    result.extend_from_slice(&16u16.to_le_bytes());
+
+    // write the data chunk
+    result.extend_from_slice(b"data");
+    result.extend_from_slice(&data_size.to_le_bytes());
+    for n in samples {
+        let n = (n.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        result.extend_from_slice(&n.to_le_bytes());
+    }
+    result
+}
@@ -6,7 +6,7 @@ use winit::{
 };
 
 /// An enum for enumerating all the key and mouse pressing inputs used in this game.
-#[derive(PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PressingInput {
     KeyW,
     KeyA,
@@ -14,27 +14,42 @@ pub enum PressingInput {
     KeyD,
     KeyE,
     MouseLeft,
+    Escape,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
 }
 
+/// All the pressing inputs in a fixed order.
+pub const PRESSING_INPUTS: [PressingInput; 11] = [
+    PressingInput::KeyW,
+    PressingInput::KeyA,
+    PressingInput::KeyS,
+    PressingInput::KeyD,
+    PressingInput::KeyE,
+    PressingInput::MouseLeft,
+    PressingInput::Escape,
+    PressingInput::ArrowUp,
+    PressingInput::ArrowDown,
+    PressingInput::ArrowLeft,
+    PressingInput::ArrowRight,
+];
+
 /// A struct for save the key or button pressing states.
 pub struct PressingInputStates {
     pub states: HashMap<PressingInput, u32>,
 }
 impl Default for PressingInputStates {
     fn default() -> Self {
-        let mut states = HashMap::new();
-        states.insert(PressingInput::KeyW, 0);
-        states.insert(PressingInput::KeyA, 0);
-        states.insert(PressingInput::KeyS, 0);
-        states.insert(PressingInput::KeyD, 0);
-        states.insert(PressingInput::KeyE, 0);
-        states.insert(PressingInput::MouseLeft, 0);
-        Self { states }
+        Self {
+            states: PRESSING_INPUTS.iter().map(|n| (*n, 0)).collect(),
+        }
     }
 }
 impl PressingInputStates {
     pub fn get(&self, pressing_input: &PressingInput) -> u32 {
-        self.states.get(pressing_input).unwrap_or(&0).clone()
+        *self.states.get(pressing_input).unwrap_or(&0)
     }
 }
 
@@ -76,6 +91,11 @@ impl InputManager {
             PhysicalKey::Code(KeyCode::KeyS) => PressingInput::KeyS,
             PhysicalKey::Code(KeyCode::KeyD) => PressingInput::KeyD,
             PhysicalKey::Code(KeyCode::KeyE) => PressingInput::KeyE,
+            PhysicalKey::Code(KeyCode::Escape) => PressingInput::Escape,
+            PhysicalKey::Code(KeyCode::ArrowUp) => PressingInput::ArrowUp,
+            PhysicalKey::Code(KeyCode::ArrowDown) => PressingInput::ArrowDown,
+            PhysicalKey::Code(KeyCode::ArrowLeft) => PressingInput::ArrowLeft,
+            PhysicalKey::Code(KeyCode::ArrowRight) => PressingInput::ArrowRight,
             _ => return,
         };
         let value = if !event.state.is_pressed() {
@@ -126,7 +146,7 @@ impl InputManager {
     /// It's should be called the end of every frame.
     pub fn go_next(&mut self) {
         self.states.moving = MovingInputState::default();
-        for (_, value) in &mut self.states.pressing.states {
+        for value in self.states.pressing.states.values_mut() {
             if *value > 0 {
                 *value += 1;
             }
@@ -1,21 +1,39 @@
+pub mod error;
 pub mod model;
+pub mod profiler;
 pub mod shader;
 mod texture;
 
-use crate::util::{camera::CameraController, instance::InstanceController};
+use crate::{
+    settings::{AdapterSelection, Settings},
+    system::asset::{AssetHandle, AssetManager, ShaderHandle, TextureHandle},
+    util::{camera::CameraController, instance::InstanceController},
+};
+use error::RendererError;
 use futures::executor;
-use model::{Model, ModelId};
+use model::{mesh::Shape, Model, ModelId, ModelSource};
+use profiler::{GpuPass, GpuProfiler};
 use shader::{
-    skybox::SkyboxPipeline,
-    ui::{DrawUiDescriptor, UiPipeline},
+    crosshair::{CrosshairPipeline, DrawCrosshairDescriptor},
+    mipmap::MipmapPipeline,
+    particle::{DrawParticlesDescriptor, Particle, ParticlePipeline},
+    post::{PostProcessEffect, PostProcessPipeline},
+    skybox::{SkyboxPipeline, SkyboxSource},
+    ui::{DrawUiDescriptor, UiPipeline, UiTextureId},
     world::{DrawWorldDescriptor, WorldPipeline},
 };
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use texture::hdr;
 use wgpu::{
-    Backends, CommandEncoderDescriptor, Device, DeviceDescriptor, Features, Instance,
-    InstanceDescriptor, Limits, MemoryHints, PowerPreference, PresentMode, Queue,
-    RequestAdapterOptions, Surface, SurfaceCapabilities, SurfaceConfiguration, TextureFormat,
-    TextureUsages, TextureViewDescriptor,
+    Adapter, AdapterInfo, Backends, CommandEncoderDescriptor, Device, DeviceDescriptor,
+    DeviceLostReason, Features, Instance, InstanceDescriptor, Limits, MemoryHints, PresentMode,
+    Queue, RequestAdapterOptions, Surface, SurfaceCapabilities, SurfaceConfiguration, SurfaceError,
+    SurfaceTexture, TextureFormat, TextureUsages, TextureViewDescriptor,
 };
 use winit::window::Window;
 
@@ -24,6 +42,143 @@ const BACKEND: Backends = Backends::DX12;
 #[cfg(not(target_os = "windows"))]
 const BACKEND: Backends = Backends::all();
 
+/// A constant for the exposure of the tonemapping when the brightness is 1.
+const TONEMAP_EXPOSURE: f32 = 1.6;
+
+/// A function to create the post-process effects applied to the scene in order.
+///
+/// NOTE: The conversion to sRGB is done by the surface, so no gamma pass is needed.
+pub fn create_post_effects(settings: &Settings) -> Vec<PostProcessEffect> {
+    let mut effects = Vec::new();
+    if settings.has_bloom {
+        effects.push(PostProcessEffect::Bloom {
+            threshold: 0.9,
+            intensity: 0.6,
+            radius: 2.0,
+        });
+    }
+    effects.push(PostProcessEffect::Tonemap {
+        exposure: TONEMAP_EXPOSURE * settings.brightness,
+    });
+    effects.push(PostProcessEffect::Fxaa);
+    effects.push(PostProcessEffect::Vignette {
+        intensity: 0.3,
+        inner_radius: 0.4,
+        outer_radius: 0.8,
+    });
+    effects
+}
+
+/// A function to print all the adapters on the backends with their features and limits.
+///
+/// The indices printed can be used for the `adapter` setting.
+pub fn print_adapters(settings: &Settings) {
+    let backends = settings.backend.unwrap_or(BACKEND);
+    let instance = Instance::new(InstanceDescriptor {
+        backends,
+        ..Default::default()
+    });
+    let adapters = instance.enumerate_adapters(backends);
+    if adapters.is_empty() {
+        println!("no adapter is found.");
+        return;
+    }
+    for (i, n) in adapters.iter().enumerate() {
+        let info = n.get_info();
+        println!("[{}] {}", i, info.name);
+        println!("    backend: {}", info.backend.to_str());
+        println!("    device type: {:?}", info.device_type);
+        println!("    driver: {} {}", info.driver, info.driver_info);
+        println!("    features: {:?}", n.features());
+        println!("    limits: {:#?}", n.limits());
+    }
+}
+
+/// A function to select an adapter.
+///
+/// If `settings.adapter` is specified, the adapter is selected from the adapters enumerated on `backends`.
+/// Otherwise, or if no adapter matches, it's requested with `settings.power_preference`.
+fn select_adapter(
+    instance: &Instance,
+    backends: Backends,
+    surface: &Surface,
+    settings: &Settings,
+) -> Option<Adapter> {
+    if let Some(selection) = &settings.adapter {
+        let adapter = match selection {
+            AdapterSelection::Index(i) => instance.enumerate_adapters(backends).into_iter().nth(*i),
+            AdapterSelection::Name(name) => {
+                instance.enumerate_adapters(backends).into_iter().find(|n| {
+                    n.get_info()
+                        .name
+                        .to_lowercase()
+                        .contains(&name.to_lowercase())
+                })
+            }
+        };
+        match adapter {
+            Some(n) if n.is_surface_supported(surface) => return Some(n),
+            Some(n) => {
+                warn!(
+                    "select_adapter",
+                    "the adapter doesn't support the surface: {}",
+                    n.get_info().name
+                );
+            }
+            None => {
+                warn!("select_adapter", "no adapter matches {:?}.", selection);
+            }
+        }
+    }
+    let request = instance.request_adapter(&RequestAdapterOptions {
+        power_preference: settings.power_preference,
+        compatible_surface: Some(surface),
+        force_fallback_adapter: false,
+    });
+    executor::block_on(request)
+}
+
+/// A function to select the present mode.
+///
+/// If `requested` isn't supported by the surface, it falls back to `Fifo`, which is always supported.
+fn select_present_mode(
+    surface_capabilities: &SurfaceCapabilities,
+    requested: PresentMode,
+) -> PresentMode {
+    if surface_capabilities.present_modes.contains(&requested) {
+        requested
+    } else {
+        warn!(
+            "select_present_mode",
+            "{:?} isn't supported. Fifo is used instead.", requested
+        );
+        PresentMode::Fifo
+    }
+}
+
+/// A function to create a surface configuration.
+///
+/// NOTE: It's used in both `Renderer::new()` and `Renderer::resize()`
+///       to keep the surface configuration consistent.
+fn create_surface_configuration(
+    surface_capabilities: &SurfaceCapabilities,
+    surface_format: TextureFormat,
+    present_mode: PresentMode,
+    width: u32,
+    height: u32,
+) -> SurfaceConfiguration {
+    SurfaceConfiguration {
+        usage: TextureUsages::RENDER_ATTACHMENT,
+        format: surface_format,
+        width,
+        height,
+        present_mode,
+        view_formats: Vec::new(),
+        alpha_mode: surface_capabilities.alpha_modes[0],
+        desired_maximum_frame_latency: 2,
+    }
+}
+
 /// A enum for enumerating requests for a renderer.
 pub enum RenderRequest {
     UpdateCamera(CameraController),
@@ -33,11 +188,31 @@ pub enum RenderRequest {
     /// If no update is needed, store `None`.
     UpdateWorldInstances(Vec<Option<InstanceController>>),
     DrawWorld(DrawWorldDescriptor),
+    /// Remove all the particles, e.g. the ones left by the previous game.
+    ClearParticles,
+    /// Spawn particles at the current time of the particle pipeline.
+    SpawnParticles(Vec<Particle>),
+    /// Advance the time and draw the particles on the world.
+    ///
+    /// WARN: It must follow `DrawWorld` because the particles are tested against its depth.
+    DrawParticles(DrawParticlesDescriptor),
     /// List the instance information.
     /// The indices in this array correspond to the indices in the instance buffer.
     /// If no update is needed, store `None`.
     UpdateUiInstances(Vec<Option<InstanceController>>),
     DrawUi(DrawUiDescriptor),
+    /// Draw a procedural crosshair on the UI.
+    ///
+    /// WARN: Only one crosshair can be drawn in a frame.
+    DrawCrosshair(DrawCrosshairDescriptor),
+}
+
+/// A struct for the CPU times spent on rendering a frame.
+///
+/// NOTE: The GPU times are read asynchronously by `take_gpu_frame_times()`.
+pub struct RenderTimings {
+    /// The time to encode and submit the commands.
+    pub encode: Duration,
 }
 
 /// A renderer on WebGPU.
@@ -48,51 +223,53 @@ pub struct Renderer<'a> {
     surface: Surface<'a>,
     device: Device,
     queue: Queue,
+    surface_configuration: SurfaceConfiguration,
     surface_capabilities: SurfaceCapabilities,
-    surface_format: TextureFormat,
+    /// The message of the device lost event set by the device lost callback.
+    device_lost: Arc<Mutex<Option<String>>>,
+    adapter_info: AdapterInfo,
+    /// The GPU profiler, only if the timestamp query is supported.
+    profiler: Option<GpuProfiler>,
+    mipmap_pipeline: MipmapPipeline,
     skybox_pipeline: SkyboxPipeline,
     world_pipeline: WorldPipeline,
+    particle_pipeline: ParticlePipeline,
     ui_pipeline: UiPipeline,
+    crosshair_pipeline: CrosshairPipeline,
+    post_process_pipeline: PostProcessPipeline,
     models: HashMap<ModelId, Model>,
+    /// The models failed to load, which aren't loaded again.
+    failed_models: HashSet<ModelId>,
+    /// The skybox failed to load last, which isn't loaded again.
+    failed_skybox: Option<SkyboxSource>,
 }
 
 impl<'a> Renderer<'a> {
     /// A constructor.
     ///
     /// The arc of window is cloned in this.
-    pub fn new(window: Arc<Window>) -> Self {
+    ///
+    /// WARN: It fails if no adapter or device is available or a resource can't be loaded.
+    pub fn new(
+        window: Arc<Window>,
+        settings: &Settings,
+        assets: &AssetManager,
+    ) -> Result<Self, RendererError> {
         // create an instance
+        let backends = settings.backend.unwrap_or(BACKEND);
         let instance = Instance::new(InstanceDescriptor {
-            backends: BACKEND,
+            backends,
             ..Default::default()
         });
 
         // create a surface
-        let surface = match instance.create_surface(Arc::clone(&window)) {
-            Ok(n) => n,
-            Err(e) => {
-                error!(
-                    "Renderer.new",
-                    "failed to create a surface: {}",
-                    e.to_string()
-                );
-                std::process::exit(1);
-            }
-        };
+        let surface = instance
+            .create_surface(Arc::clone(&window))
+            .map_err(RendererError::CreateSurface)?;
 
         // get an adapter
-        let request = instance.request_adapter(&RequestAdapterOptions {
-            power_preference: PowerPreference::default(),
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        });
-        let adapter = match executor::block_on(request) {
-            Some(n) => n,
-            None => {
-                error!("Renderer.new", "failed to get an adapter.");
-                std::process::exit(1);
-            }
-        };
+        let adapter = select_adapter(&instance, backends, &surface, settings)
+            .ok_or(RendererError::NoAdapter)?;
         info!(
             "Renderer.new",
             "the adapter selected: {}.",
@@ -105,26 +282,35 @@ impl<'a> Renderer<'a> {
         );
 
         // get a device and a queue
+        // NOTE: The timestamp query is enabled only if the adapter supports it.
+        let required_features = adapter.features() & Features::TIMESTAMP_QUERY;
         let request = adapter.request_device(
             &DeviceDescriptor {
                 label: None,
-                required_features: Features::empty(),
+                required_features,
                 required_limits: Limits::default(),
                 memory_hints: MemoryHints::MemoryUsage,
             },
             None,
         );
-        let (device, queue) = match executor::block_on(request) {
-            Ok(n) => n,
-            Err(e) => {
-                error!(
-                    "Renderer.new",
-                    "failed to get a device and a queue: {}",
-                    e.to_string()
-                );
-                std::process::exit(1);
+        let (device, queue) = executor::block_on(request).map_err(RendererError::RequestDevice)?;
+
+        // set error handlers
+        // NOTE: The device lost event is checked at the beginning of `render()`.
+        //       The event caused by dropping this renderer is ignored.
+        let device_lost = Arc::new(Mutex::new(None));
+        let device_lost_cloned = Arc::clone(&device_lost);
+        device.set_device_lost_callback(move |reason, message| {
+            if reason == DeviceLostReason::Dropped || reason == DeviceLostReason::ReplacedCallback {
+                return;
             }
-        };
+            if let Ok(mut n) = device_lost_cloned.lock() {
+                *n = Some(format!("{:?}: {}", reason, message));
+            }
+        });
+        device.on_uncaptured_error(Box::new(|e| {
+            error!("Renderer", "uncaptured error: {}", e.to_string());
+        }));
 
         // configure the surface
         let surface_capabilities = surface.get_capabilities(&adapter);
@@ -132,107 +318,451 @@ impl<'a> Renderer<'a> {
             .formats
             .iter()
             .copied()
-            .filter(|f| f.is_srgb())
-            .next()
+            .find(|f| f.is_srgb())
             .unwrap_or(surface_capabilities.formats[0]);
-        surface.configure(
-            &device,
-            &SurfaceConfiguration {
-                usage: TextureUsages::RENDER_ATTACHMENT,
-                format: surface_format,
-                width: window.inner_size().width,
-                height: window.inner_size().height,
-                present_mode: PresentMode::Immediate,
-                view_formats: Vec::new(),
-                alpha_mode: surface_capabilities.alpha_modes[0],
-                desired_maximum_frame_latency: 2,
-            },
+        let present_mode = select_present_mode(&surface_capabilities, settings.present_mode);
+        let surface_configuration = create_surface_configuration(
+            &surface_capabilities,
+            surface_format,
+            present_mode,
+            window.inner_size().width,
+            window.inner_size().height,
+        );
+        surface.configure(&device, &surface_configuration);
+        info!(
+            "Renderer.new",
+            "the present mode selected: {:?}.", present_mode
         );
 
         // create render pipelines
+        // NOTE: The mipmap pipeline is created first to generate the mip levels of the textures.
+        //       The skybox and the world are rendered to the HDR texture to be post-processed.
+        let mipmap_pipeline = MipmapPipeline::new(&device, assets)?;
         let skybox_pipeline = SkyboxPipeline::new(
             &device,
             &queue,
-            surface_format.into(),
+            assets,
+            hdr::HDR_TEXTURE_FORMAT.into(),
             window.inner_size().width,
             window.inner_size().height,
-        );
+        )?;
         let world_pipeline = WorldPipeline::new(
             &device,
-            surface_format.into(),
+            &queue,
+            assets,
+            hdr::HDR_TEXTURE_FORMAT.into(),
             window.inner_size().width,
             window.inner_size().height,
-        );
+        )?;
+        let particle_pipeline =
+            ParticlePipeline::new(&device, assets, hdr::HDR_TEXTURE_FORMAT.into())?;
         let ui_pipeline = UiPipeline::new(
             &device,
             &queue,
+            assets,
             surface_format.into(),
             window.inner_size().width,
             window.inner_size().height,
-        );
+        )?;
+        let crosshair_pipeline = CrosshairPipeline::new(
+            &device,
+            assets,
+            surface_format.into(),
+            window.inner_size().width,
+            window.inner_size().height,
+        )?;
+        let post_process_pipeline = PostProcessPipeline::new(
+            &device,
+            assets,
+            surface_format.into(),
+            window.inner_size().width,
+            window.inner_size().height,
+            &create_post_effects(settings),
+        )?;
 
-        // create models
+        // create a GPU profiler
+        let profiler = if required_features.contains(Features::TIMESTAMP_QUERY) {
+            Some(GpuProfiler::new(&device, &queue))
+        } else {
+            info!(
+                "Renderer.new",
+                "the timestamp query isn't supported. the GPU profiler is disabled."
+            );
+            None
+        };
+
+        // create the built-in models
         let mut models = HashMap::new();
-        models.insert(ModelId::Cube, Model::cube(&device));
-        models.insert(ModelId::Square, Model::square(&device));
-        models.insert(ModelId::Sphere, Model::sphere(&device));
+        for (id, shape) in [
+            (
+                ModelId::CUBE,
+                Shape::Cuboid {
+                    width: 1.0,
+                    height: 1.0,
+                    depth: 1.0,
+                },
+            ),
+            (
+                ModelId::SQUARE,
+                Shape::Rectangle {
+                    width: 1.0,
+                    height: 1.0,
+                },
+            ),
+            (
+                ModelId::SPHERE,
+                Shape::Sphere {
+                    radius: 1.0,
+                    latitude_count: 20,
+                    longitude_count: 20,
+                },
+            ),
+        ] {
+            models.insert(id, Model::from_mesh(&device, &shape.create_mesh()));
+        }
 
         // finish
         info!("Renderer.new", "renderer created.");
-        Self {
+        Ok(Self {
             surface,
             device,
             queue,
+            surface_configuration,
             surface_capabilities,
-            surface_format,
+            device_lost,
+            adapter_info: adapter.get_info(),
+            profiler,
+            mipmap_pipeline,
             skybox_pipeline,
             world_pipeline,
+            particle_pipeline,
             ui_pipeline,
+            crosshair_pipeline,
+            post_process_pipeline,
             models,
+            failed_models: HashSet::new(),
+            failed_skybox: None,
+        })
+    }
+
+    /// A method to get the information of the adapter in use.
+    pub fn get_adapter_info(&self) -> &AdapterInfo {
+        &self.adapter_info
+    }
+
+    /// A method to get the GPU profiler.
+    ///
+    /// If the timestamp query isn't supported, it returns `None`.
+    pub fn get_gpu_profiler(&self) -> Option<&GpuProfiler> {
+        self.profiler.as_ref()
+    }
+
+    /// A method to take the GPU times of the frames profiled since the last call.
+    ///
+    /// If the timestamp query isn't supported, it returns an empty vector.
+    /// NOTE: It should be called every frame not to accumulate the times.
+    pub fn take_gpu_frame_times(&mut self) -> Vec<Duration> {
+        self.profiler
+            .as_mut()
+            .map(|n| n.take_frame_times())
+            .unwrap_or_default()
+    }
+
+    /// A method to reload a shader and recreate the render pipelines with it.
+    ///
+    /// If the shader can't be loaded or is invalid, the error is reported and the old pipelines are kept.
+    pub fn reload_shader(&mut self, assets: &AssetManager, handle: ShaderHandle) {
+        let device = &self.device;
+        let result = assets
+            .load_shader(handle)
+            .map_err(|e| e.to_string())
+            .and_then(|n| match handle {
+                ShaderHandle::World => self.world_pipeline.reload_shader(device, &n),
+                ShaderHandle::Skybox => self.skybox_pipeline.reload_shader(device, &n),
+                ShaderHandle::Ui => self.ui_pipeline.reload_shader(device, &n),
+                ShaderHandle::Crosshair => self.crosshair_pipeline.reload_shader(device, &n),
+                ShaderHandle::Particle => self.particle_pipeline.reload_shader(device, &n),
+                ShaderHandle::Post => self.post_process_pipeline.reload_shader(device, &n),
+                ShaderHandle::Mipmap => self.mipmap_pipeline.reload_shader(device, &n),
+            });
+        match result {
+            Ok(()) => info!("Renderer.reload_shader", "reloaded: {}.", handle.get_path()),
+            Err(e) => error!(
+                "Renderer.reload_shader",
+                "failed to reload {}, the old one is kept: {}",
+                handle.get_path(),
+                e
+            ),
+        }
+    }
+
+    /// A method to reload a texture.
+    ///
+    /// If the texture can't be loaded or decoded, the error is reported and the old texture is kept.
+    pub fn reload_texture(&mut self, assets: &AssetManager, handle: TextureHandle) {
+        let (device, queue) = (&self.device, &self.queue);
+        let result = match handle {
+            // NOTE: The skybox is reloaded from the source of the level, which may not use the file.
+            TextureHandle::Skybox => self
+                .skybox_pipeline
+                .reload_texture(device, queue, assets)
+                .map_err(Box::<dyn Error>::from),
+            TextureHandle::Ui | TextureHandle::Font => assets
+                .load_texture(handle)
+                .map_err(Box::<dyn Error>::from)
+                .and_then(|n| {
+                    let id = if handle == TextureHandle::Ui {
+                        UiTextureId::Ui
+                    } else {
+                        UiTextureId::Font
+                    };
+                    self.ui_pipeline.reload_texture(device, queue, id, &n)
+                }),
+        };
+        match result {
+            Ok(()) => info!(
+                "Renderer.reload_texture",
+                "reloaded: {}.",
+                handle.get_path()
+            ),
+            Err(e) => error!(
+                "Renderer.reload_texture",
+                "failed to reload {}, the old one is kept: {}",
+                handle.get_path(),
+                e
+            ),
         }
     }
 
+    /// A method to load or create the models that aren't loaded yet and unload the others.
+    ///
+    /// * `models` - the pairs of the id and the source of the models needed now
+    ///
+    /// NOTE: It's cheap if nothing changes, so you can call it every frame.
+    ///       The built-in models are always kept.
+    ///       If a model can't be loaded, the error is reported once and the model isn't drawn.
+    pub fn load_models(&mut self, assets: &AssetManager, models: &[(ModelId, ModelSource)]) {
+        self.models
+            .retain(|id, _| id.is_builtin() || models.iter().any(|(n, _)| n == id));
+        for (id, source) in models {
+            if self.models.contains_key(id) || self.failed_models.contains(id) {
+                continue;
+            }
+            match self.load_model(assets, source) {
+                Ok(n) => {
+                    info!("Renderer.load_models", "loaded: {}.", source);
+                    self.models.insert(*id, n);
+                }
+                Err(e) => {
+                    error!("Renderer.load_models", "failed to load a model: {}", e);
+                    self.failed_models.insert(*id);
+                }
+            }
+        }
+    }
+
+    /// A method to load the models from the asset files again because the files are modified.
+    ///
+    /// If a model can't be loaded, the error is reported and the old one is kept.
+    pub fn reload_models(&mut self, assets: &AssetManager, models: &[(ModelId, ModelSource)]) {
+        for (id, source) in models {
+            let ModelSource::Asset(_) = source else {
+                continue;
+            };
+            match self.load_model(assets, source) {
+                Ok(n) => {
+                    info!("Renderer.reload_models", "reloaded: {}.", source);
+                    self.models.insert(*id, n);
+                    self.failed_models.remove(id);
+                }
+                Err(e) => error!(
+                    "Renderer.reload_models",
+                    "failed to reload a model, the old one is kept: {}", e
+                ),
+            }
+        }
+    }
+
+    /// A method to load or create a model from a source.
+    fn load_model(
+        &self,
+        assets: &AssetManager,
+        source: &ModelSource,
+    ) -> Result<Model, RendererError> {
+        match source {
+            ModelSource::Asset(path) if path.to_ascii_lowercase().ends_with(".obj") => {
+                Model::load_obj(&self.device, assets, path)
+            }
+            ModelSource::Asset(path) => Model::load_gltf(
+                &self.device,
+                &self.queue,
+                assets,
+                &self.mipmap_pipeline,
+                &self.world_pipeline,
+                path,
+            ),
+            ModelSource::Shape(shape) => Ok(Model::from_mesh(&self.device, &shape.create_mesh())),
+        }
+    }
+
+    /// A method to load the skybox if it differs from the one drawn now.
+    ///
+    /// NOTE: It's cheap if nothing changes, so you can call it every frame.
+    ///       If the skybox can't be loaded, the error is reported once and the old one is kept.
+    pub fn load_skybox(&mut self, assets: &AssetManager, source: &SkyboxSource) {
+        if self.skybox_pipeline.get_source() == source
+            || self.failed_skybox.as_ref() == Some(source)
+        {
+            return;
+        }
+        match self
+            .skybox_pipeline
+            .load_texture(&self.device, &self.queue, assets, source)
+        {
+            Ok(()) => {
+                info!("Renderer.load_skybox", "loaded: {}.", source);
+                self.failed_skybox = None;
+            }
+            Err(e) => {
+                error!("Renderer.load_skybox", "failed to load a skybox: {}", e);
+                self.failed_skybox = Some(source.clone());
+            }
+        }
+    }
+
+    /// A method to load the skybox again because its files are modified.
+    ///
+    /// If the skybox can't be loaded, the error is reported and the old one is kept.
+    pub fn reload_skybox(&mut self, assets: &AssetManager, source: &SkyboxSource) {
+        self.failed_skybox = None;
+        if self.skybox_pipeline.get_source() == source {
+            self.reload_texture(assets, TextureHandle::Skybox);
+        } else {
+            self.load_skybox(assets, source);
+        }
+    }
+
+    /// A method to replace the post-process effects.
+    ///
+    /// If only the parameters change, e.g. the brightness, the parameter buffers are just rewritten.
+    /// If the effects can't be created, the error is reported and the old ones are kept.
+    pub fn set_post_effects(&mut self, effects: &[PostProcessEffect]) {
+        match self
+            .post_process_pipeline
+            .set_effects(&self.device, &self.queue, effects)
+        {
+            Ok(()) => info!("Renderer.set_post_effects", "post-process effects changed."),
+            Err(e) => error!(
+                "Renderer.set_post_effects",
+                "failed to change the post-process effects, the old ones are kept: {}", e
+            ),
+        }
+    }
+
+    /// A method to change the present mode.
+    ///
+    /// If `present_mode` isn't supported by the surface, `Fifo` is used instead.
+    pub fn set_present_mode(&mut self, present_mode: PresentMode) {
+        self.surface_configuration.present_mode =
+            select_present_mode(&self.surface_capabilities, present_mode);
+        self.surface
+            .configure(&self.device, &self.surface_configuration);
+        info!(
+            "Renderer.set_present_mode",
+            "the present mode selected: {:?}.", self.surface_configuration.present_mode
+        );
+    }
+
     /// A method to resize something that depends on the window size.
+    ///
+    /// WARN: If `width` or `height` is zero, it does nothing.
     pub fn resize(&mut self, width: u32, height: u32) {
-        self.surface.configure(
-            &self.device,
-            &SurfaceConfiguration {
-                usage: TextureUsages::RENDER_ATTACHMENT,
-                format: self.surface_format,
-                width,
-                height,
-                present_mode: self.surface_capabilities.present_modes[0],
-                view_formats: Vec::new(),
-                alpha_mode: self.surface_capabilities.alpha_modes[0],
-                desired_maximum_frame_latency: 2,
-            },
-        );
+        if width == 0 || height == 0 {
+            return;
+        }
+        self.surface_configuration.width = width;
+        self.surface_configuration.height = height;
+        self.surface
+            .configure(&self.device, &self.surface_configuration);
         self.skybox_pipeline.resize(&self.device, width, height);
         self.world_pipeline.resize(&self.device, width, height);
         self.ui_pipeline.resize(&self.queue, width, height);
+        self.crosshair_pipeline.resize(width, height);
+        self.post_process_pipeline
+            .resize(&self.device, width, height);
     }
 
-    /// A method to render entities.
+    /// A method to acquire the next surface texture.
     ///
-    /// It locks the thread until a framebuffer is presented.
-    pub fn render(&self, render_requests: Vec<RenderRequest>) {
-        let surface_texture = match self.surface.get_current_texture() {
-            Ok(n) => n,
-            Err(e) => {
+    /// It handles recoverable errors as follows:
+    /// - `Lost`, `Outdated`: reconfigures the surface and skips this frame
+    /// - `Timeout`: retries once and skips this frame if it fails again
+    ///
+    /// If this frame should be skipped, it returns `None`.
+    fn acquire_surface_texture(&self) -> Result<Option<SurfaceTexture>, RendererError> {
+        let error = match self.surface.get_current_texture() {
+            Ok(n) => return Ok(Some(n)),
+            Err(e) => e,
+        };
+        match error {
+            SurfaceError::Lost | SurfaceError::Outdated => {
                 warn!(
-                    "Renderer.render",
-                    "failed to get surface current texture: {}",
-                    e.to_string()
+                    "Renderer.acquire_surface_texture",
+                    "the surface is reconfigured: {}",
+                    error.to_string()
                 );
-                return;
+                self.surface
+                    .configure(&self.device, &self.surface_configuration);
+                Ok(None)
             }
+            SurfaceError::Timeout => match self.surface.get_current_texture() {
+                Ok(n) => Ok(Some(n)),
+                Err(e) => {
+                    warn!(
+                        "Renderer.acquire_surface_texture",
+                        "the frame is skipped: {}",
+                        e.to_string()
+                    );
+                    Ok(None)
+                }
+            },
+            SurfaceError::OutOfMemory => Err(RendererError::OutOfMemory),
+        }
+    }
+
+    /// A method to render entities.
+    ///
+    /// It locks the thread until a framebuffer is presented.
+    ///
+    /// The skybox and the world are rendered to the HDR texture.
+    /// The post-process is applied just before the first UI or crosshair draw or at the end of the frame,
+    /// only if something is rendered to the HDR texture.
+    ///
+    /// If this frame is skipped, it returns `None`, otherwise the times spent on rendering.
+    ///
+    /// WARN: If it returns `RendererError::DeviceLost`, this renderer must be recreated.
+    pub fn render(
+        &mut self,
+        render_requests: Vec<RenderRequest>,
+    ) -> Result<Option<RenderTimings>, RendererError> {
+        if let Some(n) = self.device_lost.lock().ok().and_then(|n| n.clone()) {
+            return Err(RendererError::DeviceLost(n));
+        }
+        let Some(surface_texture) = self.acquire_surface_texture()? else {
+            return Ok(None);
         };
+        let encode_start_time = Instant::now();
+        if let Some(n) = &mut self.profiler {
+            n.begin_frame(&self.device);
+        }
         let render_target_view = surface_texture
             .texture
             .create_view(&TextureViewDescriptor::default());
         let mut command_encoder = self
             .device
             .create_command_encoder(&CommandEncoderDescriptor { label: None });
+        let hdr_target_view = self.post_process_pipeline.get_render_target_view();
+        let mut is_hdr_drawn = false;
 
         for request in render_requests {
             match request {
@@ -241,13 +771,19 @@ impl<'a> Renderer<'a> {
                         .update_camera(&self.queue, &camera_controller);
                     self.skybox_pipeline
                         .update_camera(&self.queue, &camera_controller);
+                    self.particle_pipeline
+                        .update_camera(&self.queue, &camera_controller);
                 }
                 RenderRequest::DrawSkybox => {
                     self.skybox_pipeline.draw(
                         &mut command_encoder,
-                        &render_target_view,
-                        &self.models[&ModelId::Sphere],
+                        hdr_target_view,
+                        &self.models[&ModelId::SPHERE],
+                        self.profiler
+                            .as_mut()
+                            .and_then(|n| n.get_timestamp_writes(GpuPass::Skybox)),
                     );
+                    is_hdr_drawn = true;
                 }
                 RenderRequest::UpdateWorldInstances(instance_controllers) => {
                     self.world_pipeline
@@ -256,27 +792,95 @@ impl<'a> Renderer<'a> {
                 RenderRequest::DrawWorld(descriptor) => {
                     self.world_pipeline.draw(
                         &mut command_encoder,
-                        &render_target_view,
+                        hdr_target_view,
                         &self.models,
                         descriptor,
+                        self.profiler
+                            .as_mut()
+                            .and_then(|n| n.get_timestamp_writes(GpuPass::World)),
+                    );
+                    is_hdr_drawn = true;
+                }
+                RenderRequest::ClearParticles => self.particle_pipeline.clear(),
+                RenderRequest::SpawnParticles(particles) => {
+                    self.particle_pipeline.spawn(&self.queue, particles);
+                }
+                RenderRequest::DrawParticles(descriptor) => {
+                    self.particle_pipeline.draw(
+                        &self.queue,
+                        &mut command_encoder,
+                        hdr_target_view,
+                        self.world_pipeline.get_depth_texture_view(),
+                        &self.models[&ModelId::SQUARE],
+                        descriptor,
+                        self.profiler
+                            .as_mut()
+                            .and_then(|n| n.get_timestamp_writes(GpuPass::Particle)),
                     );
+                    is_hdr_drawn = true;
                 }
                 RenderRequest::UpdateUiInstances(instance_controllers) => {
                     self.ui_pipeline
                         .update_instances(&self.queue, instance_controllers);
                 }
                 RenderRequest::DrawUi(descriptor) => {
+                    if is_hdr_drawn {
+                        self.post_process_pipeline.draw(
+                            &mut command_encoder,
+                            &render_target_view,
+                            self.profiler.as_mut(),
+                        );
+                        is_hdr_drawn = false;
+                    }
                     self.ui_pipeline.draw(
                         &mut command_encoder,
                         &render_target_view,
-                        &self.models[&ModelId::Square],
+                        &self.models[&ModelId::SQUARE],
                         descriptor,
+                        self.profiler
+                            .as_mut()
+                            .and_then(|n| n.get_timestamp_writes(GpuPass::Ui)),
+                    );
+                }
+                RenderRequest::DrawCrosshair(descriptor) => {
+                    if is_hdr_drawn {
+                        self.post_process_pipeline.draw(
+                            &mut command_encoder,
+                            &render_target_view,
+                            self.profiler.as_mut(),
+                        );
+                        is_hdr_drawn = false;
+                    }
+                    self.crosshair_pipeline.draw(
+                        &self.queue,
+                        &mut command_encoder,
+                        &render_target_view,
+                        &self.models[&ModelId::SQUARE],
+                        descriptor,
+                        self.profiler
+                            .as_mut()
+                            .and_then(|n| n.get_timestamp_writes(GpuPass::Ui)),
                     );
                 }
             }
         }
+        if is_hdr_drawn {
+            self.post_process_pipeline.draw(
+                &mut command_encoder,
+                &render_target_view,
+                self.profiler.as_mut(),
+            );
+        }
 
+        if let Some(n) = &mut self.profiler {
+            n.resolve(&mut command_encoder);
+        }
         self.queue.submit(Some(command_encoder.finish()));
+        if let Some(n) = &mut self.profiler {
+            n.end_frame();
+        }
+        let encode = encode_start_time.elapsed();
         surface_texture.present();
+        Ok(Some(RenderTimings { encode }))
     }
 }
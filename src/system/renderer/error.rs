@@ -0,0 +1,48 @@
+use crate::system::asset::AssetError;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+use wgpu::{CreateSurfaceError, RequestDeviceError};
+
+/// An enum for enumerating errors of a renderer.
+#[derive(Debug)]
+pub enum RendererError {
+    /// Failed to create a surface for the window.
+    CreateSurface(CreateSurfaceError),
+    /// No adapter compatible with the surface is found.
+    NoAdapter,
+    /// Failed to get a device and a queue from the adapter.
+    RequestDevice(RequestDeviceError),
+    /// An asset isn't found or can't be read.
+    LoadAsset(AssetError),
+    /// Failed to load an image file into a texture.
+    LoadImage(String, Box<dyn Error>),
+    /// Failed to parse a model file.
+    LoadModel(String, String),
+    /// The system ran out of memory while acquiring a surface texture.
+    OutOfMemory,
+    /// The device was lost, e.g. the driver was reset or the GPU was removed.
+    ///
+    /// NOTE: The renderer can't be used anymore. It should be recreated.
+    DeviceLost(String),
+}
+
+impl Display for RendererError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CreateSurface(e) => write!(f, "failed to create a surface: {}", e),
+            Self::NoAdapter => write!(f, "failed to get an adapter"),
+            Self::RequestDevice(e) => write!(f, "failed to get a device and a queue: {}", e),
+            Self::LoadAsset(e) => write!(f, "failed to load an asset: {}", e),
+            Self::LoadImage(path, e) => {
+                write!(f, "failed to create an image texture: {}: {}", path, e)
+            }
+            Self::LoadModel(path, e) => write!(f, "failed to create a model: {}: {}", path, e),
+            Self::OutOfMemory => write!(f, "out of memory"),
+            Self::DeviceLost(n) => write!(f, "the device was lost: {}", n),
+        }
+    }
+}
+
+impl Error for RendererError {}
@@ -1,20 +1,54 @@
-use crate::util::memory;
-use std::mem;
+mod gltf;
+pub mod mesh;
+mod obj;
+
+use super::{
+    error::RendererError,
+    shader::{mipmap::MipmapPipeline, world::WorldPipeline},
+    texture::image,
+};
+use crate::{system::asset::AssetManager, util::memory};
+use glam::Vec4;
+use mesh::{Mesh, Shape};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    mem,
+    ops::Range,
+    sync::atomic::{AtomicU32, Ordering},
+};
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
-    Buffer, BufferUsages, Device, VertexAttribute, VertexBufferLayout, VertexFormat,
-    VertexStepMode,
+    BindGroup, Buffer, BufferUsages, Device, IndexFormat, Queue, VertexAttribute,
+    VertexBufferLayout, VertexFormat, VertexStepMode,
 };
 
 /// A struct for the data of a single vertex in the common model within this application’s shader.
 ///
 /// NOTE: You can create custom vertex data to pass to `Model::from()`.
+#[derive(Clone, Copy)]
 pub struct Vertex {
     pub _position: [f32; 4],
     pub _normal: [f32; 4],
     pub _tex_coord: [f32; 2],
 }
 
+impl Vertex {
+    /// A method to get the bit patterns of the components to compare vertices exactly.
+    fn to_bits(self) -> [u32; 10] {
+        let mut bits = [0; 10];
+        let components = self
+            ._position
+            .iter()
+            .chain(&self._normal)
+            .chain(&self._tex_coord);
+        for (i, n) in components.enumerate() {
+            bits[i] = n.to_bits();
+        }
+        bits
+    }
+}
+
 /// A constant for descripting a vertex data layout to create a render pipeline.
 pub const VERTEX_BUFFER_LAYOUTS: &[VertexBufferLayout] = &[VertexBufferLayout {
     array_stride: mem::size_of::<Vertex>() as u64,
@@ -38,117 +72,278 @@ pub const VERTEX_BUFFER_LAYOUTS: &[VertexBufferLayout] = &[VertexBufferLayout {
     ],
 }];
 
-const CUBE_OBJ: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/model/cube.obj"));
-const SQUARE_OBJ: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/model/square.obj"));
-const SPHERE_OBJ: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/model/sphere.obj"));
+/// A function to merge the identical vertices and remap the indices to them.
+///
+/// The order and the count of the indices are kept, so the ranges of the indices are still valid.
+///
+/// NOTE: The vertices are compared bitwise, e.g. `0.0` and `-0.0` are different.
+pub fn deduplicate(vertex_data: &[Vertex], index_data: &[u32]) -> (Vec<Vertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices_by_bits = HashMap::new();
+    let remapped_indices = vertex_data
+        .iter()
+        .map(|n| {
+            *indices_by_bits.entry(n.to_bits()).or_insert_with(|| {
+                vertices.push(*n);
+                vertices.len() as u32 - 1
+            })
+        })
+        .collect::<Vec<u32>>();
+    let indices = index_data
+        .iter()
+        .map(|n| remapped_indices[*n as usize])
+        .collect();
+    (vertices, indices)
+}
 
-/// A enum for model id.
-#[derive(Hash, Eq, PartialEq)]
-pub enum ModelId {
-    Cube,
-    Square,
-    Sphere,
+/// A function to get the smallest index format that can refer to all the vertices.
+///
+/// NOTE: The index `u16::MAX` is avoided since it's the primitive restart value of `Uint16`.
+fn get_index_format(vertex_count: usize) -> IndexFormat {
+    if vertex_count < u16::MAX as usize {
+        IndexFormat::Uint16
+    } else {
+        IndexFormat::Uint32
+    }
+}
+
+/// The count of the built-in models.
+const BUILTIN_MODEL_COUNT: u32 = 3;
+
+/// A struct for the id of a model.
+///
+/// The ids of the built-in models are constants, and the others are allocated at runtime.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Debug)]
+pub struct ModelId(u32);
+
+impl ModelId {
+    pub const CUBE: Self = Self(0);
+    pub const SQUARE: Self = Self(1);
+    pub const SPHERE: Self = Self(2);
+
+    /// A static method to allocate a new id unique in this process.
+    pub fn allocate() -> Self {
+        static NEXT_ID: AtomicU32 = AtomicU32::new(BUILTIN_MODEL_COUNT);
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// A method to check if the id is of a built-in model.
+    pub fn is_builtin(self) -> bool {
+        self.0 < BUILTIN_MODEL_COUNT
+    }
+}
+
+/// An enum for the sources of the models created at runtime.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ModelSource {
+    /// The asset path of a glTF, GLB or Wavefront OBJ model, which is distinguished by the extension.
+    Asset(String),
+    /// A procedural mesh.
+    Shape(Shape),
+}
+
+impl ModelSource {
+    /// A static method to parse a source written as an asset path or a shape such as `cylinder(0.5,2,16)`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if text.contains('(') {
+            Ok(Self::Shape(Shape::parse(text)?))
+        } else {
+            Ok(Self::Asset(text.to_string()))
+        }
+    }
+}
+
+impl Display for ModelSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Asset(path) => write!(f, "{}", path),
+            Self::Shape(shape) => write!(f, "{}", shape),
+        }
+    }
+}
+
+/// A struct for a range of indices drawn with the same material.
+pub struct ModelPart {
+    pub index_range: Range<u32>,
+    /// The material bind group of the world pipeline.
+    ///
+    /// If it's `None`, the default material, which is white, is used.
+    pub material: Option<BindGroup>,
 }
 
 /// A struct for a model.
 pub struct Model {
     pub vertex_buffer: Buffer,
     pub index_buffer: Buffer,
+    pub index_format: IndexFormat,
     pub index_count: usize,
+    /// The parts drawn on the world pipeline.
+    pub parts: Vec<ModelPart>,
 }
 
 impl Model {
     /// A static method to create a model from a vertex data array and an index data array.
     ///
     /// NOTE: You can call this to create a custom model.
-    pub fn from(device: &Device, vertex_data: &[Vertex], index_data: &[u16]) -> Self {
+    ///       The indices are stored as `Uint16` if the count of the vertices allows, or `Uint32` otherwise.
+    pub fn from(device: &Device, vertex_data: &[Vertex], index_data: &[u32]) -> Self {
         let vertex_buffer = device.create_buffer_init(&BufferInitDescriptor {
             label: None,
             contents: memory::slice_to_u8slice(vertex_data),
             usage: BufferUsages::VERTEX,
         });
+        let index_format = get_index_format(vertex_data.len());
+        let index_data_u16;
+        let contents = match index_format {
+            IndexFormat::Uint16 => {
+                index_data_u16 = index_data.iter().map(|n| *n as u16).collect::<Vec<u16>>();
+                memory::slice_to_u8slice(&index_data_u16)
+            }
+            IndexFormat::Uint32 => memory::slice_to_u8slice(index_data),
+        };
         let index_buffer = device.create_buffer_init(&BufferInitDescriptor {
             label: None,
-            contents: memory::slice_to_u8slice(index_data),
+            contents,
             usage: BufferUsages::INDEX,
         });
         Self {
             vertex_buffer,
             index_buffer,
+            index_format,
             index_count: index_data.len(),
+            parts: Vec::from([ModelPart {
+                index_range: 0..index_data.len() as u32,
+                material: None,
+            }]),
         }
     }
 
+    /// A static method to create a model from a mesh.
+    ///
+    /// NOTE: You can call this to create a model of a procedural mesh, e.g. `Shape::create_mesh()`.
+    pub fn from_mesh(device: &Device, mesh: &Mesh) -> Self {
+        Self::from(device, &mesh.vertices, &mesh.indices)
+    }
+
     /// A static method to create a model from a Wavefront OBJ format text.
     ///
     /// NOTE: You can call this to create a custom model.
+    ///       See `obj` for the supported statements.
     ///
-    /// WARN: Each parameter specified in a face element must be pre-defined in the text.
-    ///
-    /// WARN: Only the parameters v, vt, and vn are supported.
-    ///       Unsupported parameters will be ignored.
+    /// If it fails, the error message starts with the line number.
     pub fn from_obj(device: &Device, obj: &str) -> Result<Self, String> {
-        let mut v = Vec::new();
-        let mut vt = Vec::new();
-        let mut vn = Vec::new();
+        let (vertex_data, index_data) = obj::parse(obj)?;
+        let (vertex_data, index_data) = deduplicate(&vertex_data, &index_data);
+        Ok(Self::from(device, &vertex_data, &index_data))
+    }
+
+    /// A static method to load a model from a Wavefront OBJ asset.
+    pub fn load_obj(
+        device: &Device,
+        assets: &AssetManager,
+        path: &str,
+    ) -> Result<Self, RendererError> {
+        let obj = assets.read_text(path).map_err(RendererError::LoadAsset)?;
+        Self::from_obj(device, &obj).map_err(|e| RendererError::LoadModel(path.to_string(), e))
+    }
+
+    /// A static method to load a model from a glTF or GLB asset.
+    ///
+    /// The primitives are merged into a model, and each of them is a part with its material.
+    /// The external files are searched relative to `path`.
+    /// The textures are created with the full mip chains.
+    pub fn load_gltf(
+        device: &Device,
+        queue: &Queue,
+        assets: &AssetManager,
+        mipmap_pipeline: &MipmapPipeline,
+        world_pipeline: &WorldPipeline,
+        path: &str,
+    ) -> Result<Self, RendererError> {
+        let to_error = |e: String| RendererError::LoadModel(path.to_string(), e);
+        let data = assets.read(path).map_err(RendererError::LoadAsset)?;
+        let directory = path.rsplit_once('/').map_or("", |(n, _)| n);
+        let scene = gltf::parse(&data, |n| {
+            let path = if directory.is_empty() {
+                n.to_string()
+            } else {
+                format!("{}/{}", directory, n)
+            };
+            assets.read(&path).map_err(|e| e.to_string())
+        })
+        .map_err(to_error)?;
+
+        // create the textures and the samplers
+        let mut texture_views = HashMap::new();
+        for (i, n) in &scene.images {
+            let texture_view =
+                image::create_image_texture_view(device, queue, Some(mipmap_pipeline), n)
+                    .map_err(|e| to_error(format!("image {}: {}", i, e)))?;
+            texture_views.insert(*i, texture_view);
+        }
+        let mut samplers = HashMap::new();
+        for n in &scene.primitives {
+            samplers
+                .entry(n.base_color_sampler)
+                .or_insert_with(|| image::create_sampler(device, &n.base_color_sampler));
+        }
+
+        // merge the primitives
         let mut vertex_data = Vec::new();
         let mut index_data = Vec::new();
-        for n in obj.lines() {
-            let n = n.trim();
-            if n.is_empty() || n.starts_with("#") {
-                continue;
-            }
-            if n.starts_with("f") {
-                let elements = n
-                    .split(' ')
-                    .skip(1)
-                    .map(|n| {
-                        n.split('/')
-                            .map(|n| n.parse::<usize>().unwrap())
-                            .collect::<Vec<usize>>()
-                    })
-                    .collect::<Vec<Vec<usize>>>();
-                for e in elements {
-                    let p: &Vec<f32> = &v[e[0] - 1];
-                    let t: &Vec<f32> = &vt[e[1] - 1];
-                    let n: &Vec<f32> = &vn[e[2] - 1];
-                    vertex_data.push(Vertex {
-                        _position: [p[0], p[1], p[2], 1.0],
-                        _normal: [n[0], n[1], n[2], 1.0],
-                        _tex_coord: [t[0], t[1]],
-                    });
-                    index_data.push(index_data.len() as u16);
-                }
-                continue;
-            }
-            let data = n
-                .split(' ')
-                .skip(1)
-                .map(|n| n.parse::<f32>().unwrap())
-                .collect::<Vec<f32>>();
-            if n.starts_with("v ") {
-                v.push(data);
-            } else if n.starts_with("vt ") {
-                vt.push(data);
-            } else if n.starts_with("vn ") {
-                vn.push(data);
-            }
+        let mut parts = Vec::new();
+        for n in scene.primitives {
+            let start = index_data.len() as u32;
+            let offset = vertex_data.len() as u32;
+            index_data.extend(n.indices.iter().map(|i| i + offset));
+            vertex_data.extend(n.vertices);
+            let material = world_pipeline.create_material(
+                device,
+                n.base_color_texture
+                    .map(|i| (&texture_views[&i], &samplers[&n.base_color_sampler])),
+                Vec4::from(n.base_color_factor),
+            );
+            parts.push(ModelPart {
+                index_range: start..index_data.len() as u32,
+                material: Some(material),
+            });
         }
-        Ok(Self::from(device, &vertex_data, &index_data))
-    }
+        let (vertex_data, index_data) = deduplicate(&vertex_data, &index_data);
 
-    /// A static method to create a cube model.
-    pub fn cube(device: &Device) -> Self {
-        Self::from_obj(device, CUBE_OBJ).unwrap()
+        let mut model = Self::from(device, &vertex_data, &index_data);
+        model.parts = parts;
+        Ok(model)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    /// A static method to create a square model.
-    pub fn square(device: &Device) -> Self {
-        Self::from_obj(device, SQUARE_OBJ).unwrap()
+    #[test]
+    fn identical_vertices_are_merged() {
+        let cube = Shape::parse("cuboid(1,1,1)")
+            .unwrap()
+            .create_mesh()
+            .to_obj();
+        let (vertex_data, index_data) = obj::parse(&cube).unwrap();
+        assert_eq!(vertex_data.len(), 36);
+        let (vertices, indices) = deduplicate(&vertex_data, &index_data);
+        // 4 corners of 6 faces with different normals
+        assert_eq!(vertices.len(), 24);
+        assert_eq!(indices.len(), 36);
+        for (i, n) in index_data.iter().enumerate() {
+            let (a, b) = (vertex_data[*n as usize], vertices[indices[i] as usize]);
+            assert_eq!(a.to_bits(), b.to_bits());
+        }
     }
 
-    /// A static method to create a sphere model.
-    pub fn sphere(device: &Device) -> Self {
-        Self::from_obj(device, SPHERE_OBJ).unwrap()
+    #[test]
+    fn index_format_is_chosen_by_vertex_count() {
+        assert_eq!(get_index_format(3), IndexFormat::Uint16);
+        // the index 65535 is the primitive restart value
+        assert_eq!(get_index_format(65534), IndexFormat::Uint16);
+        assert_eq!(get_index_format(65535), IndexFormat::Uint32);
+        assert_eq!(get_index_format(65536), IndexFormat::Uint32);
     }
 }
@@ -0,0 +1,431 @@
+//! A module for the glTF 2.0 format.
+//!
+//! Both `.gltf` and `.glb` are supported, and the buffers can be in the binary chunk,
+//! in external files or in base64 data URIs.
+//! The meshes of all the nodes in the default scene are imported with the node transforms applied.
+//!
+//! The following are imported for each primitive.
+//!
+//! - positions, normals and texture coordinates
+//! - the base color texture with its sampler and the base color factor of the material
+//!
+//! If the normals are missing, the smooth normals are calculated from the triangles.
+//!
+//! WARN: Only triangles and PNG images are supported.
+//!       Animations, skins, morph targets and the other material properties are ignored.
+
+use super::{super::texture::image::SamplerConfig, Vertex};
+use ::gltf::{
+    buffer, image,
+    mesh::Mode,
+    texture::{MagFilter, MinFilter, Sampler, WrappingMode},
+    Gltf, Node,
+};
+use glam::{Mat4, Vec2, Vec3};
+use std::{borrow::Cow, collections::HashMap};
+use wgpu::{AddressMode, FilterMode};
+
+/// The signature of PNG files.
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// A primitive of a mesh transformed into the scene.
+pub struct Primitive {
+    pub vertices: Vec<Vertex>,
+    /// The triangle indices starting from 0 in this primitive.
+    pub indices: Vec<u32>,
+    /// The image index of the base color texture.
+    pub base_color_texture: Option<usize>,
+    /// The sampler of the base color texture.
+    pub base_color_sampler: SamplerConfig,
+    /// The linear RGBA color multiplied with the base color texture.
+    pub base_color_factor: [f32; 4],
+}
+
+/// A scene imported from a glTF file.
+pub struct Scene {
+    pub primitives: Vec<Primitive>,
+    /// The encoded PNG data of the images used by the primitives, keyed by the image index.
+    pub images: HashMap<usize, Vec<u8>>,
+}
+
+/// A function to decode the percent-encoded characters of a URI.
+fn decode_percent(uri: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut rest = uri.as_bytes();
+    while let Some((n, tail)) = rest.split_first() {
+        let hex = tail
+            .get(..2)
+            .and_then(|n| u8::from_str_radix(std::str::from_utf8(n).ok()?, 16).ok());
+        match (*n, hex) {
+            (b'%', Some(n)) => {
+                bytes.push(n);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(*n);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// A function to read the data of a URI, which is a base64 data URI or a relative path.
+fn read_uri(
+    uri: &str,
+    read: &impl Fn(&str) -> Result<Cow<'static, [u8]>, String>,
+) -> Result<Cow<'static, [u8]>, String> {
+    let Some(data) = uri.strip_prefix("data:") else {
+        return read(&decode_percent(uri));
+    };
+    let Some((_, data)) = data.split_once(";base64,") else {
+        return Err("only base64 data URIs are supported".to_string());
+    };
+    base64::decode(data)
+        .map(Cow::Owned)
+        .map_err(|e| format!("invalid base64 data: {}", e))
+}
+
+/// A function to get the smooth normals by averaging the normals of the triangles around each vertex.
+fn calculate_normals(positions: &[Vec3], indices: &[u32]) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::ZERO; positions.len()];
+    for n in indices.chunks_exact(3) {
+        let [a, b, c] = [n[0], n[1], n[2]].map(|i| i as usize);
+        // NOTE: The front face is counter-clockwise.
+        let normal = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+        for i in [a, b, c] {
+            normals[i] += normal;
+        }
+    }
+    normals.into_iter().map(|n| n.normalize_or_zero()).collect()
+}
+
+/// A function to convert a glTF sampler into a sampler configuration.
+///
+/// The unspecified filters are linear.
+///
+/// NOTE: The textures always have mip levels, so a minification filter without mipmapping
+///       is approximated by the nearest mip level.
+fn read_sampler(sampler: &Sampler) -> SamplerConfig {
+    let mag_filter = match sampler.mag_filter() {
+        Some(MagFilter::Nearest) => FilterMode::Nearest,
+        _ => FilterMode::Linear,
+    };
+    let (min_filter, mipmap_filter) = match sampler.min_filter() {
+        Some(MinFilter::Nearest) | Some(MinFilter::NearestMipmapNearest) => {
+            (FilterMode::Nearest, FilterMode::Nearest)
+        }
+        Some(MinFilter::Linear) | Some(MinFilter::LinearMipmapNearest) => {
+            (FilterMode::Linear, FilterMode::Nearest)
+        }
+        Some(MinFilter::NearestMipmapLinear) => (FilterMode::Nearest, FilterMode::Linear),
+        Some(MinFilter::LinearMipmapLinear) | None => (FilterMode::Linear, FilterMode::Linear),
+    };
+    let address_mode = |n| match n {
+        WrappingMode::ClampToEdge => AddressMode::ClampToEdge,
+        WrappingMode::MirroredRepeat => AddressMode::MirrorRepeat,
+        WrappingMode::Repeat => AddressMode::Repeat,
+    };
+    SamplerConfig {
+        mag_filter,
+        min_filter,
+        mipmap_filter,
+        address_mode_u: address_mode(sampler.wrap_s()),
+        address_mode_v: address_mode(sampler.wrap_t()),
+        ..Default::default()
+    }
+}
+
+fn read_primitive(
+    primitive: &::gltf::Primitive,
+    matrix: Mat4,
+    buffers: &[Cow<[u8]>],
+) -> Result<Primitive, String> {
+    if primitive.mode() != Mode::Triangles {
+        return Err(format!(
+            "only triangles are supported, but {:?} found",
+            primitive.mode()
+        ));
+    }
+    let pbr = primitive.material().pbr_metallic_roughness();
+    let base_color_texture = pbr.base_color_texture();
+    let reader = primitive.reader(|n| buffers.get(n.index()).map(|n| &n[..]));
+
+    // read the attributes
+    let positions = reader
+        .read_positions()
+        .ok_or("no position is found")?
+        .map(Vec3::from)
+        .collect::<Vec<Vec3>>();
+    let indices = match reader.read_indices() {
+        Some(n) => n.into_u32().collect::<Vec<u32>>(),
+        None => (0..positions.len() as u32).collect(),
+    };
+    if !indices.len().is_multiple_of(3) {
+        return Err(format!(
+            "the count of indices must be a multiple of 3, but {} found",
+            indices.len()
+        ));
+    }
+    if let Some(n) = indices.iter().find(|n| **n as usize >= positions.len()) {
+        return Err(format!(
+            "index {} is out of range, only {} vertices defined",
+            n,
+            positions.len()
+        ));
+    }
+    let normals = match reader.read_normals() {
+        Some(n) => n.map(Vec3::from).collect::<Vec<Vec3>>(),
+        None => calculate_normals(&positions, &indices),
+    };
+    let tex_coord_set = base_color_texture.as_ref().map_or(0, |n| n.tex_coord());
+    let tex_coords = match reader.read_tex_coords(tex_coord_set) {
+        Some(n) => n.into_f32().map(Vec2::from).collect::<Vec<Vec2>>(),
+        None => vec![Vec2::ZERO; positions.len()],
+    };
+    if normals.len() != positions.len() || tex_coords.len() != positions.len() {
+        return Err("the counts of the attributes are different".to_string());
+    }
+
+    // transform the vertices into the scene
+    let normal_matrix = matrix.inverse().transpose();
+    let vertices = (0..positions.len())
+        .map(|i| {
+            let position = matrix.transform_point3(positions[i]);
+            let normal = normal_matrix
+                .transform_vector3(normals[i])
+                .normalize_or_zero();
+            Vertex {
+                _position: [position.x, position.y, position.z, 1.0],
+                _normal: [normal.x, normal.y, normal.z, 1.0],
+                _tex_coord: [tex_coords[i].x, tex_coords[i].y],
+            }
+        })
+        .collect();
+
+    Ok(Primitive {
+        vertices,
+        indices,
+        base_color_sampler: base_color_texture
+            .as_ref()
+            .map_or_else(SamplerConfig::default, |n| {
+                read_sampler(&n.texture().sampler())
+            }),
+        base_color_texture: base_color_texture.map(|n| n.texture().source().index()),
+        base_color_factor: pbr.base_color_factor(),
+    })
+}
+
+/// A function to read the primitives of a node and its descendants recursively.
+fn read_node(
+    node: &Node,
+    parent_matrix: Mat4,
+    buffers: &[Cow<[u8]>],
+    primitives: &mut Vec<Primitive>,
+) -> Result<(), String> {
+    let matrix = parent_matrix * Mat4::from_cols_array_2d(&node.transform().matrix());
+    if let Some(mesh) = node.mesh() {
+        for n in mesh.primitives() {
+            let primitive = read_primitive(&n, matrix, buffers)
+                .map_err(|e| format!("mesh {} primitive {}: {}", mesh.index(), n.index(), e))?;
+            primitives.push(primitive);
+        }
+    }
+    for n in node.children() {
+        read_node(&n, matrix, buffers, primitives)?;
+    }
+    Ok(())
+}
+
+fn read_image(
+    image: &image::Image,
+    buffers: &[Cow<[u8]>],
+    read: &impl Fn(&str) -> Result<Cow<'static, [u8]>, String>,
+) -> Result<Vec<u8>, String> {
+    let data = match image.source() {
+        image::Source::View { view, .. } => buffers[view.buffer().index()]
+            .get(view.offset()..view.offset() + view.length())
+            .ok_or("the buffer view is out of the buffer")?
+            .to_vec(),
+        image::Source::Uri { uri, .. } => read_uri(uri, read)?.into_owned(),
+    };
+    if !data.starts_with(PNG_SIGNATURE) {
+        return Err("only PNG images are supported".to_string());
+    }
+    Ok(data)
+}
+
+/// A function to parse the data of a `.gltf` or `.glb` file into a scene.
+///
+/// * `read` - the function to read an external file by a path relative to the glTF file
+pub fn parse(
+    data: &[u8],
+    read: impl Fn(&str) -> Result<Cow<'static, [u8]>, String>,
+) -> Result<Scene, String> {
+    let gltf = Gltf::from_slice(data).map_err(|e| e.to_string())?;
+
+    // read the buffers
+    let mut buffers = Vec::new();
+    for n in gltf.buffers() {
+        let data = match n.source() {
+            buffer::Source::Bin => {
+                Cow::Borrowed(gltf.blob.as_deref().ok_or("the binary chunk isn't found")?)
+            }
+            buffer::Source::Uri(uri) => {
+                read_uri(uri, &read).map_err(|e| format!("buffer {}: {}", n.index(), e))?
+            }
+        };
+        if data.len() < n.length() {
+            return Err(format!(
+                "buffer {}: {} bytes are expected, but {} found",
+                n.index(),
+                n.length(),
+                data.len()
+            ));
+        }
+        buffers.push(data);
+    }
+
+    // read the nodes of the scene
+    let scene = gltf
+        .default_scene()
+        .or_else(|| gltf.scenes().next())
+        .ok_or("no scene is found")?;
+    let mut primitives = Vec::new();
+    for n in scene.nodes() {
+        read_node(&n, Mat4::IDENTITY, &buffers, &mut primitives)?;
+    }
+
+    // read the images used by the primitives
+    let mut images = HashMap::new();
+    for n in gltf.images() {
+        if primitives
+            .iter()
+            .any(|p| p.base_color_texture == Some(n.index()))
+        {
+            let data = read_image(&n, &buffers, &read)
+                .map_err(|e| format!("image {}: {}", n.index(), e))?;
+            images.insert(n.index(), data);
+        }
+    }
+
+    Ok(Scene { primitives, images })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A function to create a glTF text of a triangle on the x-y plane.
+    ///
+    /// * `nodes` - the JSON array of the nodes, where the first node is the root
+    /// * `indices` - the indices of the triangle
+    fn create_triangle(nodes: &str, indices: [u16; 3]) -> String {
+        let positions = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let mut buffer = positions
+            .iter()
+            .flat_map(|n| n.to_le_bytes())
+            .collect::<Vec<u8>>();
+        buffer.extend(indices.iter().flat_map(|n| n.to_le_bytes()));
+        format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "scene": 0,
+                "scenes": [{{ "nodes": [0] }}],
+                "nodes": {},
+                "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "indices": 1 }}] }}],
+                "buffers": [{{ "byteLength": {}, "uri": "data:application/octet-stream;base64,{}" }}],
+                "bufferViews": [
+                    {{ "buffer": 0, "byteLength": 36 }},
+                    {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+                ],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0] }},
+                    {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+                ]
+            }}"#,
+            nodes,
+            buffer.len(),
+            base64::encode(&buffer)
+        )
+    }
+
+    fn read_nothing(path: &str) -> Result<Cow<'static, [u8]>, String> {
+        Err(format!("{} isn't found", path))
+    }
+
+    #[test]
+    fn node_transforms_are_applied() {
+        let nodes = r#"[
+            { "translation": [0, 0, 5], "children": [1] },
+            { "mesh": 0, "scale": [2, 2, 2] }
+        ]"#;
+        let scene = parse(create_triangle(nodes, [0, 1, 2]).as_bytes(), read_nothing).unwrap();
+        let primitive = &scene.primitives[0];
+        assert_eq!(primitive.indices, [0, 1, 2]);
+        assert_eq!(primitive.vertices[1]._position, [2.0, 0.0, 5.0, 1.0]);
+        // the normals are calculated from the counter-clockwise triangle
+        assert_eq!(primitive.vertices[0]._normal, [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(primitive.base_color_factor, [1.0; 4]);
+        assert!(scene.images.is_empty());
+    }
+
+    #[test]
+    fn external_buffer_is_read_by_relative_path() {
+        let gltf = create_triangle(r#"[{ "mesh": 0 }]"#, [0, 1, 2]);
+        let (start, end) = (gltf.find("data:").unwrap(), gltf.find("\" }],").unwrap());
+        let data = base64::decode(gltf[start..end].split_once(',').unwrap().1).unwrap();
+        let gltf = format!("{}my%20triangle.bin{}", &gltf[..start], &gltf[end..]);
+        let scene = parse(gltf.as_bytes(), |n| match n {
+            "my triangle.bin" => Ok(Cow::Owned(data.clone())),
+            _ => read_nothing(n),
+        })
+        .unwrap();
+        assert_eq!(scene.primitives[0].vertices.len(), 3);
+
+        let Err(e) = parse(gltf.as_bytes(), read_nothing) else {
+            panic!("no error for a missing buffer");
+        };
+        assert!(e.starts_with("buffer 0: my triangle.bin"), "{:?}", e);
+    }
+
+    #[test]
+    fn samplers_are_converted() {
+        let gltf = Gltf::from_slice(
+            br#"{
+                "asset": { "version": "2.0" },
+                "samplers": [
+                    { "magFilter": 9728, "minFilter": 9985, "wrapS": 33071, "wrapT": 33648 },
+                    {}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let samplers = gltf
+            .samplers()
+            .map(|n| read_sampler(&n))
+            .collect::<Vec<_>>();
+        assert_eq!(samplers[0].mag_filter, FilterMode::Nearest);
+        assert_eq!(samplers[0].min_filter, FilterMode::Linear);
+        assert_eq!(samplers[0].mipmap_filter, FilterMode::Nearest);
+        assert_eq!(samplers[0].address_mode_u, AddressMode::ClampToEdge);
+        assert_eq!(samplers[0].address_mode_v, AddressMode::MirrorRepeat);
+        assert_eq!(samplers[1], SamplerConfig::default());
+    }
+
+    #[test]
+    fn malformed_input_is_reported() {
+        let gltf = create_triangle(r#"[{ "mesh": 0 }]"#, [0, 1, 3]);
+        let Err(e) = parse(gltf.as_bytes(), read_nothing) else {
+            panic!("no error for an out-of-range index");
+        };
+        assert!(
+            e.starts_with("mesh 0 primitive 0: index 3 is out of range"),
+            "{:?}",
+            e
+        );
+
+        assert!(parse(b"{ \"asset\": ", read_nothing).is_err());
+        assert!(parse(b"glTF\x02\x00\x00\x00", read_nothing).is_err());
+    }
+}
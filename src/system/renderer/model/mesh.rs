@@ -0,0 +1,610 @@
+//! A module for procedural meshes.
+//!
+//! A `Shape` is a kind of mesh with its parameters, and it's written as `NAME(PARAMETER,...)` in text.
+//!
+//! - `cuboid(WIDTH,HEIGHT,DEPTH)`
+//! - `sphere(RADIUS,LATITUDE_COUNT,LONGITUDE_COUNT)`: `LATITUDE_COUNT` must be even
+//! - `rectangle(WIDTH,HEIGHT)`: on the xy-plane facing -z
+//! - `cylinder(RADIUS,HEIGHT,SEGMENT_COUNT)`
+//! - `capsule(RADIUS,HEIGHT,SEGMENT_COUNT,RING_COUNT)`: `HEIGHT` includes the hemispheres
+//! - `plane(WIDTH,DEPTH,SUBDIVISION_X,SUBDIVISION_Z)`: on the xz-plane facing +y
+//! - `torus(MAJOR_RADIUS,MINOR_RADIUS,MAJOR_SEGMENT_COUNT,MINOR_SEGMENT_COUNT)`
+//!
+//! The meshes are centered at the origin, and the axes of the rotational ones are the y-axis.
+//! When viewed from the front, the vertices of all faces are connected in a counter-clockwise order.
+
+use super::Vertex;
+use glam::{Vec2, Vec3};
+use std::{
+    f32::consts,
+    fmt::{self, Display, Formatter, Write},
+};
+
+/// The maximum count of the divisions of a shape.
+const MAX_DIVISION_COUNT: u32 = 1024;
+
+/// A struct for a mesh made of triangles.
+pub struct Mesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    fn new() -> Self {
+        Self {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    fn push_vertex(&mut self, position: Vec3, normal: Vec3, tex_coord: Vec2) -> u32 {
+        self.vertices.push(Vertex {
+            _position: [position.x, position.y, position.z, 1.0],
+            _normal: [normal.x, normal.y, normal.z, 1.0],
+            _tex_coord: [tex_coord.x, tex_coord.y],
+        });
+        self.vertices.len() as u32 - 1
+    }
+
+    /// A method to add a grid of quads whose vertices are given by `f(column, row)`.
+    ///
+    /// The texture coordinates are `(column / column_count, row / row_count)`.
+    ///
+    /// NOTE: The front face is the side that the direction of the rows × the direction of the columns points to.
+    fn push_grid(
+        &mut self,
+        column_count: u32,
+        row_count: u32,
+        f: impl Fn(u32, u32) -> (Vec3, Vec3),
+    ) {
+        let start = self.vertices.len() as u32;
+        for j in 0..=row_count {
+            for i in 0..=column_count {
+                let (position, normal) = f(i, j);
+                let tex_coord =
+                    Vec2::new(i as f32 / column_count as f32, j as f32 / row_count as f32);
+                self.push_vertex(position, normal, tex_coord);
+            }
+        }
+        for j in 0..row_count {
+            for i in 0..column_count {
+                let a = start + j * (column_count + 1) + i;
+                let b = a + column_count + 1;
+                self.indices
+                    .extend_from_slice(&[a, b, b + 1, a, b + 1, a + 1]);
+            }
+        }
+    }
+
+    /// A method to add a disk on the xz-plane at `y` as a fan.
+    fn push_disk(&mut self, radius: f32, y: f32, segment_count: u32, is_upward: bool) {
+        let normal = if is_upward { Vec3::Y } else { Vec3::NEG_Y };
+        let center = self.push_vertex(Vec3::new(0.0, y, 0.0), normal, Vec2::splat(0.5));
+        for i in 0..=segment_count {
+            let (s, c) = (consts::TAU / segment_count as f32 * i as f32).sin_cos();
+            let position = Vec3::new(radius * s, y, radius * c);
+            let tex_coord = Vec2::new(0.5 + 0.5 * s, 0.5 - 0.5 * c);
+            self.push_vertex(position, normal, tex_coord);
+        }
+        for i in 1..=segment_count {
+            let (a, b) = (center + i, center + i + 1);
+            if is_upward {
+                self.indices.extend_from_slice(&[center, a, b]);
+            } else {
+                self.indices.extend_from_slice(&[center, b, a]);
+            }
+        }
+    }
+
+    /// A method to add a hemisphere of the sphere.
+    ///
+    /// NOTE: The texture of the sphere has the northern hemisphere on the left half
+    ///       and the southern one on the right half, as seen from the poles.
+    fn push_hemisphere(
+        &mut self,
+        radius: f32,
+        latitude_count: u32,
+        longitude_count: u32,
+        is_northern: bool,
+    ) {
+        let half_latitude_count = latitude_count / 2;
+        let (start, end, x_offset) = if is_northern {
+            (1, half_latitude_count + 1, 0.25)
+        } else {
+            (half_latitude_count, latitude_count, 0.75)
+        };
+        for lo in start..end {
+            let theta_lo = consts::PI / latitude_count as f32 * lo as f32;
+            let l = radius * theta_lo.sin().abs();
+            let y = radius * theta_lo.cos();
+            let k = if is_northern { lo } else { latitude_count - lo };
+            let k = 0.98 / half_latitude_count as f32 * k as f32;
+            for la in 0..longitude_count {
+                let (s, c) = (consts::TAU / longitude_count as f32 * la as f32).sin_cos();
+                let position = Vec3::new(l * s, y, l * c);
+                let tex_coord = Vec2::new(x_offset + s * 0.25 * k, 0.5 + c * 0.5 * k);
+                self.push_vertex(position, position.normalize(), tex_coord);
+            }
+        }
+    }
+
+    /// A method to create a Wavefront OBJ text of the mesh.
+    pub fn to_obj(&self) -> String {
+        let mut obj = String::new();
+        for n in &self.vertices {
+            let (p, t, v) = (n._position, n._tex_coord, n._normal);
+            let _ = writeln!(obj, "v {} {} {}", p[0], p[1], p[2]);
+            let _ = writeln!(obj, "vt {} {}", t[0], t[1]);
+            let _ = writeln!(obj, "vn {} {} {}", v[0], v[1], v[2]);
+        }
+        for n in self.indices.chunks(3) {
+            let _ = writeln!(
+                obj,
+                "f {0}/{0}/{0} {1}/{1}/{1} {2}/{2}/{2}",
+                n[0] + 1,
+                n[1] + 1,
+                n[2] + 1
+            );
+        }
+        obj
+    }
+}
+
+/// An enum for the kinds of procedural meshes with their parameters.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Shape {
+    Cuboid {
+        width: f32,
+        height: f32,
+        depth: f32,
+    },
+    Sphere {
+        radius: f32,
+        latitude_count: u32,
+        longitude_count: u32,
+    },
+    Rectangle {
+        width: f32,
+        height: f32,
+    },
+    Cylinder {
+        radius: f32,
+        height: f32,
+        segment_count: u32,
+    },
+    Capsule {
+        radius: f32,
+        height: f32,
+        segment_count: u32,
+        /// The count of the rings of each hemisphere.
+        ring_count: u32,
+    },
+    Plane {
+        width: f32,
+        depth: f32,
+        subdivision_x: u32,
+        subdivision_z: u32,
+    },
+    Torus {
+        major_radius: f32,
+        minor_radius: f32,
+        major_segment_count: u32,
+        minor_segment_count: u32,
+    },
+}
+
+fn parse_size(value: &str) -> Result<f32, String> {
+    let n = value
+        .parse::<f32>()
+        .map_err(|e| format!("invalid number '{}': {}", value, e))?;
+    if !n.is_finite() || n <= 0.0 {
+        return Err(format!("the size must be positive, but {} found", value));
+    }
+    Ok(n)
+}
+
+fn parse_count(value: &str, min: u32) -> Result<u32, String> {
+    let n = value
+        .parse::<u32>()
+        .map_err(|e| format!("invalid count '{}': {}", value, e))?;
+    if n < min || n > MAX_DIVISION_COUNT {
+        return Err(format!(
+            "the count must be {} to {}, but {} found",
+            min, MAX_DIVISION_COUNT, n
+        ));
+    }
+    Ok(n)
+}
+
+impl Shape {
+    /// A static method to parse a shape written as `NAME(PARAMETER,...)`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let Some((name, parameters)) = text.strip_suffix(')').and_then(|n| n.split_once('('))
+        else {
+            return Err(format!("'{}' isn't written as NAME(PARAMETER,...)", text));
+        };
+        let values = parameters.split(',').map(str::trim).collect::<Vec<&str>>();
+        let expected = match name {
+            "rectangle" => 2,
+            "cuboid" | "sphere" | "cylinder" => 3,
+            "capsule" | "plane" | "torus" => 4,
+            _ => return Err(format!("unknown shape '{}'", name)),
+        };
+        if values.len() != expected {
+            return Err(format!(
+                "{} parameters are expected for {}, but {} found",
+                expected,
+                name,
+                values.len()
+            ));
+        }
+        let shape = match name {
+            "cuboid" => Self::Cuboid {
+                width: parse_size(values[0])?,
+                height: parse_size(values[1])?,
+                depth: parse_size(values[2])?,
+            },
+            "sphere" => {
+                let latitude_count = parse_count(values[1], 2)?;
+                if !latitude_count.is_multiple_of(2) {
+                    return Err(format!(
+                        "the latitude count must be even, but {} found",
+                        latitude_count
+                    ));
+                }
+                Self::Sphere {
+                    radius: parse_size(values[0])?,
+                    latitude_count,
+                    longitude_count: parse_count(values[2], 3)?,
+                }
+            }
+            "rectangle" => Self::Rectangle {
+                width: parse_size(values[0])?,
+                height: parse_size(values[1])?,
+            },
+            "cylinder" => Self::Cylinder {
+                radius: parse_size(values[0])?,
+                height: parse_size(values[1])?,
+                segment_count: parse_count(values[2], 3)?,
+            },
+            "capsule" => Self::Capsule {
+                radius: parse_size(values[0])?,
+                height: parse_size(values[1])?,
+                segment_count: parse_count(values[2], 3)?,
+                ring_count: parse_count(values[3], 1)?,
+            },
+            "plane" => Self::Plane {
+                width: parse_size(values[0])?,
+                depth: parse_size(values[1])?,
+                subdivision_x: parse_count(values[2], 1)?,
+                subdivision_z: parse_count(values[3], 1)?,
+            },
+            _ => Self::Torus {
+                major_radius: parse_size(values[0])?,
+                minor_radius: parse_size(values[1])?,
+                major_segment_count: parse_count(values[2], 3)?,
+                minor_segment_count: parse_count(values[3], 3)?,
+            },
+        };
+        Ok(shape)
+    }
+
+    /// A method to get the name of the shape.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Cuboid { .. } => "cuboid",
+            Self::Sphere { .. } => "sphere",
+            Self::Rectangle { .. } => "rectangle",
+            Self::Cylinder { .. } => "cylinder",
+            Self::Capsule { .. } => "capsule",
+            Self::Plane { .. } => "plane",
+            Self::Torus { .. } => "torus",
+        }
+    }
+
+    /// A method to create the mesh of the shape.
+    ///
+    /// WARN: The parameters must be valid as the ones accepted by `parse()`.
+    pub fn create_mesh(&self) -> Mesh {
+        let mut mesh = Mesh::new();
+        match *self {
+            Self::Cuboid {
+                width,
+                height,
+                depth,
+            } => {
+                // NOTE: The texture is upright on each face when viewed from the outside.
+                let half_size = Vec3::new(width, height, depth) / 2.0;
+                let faces = [
+                    (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+                    (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+                    (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+                    (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+                    (Vec3::Z, Vec3::X, Vec3::Y),
+                    (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+                ];
+                for (normal, right, up) in faces {
+                    let start = mesh.vertices.len() as u32;
+                    for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+                        let position = (normal + right * x + up * y) * half_size;
+                        let tex_coord = Vec2::new((x + 1.0) / 2.0, (1.0 - y) / 2.0);
+                        mesh.push_vertex(position, normal, tex_coord);
+                    }
+                    mesh.indices.extend_from_slice(&[
+                        start,
+                        start + 1,
+                        start + 2,
+                        start,
+                        start + 2,
+                        start + 3,
+                    ]);
+                }
+            }
+            Self::Sphere {
+                radius,
+                latitude_count,
+                longitude_count,
+            } => {
+                // vertices
+                mesh.push_vertex(Vec3::new(0.0, radius, 0.0), Vec3::Y, Vec2::new(0.25, 0.5));
+                mesh.push_hemisphere(radius, latitude_count, longitude_count, true);
+                mesh.push_hemisphere(radius, latitude_count, longitude_count, false);
+                let south_pole = mesh.push_vertex(
+                    Vec3::new(0.0, -radius, 0.0),
+                    Vec3::NEG_Y,
+                    Vec2::new(0.75, 0.5),
+                );
+
+                // indices
+                // NOTE: The southern hemisphere has its own ring at the equator,
+                //       so the rows below the equator are shifted by a ring.
+                let ring = |lo: u32, la: u32| 1 + longitude_count * lo + la % longitude_count;
+                for la in 0..longitude_count {
+                    mesh.indices
+                        .extend_from_slice(&[0, ring(0, la), ring(0, la + 1)]);
+                }
+                for lo in 0..latitude_count - 2 {
+                    let lo = if lo < latitude_count / 2 - 1 {
+                        lo
+                    } else {
+                        lo + 1
+                    };
+                    for la in 0..longitude_count {
+                        let (u1, u2) = (ring(lo, la), ring(lo, la + 1));
+                        let (b1, b2) = (ring(lo + 1, la), ring(lo + 1, la + 1));
+                        mesh.indices.extend_from_slice(&[u1, b1, b2, u1, b2, u2]);
+                    }
+                }
+                for la in 0..longitude_count {
+                    let lo = latitude_count - 1;
+                    mesh.indices
+                        .extend_from_slice(&[ring(lo, la), south_pole, ring(lo, la + 1)]);
+                }
+            }
+            Self::Rectangle { width, height } => {
+                // NOTE: The texture is upright when viewed along the positive z-axis.
+                let (hw, hh) = (width / 2.0, height / 2.0);
+                for (x, y, u, v) in [
+                    (-hw, -hh, 0.0, 1.0),
+                    (-hw, hh, 0.0, 0.0),
+                    (hw, hh, 1.0, 0.0),
+                    (hw, -hh, 1.0, 1.0),
+                ] {
+                    mesh.push_vertex(Vec3::new(x, y, 0.0), Vec3::NEG_Z, Vec2::new(u, v));
+                }
+                mesh.indices.extend_from_slice(&[0, 1, 2, 0, 2, 3]);
+            }
+            Self::Cylinder {
+                radius,
+                height,
+                segment_count,
+            } => {
+                let hh = height / 2.0;
+                mesh.push_grid(segment_count, 1, |i, j| {
+                    let (s, c) = (consts::TAU / segment_count as f32 * i as f32).sin_cos();
+                    let y = if j == 0 { hh } else { -hh };
+                    (Vec3::new(radius * s, y, radius * c), Vec3::new(s, 0.0, c))
+                });
+                mesh.push_disk(radius, hh, segment_count, true);
+                mesh.push_disk(radius, -hh, segment_count, false);
+            }
+            Self::Capsule {
+                radius,
+                height,
+                segment_count,
+                ring_count,
+            } => {
+                // NOTE: The rows from the top are the northern hemisphere and the southern one,
+                //       and the quads between their equators are the side of the cylinder.
+                let hh = (height / 2.0 - radius).max(0.0);
+                mesh.push_grid(segment_count, ring_count * 2 + 1, |i, j| {
+                    let (s, c) = (consts::TAU / segment_count as f32 * i as f32).sin_cos();
+                    let (j, y) = if j <= ring_count {
+                        (j, hh)
+                    } else {
+                        (j - 1, -hh)
+                    };
+                    let (sp, cp) = (consts::FRAC_PI_2 / ring_count as f32 * j as f32).sin_cos();
+                    let normal = Vec3::new(sp * s, cp, sp * c);
+                    (normal * radius + Vec3::new(0.0, y, 0.0), normal)
+                });
+            }
+            Self::Plane {
+                width,
+                depth,
+                subdivision_x,
+                subdivision_z,
+            } => {
+                mesh.push_grid(subdivision_x, subdivision_z, |i, j| {
+                    let x = (i as f32 / subdivision_x as f32 - 0.5) * width;
+                    let z = (j as f32 / subdivision_z as f32 - 0.5) * depth;
+                    (Vec3::new(x, 0.0, z), Vec3::Y)
+                });
+            }
+            Self::Torus {
+                major_radius,
+                minor_radius,
+                major_segment_count,
+                minor_segment_count,
+            } => {
+                mesh.push_grid(major_segment_count, minor_segment_count, |i, j| {
+                    let (s, c) = (consts::TAU / major_segment_count as f32 * i as f32).sin_cos();
+                    let (sp, cp) = (consts::TAU / minor_segment_count as f32 * j as f32).sin_cos();
+                    let normal = Vec3::new(cp * s, -sp, cp * c);
+                    let center = Vec3::new(major_radius * s, 0.0, major_radius * c);
+                    (center + normal * minor_radius, normal)
+                });
+            }
+        }
+        mesh
+    }
+}
+
+impl Display for Shape {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let parameters = match *self {
+            Self::Cuboid {
+                width,
+                height,
+                depth,
+            } => format!("{},{},{}", width, height, depth),
+            Self::Sphere {
+                radius,
+                latitude_count,
+                longitude_count,
+            } => format!("{},{},{}", radius, latitude_count, longitude_count),
+            Self::Rectangle { width, height } => format!("{},{}", width, height),
+            Self::Cylinder {
+                radius,
+                height,
+                segment_count,
+            } => format!("{},{},{}", radius, height, segment_count),
+            Self::Capsule {
+                radius,
+                height,
+                segment_count,
+                ring_count,
+            } => format!("{},{},{},{}", radius, height, segment_count, ring_count),
+            Self::Plane {
+                width,
+                depth,
+                subdivision_x,
+                subdivision_z,
+            } => format!("{},{},{},{}", width, depth, subdivision_x, subdivision_z),
+            Self::Torus {
+                major_radius,
+                minor_radius,
+                major_segment_count,
+                minor_segment_count,
+            } => format!(
+                "{},{},{},{}",
+                major_radius, minor_radius, major_segment_count, minor_segment_count
+            ),
+        };
+        write!(f, "{}({})", self.get_name(), parameters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHAPES: &[&str] = &[
+        "cuboid(1,2,3)",
+        "sphere(1,20,20)",
+        "sphere(0.5,2,3)",
+        "rectangle(1,1)",
+        "cylinder(0.5,2,16)",
+        "capsule(0.5,2,12,4)",
+        "capsule(1,1,3,1)",
+        "plane(4,2,3,5)",
+        "torus(1,0.25,24,12)",
+    ];
+
+    #[test]
+    fn faces_are_counter_clockwise_from_front() {
+        for text in SHAPES {
+            let mesh = Shape::parse(text).unwrap().create_mesh();
+            assert!(mesh.indices.len().is_multiple_of(3), "{}", text);
+            assert!(
+                mesh.indices
+                    .iter()
+                    .all(|n| (*n as usize) < mesh.vertices.len()),
+                "{}",
+                text
+            );
+            for n in mesh.indices.chunks(3) {
+                let vertices = n.iter().map(|i| mesh.vertices[*i as usize]);
+                let positions = vertices
+                    .clone()
+                    .map(|v| Vec3::from_slice(&v._position[..3]))
+                    .collect::<Vec<Vec3>>();
+                let normal = vertices
+                    .map(|v| Vec3::from_slice(&v._normal[..3]))
+                    .sum::<Vec3>();
+                let face_normal = (positions[1] - positions[0]).cross(positions[2] - positions[0]);
+                // NOTE: The triangles at the poles of a capsule are degenerate.
+                if face_normal.length() < 1e-6 {
+                    continue;
+                }
+                assert!(face_normal.dot(normal) > 0.0, "{} {:?}", text, n);
+            }
+        }
+    }
+
+    #[test]
+    fn builtin_shapes_have_expected_sizes() {
+        let cube = Shape::parse("cuboid(1,1,1)").unwrap().create_mesh();
+        assert_eq!((cube.vertices.len(), cube.indices.len()), (24, 36));
+        for n in &cube.vertices {
+            assert!(n._position[..3].iter().all(|n| n.abs() == 0.5));
+        }
+        let square = Shape::parse("rectangle(1,1)").unwrap().create_mesh();
+        assert_eq!((square.vertices.len(), square.indices.len()), (4, 6));
+        // the poles and 20 rings, where the equator is duplicated
+        let sphere = Shape::parse("sphere(1,20,20)").unwrap().create_mesh();
+        assert_eq!(sphere.vertices.len(), 2 + 20 * 20);
+        assert_eq!(sphere.indices.len(), 20 * 3 * 2 + 18 * 20 * 6);
+        for n in &sphere.vertices {
+            assert!((Vec3::from_slice(&n._position[..3]).length() - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn shapes_are_formatted_as_parsed() {
+        for text in SHAPES {
+            assert_eq!(Shape::parse(text).unwrap().to_string(), *text);
+        }
+        assert_eq!(
+            Shape::parse("torus( 1, 0.25 ,24,12)").unwrap().to_string(),
+            "torus(1,0.25,24,12)"
+        );
+    }
+
+    #[test]
+    fn invalid_shapes_are_reported() {
+        let cases = [
+            ("cube", "'cube' isn't written as"),
+            ("cube(1,1,1)", "unknown shape 'cube'"),
+            (
+                "cuboid(1,1)",
+                "3 parameters are expected for cuboid, but 2 found",
+            ),
+            ("cuboid(1,x,1)", "invalid number 'x'"),
+            ("cuboid(1,0,1)", "the size must be positive"),
+            ("sphere(1,3,20)", "the latitude count must be even"),
+            (
+                "cylinder(1,1,2)",
+                "the count must be 3 to 1024, but 2 found",
+            ),
+            ("plane(1,1,0,1)", "the count must be 1 to 1024, but 0 found"),
+            ("torus(1,1,-3,3)", "invalid count '-3'"),
+        ];
+        for (text, expected) in cases {
+            let Err(e) = Shape::parse(text) else {
+                panic!("no error for {:?}", text);
+            };
+            assert!(e.starts_with(expected), "{:?} for {:?}", e, text);
+        }
+    }
+}
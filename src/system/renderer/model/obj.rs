@@ -0,0 +1,310 @@
+//! A module for the Wavefront OBJ format.
+//!
+//! The following statements are supported.
+//!
+//! - `v X Y Z [W]`: a position (`W` and vertex colors are ignored)
+//! - `vt U [V [W]]`: a texture coordinate
+//! - `vn X Y Z`: a normal
+//! - `f V/VT/VN ...`: a face with 3 or more corners, where `VT` and `VN` can be omitted as `V`, `V/VT` or `V//VN`
+//!
+//! The indices are 1-based, and negative indices are relative to the end of the elements defined so far.
+//! `o`, `g`, `mtllib` and `usemtl` are accepted, but all the groups are merged into one model.
+//! The other statements such as `s` and `l` are ignored.
+
+use super::Vertex;
+use glam::{Vec2, Vec3};
+
+/// A corner of a face that refers to the elements by their indices.
+struct Corner {
+    position: usize,
+    tex_coord: Option<usize>,
+    normal: Option<usize>,
+}
+
+fn parse_f32s(values: &[&str], min: usize, max: usize) -> Result<Vec<f32>, String> {
+    if values.len() < min || values.len() > max {
+        return Err(format!(
+            "{} to {} numbers are expected, but {} found",
+            min,
+            max,
+            values.len()
+        ));
+    }
+    values
+        .iter()
+        .map(|n| {
+            n.parse::<f32>()
+                .map_err(|e| format!("invalid number '{}': {}", n, e))
+        })
+        .collect()
+}
+
+/// A function to convert an index in a face into a 0-based index.
+///
+/// * `count` - the count of the elements defined so far
+/// * `kind` - the name of the elements used in the error message
+fn parse_index(value: &str, count: usize, kind: &str) -> Result<usize, String> {
+    let n = value
+        .parse::<i64>()
+        .map_err(|e| format!("invalid {} index '{}': {}", kind, value, e))?;
+    let index = match n {
+        0 => return Err(format!("{} index is 0, but indices start from 1", kind)),
+        1.. => n - 1,
+        _ => count as i64 + n,
+    };
+    if index < 0 || index >= count as i64 {
+        return Err(format!(
+            "{} index {} is out of range, only {} defined",
+            kind, n, count
+        ));
+    }
+    Ok(index as usize)
+}
+
+/// A parser that accumulates the elements and the triangulated faces line by line.
+struct Parser {
+    positions: Vec<Vec3>,
+    tex_coords: Vec<Vec2>,
+    normals: Vec<Vec3>,
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+}
+
+impl Parser {
+    fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        // NOTE: A comment can follow a statement.
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            return Ok(());
+        };
+        let values = tokens.collect::<Vec<&str>>();
+        match keyword {
+            "v" => {
+                let n = parse_f32s(&values, 3, 7)?;
+                self.positions.push(Vec3::new(n[0], n[1], n[2]));
+            }
+            "vt" => {
+                let n = parse_f32s(&values, 1, 3)?;
+                self.tex_coords
+                    .push(Vec2::new(n[0], n.get(1).copied().unwrap_or(0.0)));
+            }
+            "vn" => {
+                let n = parse_f32s(&values, 3, 3)?;
+                self.normals.push(Vec3::new(n[0], n[1], n[2]));
+            }
+            "f" => self.parse_face(&values)?,
+            "o" | "g" => (),
+            "mtllib" | "usemtl" if values.is_empty() => {
+                return Err(format!("'{}' requires a name", keyword))
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn parse_corner(&self, value: &str) -> Result<Corner, String> {
+        let mut parts = value.split('/');
+        let position = parse_index(
+            parts.next().unwrap_or_default(),
+            self.positions.len(),
+            "position",
+        )?;
+        let tex_coord = match parts.next() {
+            None | Some("") => None,
+            Some(n) => Some(parse_index(n, self.tex_coords.len(), "texture coordinate")?),
+        };
+        let normal = match parts.next() {
+            None | Some("") => None,
+            Some(n) => Some(parse_index(n, self.normals.len(), "normal")?),
+        };
+        if parts.next().is_some() {
+            return Err(format!("too many '/' in '{}'", value));
+        }
+        Ok(Corner {
+            position,
+            tex_coord,
+            normal,
+        })
+    }
+
+    /// A method to add a face triangulated as a fan.
+    ///
+    /// WARN: A concave polygon may be triangulated wrongly.
+    fn parse_face(&mut self, values: &[&str]) -> Result<(), String> {
+        if values.len() < 3 {
+            return Err(format!(
+                "a face requires 3 or more corners, but {} found",
+                values.len()
+            ));
+        }
+        let corners = values
+            .iter()
+            .map(|n| self.parse_corner(n))
+            .collect::<Result<Vec<Corner>, String>>()?;
+
+        // NOTE: If a corner has no normal, the normal of the face is used instead.
+        //       The front face is counter-clockwise as the meshes of `mesh`.
+        let positions = corners
+            .iter()
+            .map(|n| self.positions[n.position])
+            .collect::<Vec<Vec3>>();
+        let face_normal = (1..positions.len() - 1)
+            .map(|i| (positions[i] - positions[0]).cross(positions[i + 1] - positions[0]))
+            .sum::<Vec3>()
+            .normalize_or_zero();
+
+        let start = self.vertices.len() as u32;
+        for n in &corners {
+            let position = self.positions[n.position];
+            let normal = n.normal.map_or(face_normal, |i| self.normals[i]);
+            let tex_coord = n.tex_coord.map_or(Vec2::ZERO, |i| self.tex_coords[i]);
+            self.vertices.push(Vertex {
+                _position: [position.x, position.y, position.z, 1.0],
+                _normal: [normal.x, normal.y, normal.z, 1.0],
+                _tex_coord: [tex_coord.x, tex_coord.y],
+            });
+        }
+        for i in 1..corners.len() as u32 - 1 {
+            self.indices
+                .extend_from_slice(&[start, start + i, start + i + 1]);
+        }
+        Ok(())
+    }
+}
+
+/// A function to parse a Wavefront OBJ text into vertices and triangle indices.
+///
+/// If it fails, the error message starts with the line number.
+pub fn parse(obj: &str) -> Result<(Vec<Vertex>, Vec<u32>), String> {
+    let mut parser = Parser {
+        positions: Vec::new(),
+        tex_coords: Vec::new(),
+        normals: Vec::new(),
+        vertices: Vec::new(),
+        indices: Vec::new(),
+    };
+    for (i, line) in obj.lines().enumerate() {
+        parser
+            .parse_line(line)
+            .map_err(|e| format!("{}: {}", i + 1, e))?;
+    }
+    Ok((parser.vertices, parser.indices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::mesh::Shape, *};
+
+    const TRIANGLE: &str = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 0 1
+vn 0 0 1
+";
+
+    #[test]
+    fn triangle_has_all_attributes() {
+        let (vertices, indices) = parse(&format!("{}f 1/1/1 2/2/1 3/3/1\n", TRIANGLE)).unwrap();
+        assert_eq!(indices, [0, 1, 2]);
+        assert_eq!(vertices[1]._position, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(vertices[2]._tex_coord, [0.0, 1.0]);
+        assert_eq!(vertices[0]._normal, [0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn missing_attributes_are_filled() {
+        let (vertices, _) = parse(&format!("{}f 1 2/2 3//1\n", TRIANGLE)).unwrap();
+        assert_eq!(vertices[0]._tex_coord, [0.0, 0.0]);
+        assert_eq!(vertices[1]._tex_coord, [1.0, 0.0]);
+        // the counter-clockwise face faces +z
+        assert_eq!(vertices[0]._normal, [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(vertices[1]._normal, [0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn negative_indices_are_relative_to_end() {
+        let (vertices, _) = parse(&format!("{}f -3/-3/-1 -2/-2/-1 -1/-1/-1\n", TRIANGLE)).unwrap();
+        assert_eq!(vertices[0]._position, [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(vertices[2]._position, [0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(vertices[2]._tex_coord, [0.0, 1.0]);
+    }
+
+    #[test]
+    fn polygons_are_triangulated() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv -1 1 0\nf 1 2 3 4\nf 1 2 3 4 5\n";
+        let (vertices, indices) = parse(obj).unwrap();
+        assert_eq!(vertices.len(), 9);
+        assert_eq!(indices, [0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7, 4, 7, 8]);
+    }
+
+    #[test]
+    fn whitespace_comments_and_groups_are_accepted() {
+        let obj = "\
+# a comment
+mtllib\tcube.mtl
+o cube
+g  side
+usemtl red
+s off
+v\t0  0   0 # a trailing comment
+v 1 0 0
+v 0 1 0
+
+f  1\t2   3
+";
+        let (vertices, indices) = parse(obj).unwrap();
+        assert_eq!(vertices.len(), 3);
+        assert_eq!(indices.len(), 3);
+    }
+
+    #[test]
+    fn malformed_input_is_reported_with_line_number() {
+        let cases = [
+            ("v 0 0\n", "1: 3 to 7 numbers"),
+            ("v 0 0 0\nv 0 x 0\n", "2: invalid number 'x'"),
+            ("vn 0 1\n", "1: 3 to 3 numbers"),
+            ("v 0 0 0\nf 1 1\n", "2: a face requires 3 or more corners"),
+            ("v 0 0 0\nf 1 1 0\n", "2: position index is 0"),
+            ("v 0 0 0\nf 1 1 2\n", "2: position index 2 is out of range"),
+            (
+                "v 0 0 0\nf 1 1 -2\n",
+                "2: position index -2 is out of range",
+            ),
+            (
+                "v 0 0 0\nf 1/1 1 1\n",
+                "2: texture coordinate index 1 is out of range",
+            ),
+            ("v 0 0 0\nf 1//a 1 1\n", "2: invalid normal index 'a'"),
+            ("v 0 0 0\nf 1/// 1 1\n", "2: too many '/'"),
+            ("\n\nusemtl\n", "3: 'usemtl' requires a name"),
+        ];
+        for (obj, expected) in cases {
+            let Err(e) = parse(obj) else {
+                panic!("no error for {:?}", obj);
+            };
+            assert!(e.starts_with(expected), "{:?} for {:?}", e, obj);
+        }
+    }
+
+    #[test]
+    fn exported_meshes_are_read_back() {
+        for text in [
+            "cuboid(1,1,1)",
+            "rectangle(1,1)",
+            "sphere(1,20,20)",
+            "torus(1,0.25,8,6)",
+        ] {
+            let mesh = Shape::parse(text).unwrap().create_mesh();
+            let (vertices, indices) = parse(&mesh.to_obj()).unwrap();
+            assert_eq!(vertices.len(), mesh.indices.len(), "{}", text);
+            for (i, n) in mesh.indices.iter().enumerate() {
+                let (a, b) = (vertices[indices[i] as usize], mesh.vertices[*n as usize]);
+                assert_eq!(a.to_bits(), b.to_bits(), "{}", text);
+            }
+        }
+    }
+}
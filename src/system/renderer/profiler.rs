@@ -0,0 +1,290 @@
+use std::{
+    collections::VecDeque,
+    mem,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use wgpu::{
+    Buffer, BufferDescriptor, BufferUsages, CommandEncoder, Device, Maintain, MapMode, QuerySet,
+    QuerySetDescriptor, QueryType, Queue, RenderPassTimestampWrites,
+};
+
+/// The max count of profiled passes in a frame.
+///
+/// The passes exceeding it in a frame aren't profiled.
+const MAX_PASS_COUNT: u32 = 16;
+/// The count of readback buffers.
+///
+/// NOTE: The results are read some frames later, so several buffers are used in turn.
+const READBACK_BUFFER_COUNT: usize = 3;
+/// The count of frames averaged.
+const ROLLING_FRAME_COUNT: usize = 60;
+
+const READBACK_IDLE: u8 = 0;
+const READBACK_MAPPING: u8 = 1;
+const READBACK_MAPPED: u8 = 2;
+const READBACK_FAILED: u8 = 3;
+
+/// An enum for enumerating the render passes profiled on the GPU.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GpuPass {
+    Skybox,
+    World,
+    Particle,
+    /// All the post-process effects.
+    Post,
+    Ui,
+}
+
+/// All the profiled passes in a fixed order.
+pub const GPU_PASSES: [GpuPass; 5] = [
+    GpuPass::Skybox,
+    GpuPass::World,
+    GpuPass::Particle,
+    GpuPass::Post,
+    GpuPass::Ui,
+];
+
+impl GpuPass {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Self::Skybox => "skybox",
+            Self::World => "world",
+            Self::Particle => "particle",
+            Self::Post => "post",
+            Self::Ui => "ui",
+        }
+    }
+}
+
+/// A readback buffer and the passes written into it.
+struct Readback {
+    buffer: Buffer,
+    /// The state of mapping shared with the callback of `map_async`.
+    state: Arc<AtomicU8>,
+    passes: Vec<GpuPass>,
+}
+
+/// A GPU profiler with timestamp queries.
+///
+/// The timestamps are written at the beginning and the end of each profiled render pass,
+/// resolved after the frame and read asynchronously some frames later.
+/// The durations are summed up per pass in a frame and averaged over the last frames.
+/// The GPU time of a frame is the span from the beginning of the first pass to the end of the last pass.
+///
+/// WARN: It requires `Features::TIMESTAMP_QUERY`.
+pub struct GpuProfiler {
+    query_set: QuerySet,
+    resolve_buffer: Buffer,
+    readbacks: Vec<Readback>,
+    /// The index of the readback buffer used in the current frame.
+    /// If all the buffers are in use, it's `None` and the frame isn't profiled.
+    current: Option<usize>,
+    next: usize,
+    /// The passes written in the current frame.
+    passes: Vec<GpuPass>,
+    /// The nanoseconds per timestamp tick.
+    timestamp_period: f32,
+    /// The durations (ms) of the last frames for each pass in the order of `GPU_PASSES`.
+    histories: [VecDeque<f64>; GPU_PASSES.len()],
+    /// The GPU times of the frames read since they were taken last.
+    frame_times: Vec<Duration>,
+}
+
+impl GpuProfiler {
+    /// A constructor.
+    pub fn new(device: &Device, queue: &Queue) -> Self {
+        let size = mem::size_of::<u64>() as u64 * MAX_PASS_COUNT as u64 * 2;
+        let query_set = device.create_query_set(&QuerySetDescriptor {
+            label: None,
+            ty: QueryType::Timestamp,
+            count: MAX_PASS_COUNT * 2,
+        });
+        let resolve_buffer = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size,
+            usage: BufferUsages::QUERY_RESOLVE | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readbacks = (0..READBACK_BUFFER_COUNT)
+            .map(|_| Readback {
+                buffer: device.create_buffer(&BufferDescriptor {
+                    label: None,
+                    size,
+                    usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                }),
+                state: Arc::new(AtomicU8::new(READBACK_IDLE)),
+                passes: Vec::new(),
+            })
+            .collect();
+
+        Self {
+            query_set,
+            resolve_buffer,
+            readbacks,
+            current: None,
+            next: 0,
+            passes: Vec::new(),
+            timestamp_period: queue.get_timestamp_period(),
+            histories: Default::default(),
+            frame_times: Vec::new(),
+        }
+    }
+
+    /// A method to read the finished results and prepare for a new frame.
+    ///
+    /// It should be called at the beginning of every frame.
+    pub fn begin_frame(&mut self, device: &Device) {
+        // NOTE: The callbacks of `map_async` are called while polling.
+        device.poll(Maintain::Poll);
+
+        // read the results
+        for i in 0..self.readbacks.len() {
+            match self.readbacks[i].state.load(Ordering::Acquire) {
+                READBACK_MAPPED => self.read(i),
+                READBACK_FAILED => {
+                    warn!("GpuProfiler.begin_frame", "failed to read timestamps.");
+                    self.readbacks[i]
+                        .state
+                        .store(READBACK_IDLE, Ordering::Release);
+                }
+                _ => (),
+            }
+        }
+
+        // select a readback buffer
+        self.passes.clear();
+        self.current = if self.readbacks[self.next].state.load(Ordering::Acquire) == READBACK_IDLE {
+            let current = self.next;
+            self.next = (self.next + 1) % self.readbacks.len();
+            Some(current)
+        } else {
+            None
+        };
+    }
+
+    fn read(&mut self, index: usize) {
+        let readback = &mut self.readbacks[index];
+        let mut durations = [0.0; GPU_PASSES.len()];
+        let mut frame_span = (u64::MAX, 0);
+        {
+            let data = readback.buffer.slice(..).get_mapped_range();
+            for (i, pass) in readback.passes.iter().enumerate() {
+                let offset = i * 2 * mem::size_of::<u64>();
+                let begin = u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+                let end = u64::from_le_bytes(data[offset + 8..offset + 16].try_into().unwrap());
+                let nanoseconds = end.saturating_sub(begin) as f64 * self.timestamp_period as f64;
+                let j = GPU_PASSES.iter().position(|n| n == pass).unwrap();
+                durations[j] += nanoseconds / 1_000_000.0;
+                frame_span = (frame_span.0.min(begin), frame_span.1.max(end));
+            }
+        }
+        let nanoseconds =
+            frame_span.1.saturating_sub(frame_span.0) as f64 * self.timestamp_period as f64;
+        self.frame_times
+            .push(Duration::from_secs_f64(nanoseconds / 1_000_000_000.0));
+        readback.buffer.unmap();
+        readback.state.store(READBACK_IDLE, Ordering::Release);
+
+        // NOTE: Only the passes drawn in the frame are counted.
+        for (i, history) in self.histories.iter_mut().enumerate() {
+            if !readback.passes.contains(&GPU_PASSES[i]) {
+                continue;
+            }
+            if history.len() >= ROLLING_FRAME_COUNT {
+                history.pop_front();
+            }
+            history.push_back(durations[i]);
+        }
+    }
+
+    /// A method to get the timestamp writes for a render pass.
+    ///
+    /// If the frame isn't profiled or too many passes are drawn, it returns `None`.
+    pub fn get_timestamp_writes(&mut self, pass: GpuPass) -> Option<RenderPassTimestampWrites<'_>> {
+        self.current?;
+        let index = self.passes.len() as u32;
+        if index >= MAX_PASS_COUNT {
+            return None;
+        }
+        self.passes.push(pass);
+        Some(RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(index * 2),
+            end_of_pass_write_index: Some(index * 2 + 1),
+        })
+    }
+
+    /// A method to resolve the timestamps into the readback buffer.
+    ///
+    /// It should be called after all the passes are encoded.
+    pub fn resolve(&mut self, command_encoder: &mut CommandEncoder) {
+        let Some(current) = self.current else {
+            return;
+        };
+        if self.passes.is_empty() {
+            return;
+        }
+        let count = self.passes.len() as u32 * 2;
+        let size = mem::size_of::<u64>() as u64 * count as u64;
+        command_encoder.resolve_query_set(&self.query_set, 0..count, &self.resolve_buffer, 0);
+        command_encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readbacks[current].buffer,
+            0,
+            size,
+        );
+    }
+
+    /// A method to start reading the timestamps asynchronously.
+    ///
+    /// It should be called after the commands are submitted.
+    pub fn end_frame(&mut self) {
+        let Some(current) = self.current.take() else {
+            return;
+        };
+        if self.passes.is_empty() {
+            return;
+        }
+        let readback = &mut self.readbacks[current];
+        readback.passes = mem::take(&mut self.passes);
+        readback.state.store(READBACK_MAPPING, Ordering::Release);
+        let state = Arc::clone(&readback.state);
+        readback
+            .buffer
+            .slice(..)
+            .map_async(MapMode::Read, move |result| {
+                let value = if result.is_ok() {
+                    READBACK_MAPPED
+                } else {
+                    READBACK_FAILED
+                };
+                state.store(value, Ordering::Release);
+            });
+    }
+
+    /// A method to get the average duration of a pass over the last frames.
+    ///
+    /// If the pass hasn't been measured yet, it returns `None`.
+    pub fn get_average(&self, pass: GpuPass) -> Option<Duration> {
+        let i = GPU_PASSES.iter().position(|n| *n == pass)?;
+        let history = &self.histories[i];
+        if history.is_empty() {
+            return None;
+        }
+        let milliseconds = history.iter().sum::<f64>() / history.len() as f64;
+        Some(Duration::from_secs_f64(milliseconds / 1000.0))
+    }
+
+    /// A method to take the GPU times of the frames read since the last call.
+    ///
+    /// NOTE: The frames are read some frames later, and the frames not profiled are missing.
+    pub fn take_frame_times(&mut self) -> Vec<Duration> {
+        mem::take(&mut self.frame_times)
+    }
+}
@@ -1,3 +1,32 @@
+pub mod crosshair;
+pub mod mipmap;
+pub mod particle;
+pub mod post;
 pub mod skybox;
 pub mod ui;
 pub mod world;
+
+use futures::executor;
+use std::borrow::Cow;
+use wgpu::{Device, ErrorFilter, ShaderModule, ShaderModuleDescriptor, ShaderSource};
+
+/// A function to compile a WGSL shader and create something such as render pipelines with it.
+///
+/// The validation errors are captured instead of being reported as uncaptured errors,
+/// so an invalid shader can be rejected without breaking the current pipelines.
+pub fn try_compile<T>(
+    device: &Device,
+    shader: &str,
+    create: impl FnOnce(&ShaderModule) -> T,
+) -> Result<T, String> {
+    device.push_error_scope(ErrorFilter::Validation);
+    let shader_module = device.create_shader_module(ShaderModuleDescriptor {
+        label: None,
+        source: ShaderSource::Wgsl(Cow::from(shader)),
+    });
+    let result = create(&shader_module);
+    match executor::block_on(device.pop_error_scope()) {
+        Some(e) => Err(e.to_string()),
+        None => Ok(result),
+    }
+}
@@ -0,0 +1,299 @@
+use crate::{
+    system::{
+        asset::{AssetManager, ShaderHandle},
+        renderer::{
+            error::RendererError,
+            model::{self, Model},
+            shader,
+        },
+    },
+    util::memory,
+};
+use glam::{Vec2, Vec4};
+use std::{borrow::Cow, mem};
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingType, BlendComponent, BlendFactor, BlendOperation, BlendState,
+    Buffer, BufferBindingType, BufferSize, BufferUsages, ColorTargetState, CommandEncoder, Device,
+    FragmentState, LoadOp, MultisampleState, Operations, PipelineLayout, PipelineLayoutDescriptor,
+    PrimitiveState, Queue, RenderPassColorAttachment, RenderPassDescriptor,
+    RenderPassTimestampWrites, RenderPipeline, RenderPipelineDescriptor, ShaderModule,
+    ShaderModuleDescriptor, ShaderSource, ShaderStages, StoreOp, TextureView, VertexState,
+};
+
+/// The margin (px) of the quad around the shapes for antialiasing.
+const QUAD_MARGIN: f32 = 2.0;
+
+struct Crosshair {
+    _position: Vec4,
+    _color: Vec4,
+    _outline_color: Vec4,
+    _shape: Vec4,
+    _flags: Vec4,
+    _hit_marker: Vec4,
+}
+
+/// A struct for descripting the detail of a draw request on a crosshair pipeline.
+///
+/// All the lengths are in physical px.
+#[derive(Clone, Debug)]
+pub struct DrawCrosshairDescriptor {
+    /// The center in UI coordinates.
+    pub position: Vec2,
+    /// Whether the four lines around the center are drawn.
+    pub has_lines: bool,
+    /// Whether the dot at the center is drawn.
+    pub has_dot: bool,
+    /// Whether the circle whose radius is `gap` is drawn.
+    pub has_circle: bool,
+    /// The distance from the center to the lines.
+    pub gap: f32,
+    pub thickness: f32,
+    /// The length of each line.
+    pub length: f32,
+    /// The width of the black outline. 0.0 means no outline.
+    pub outline: f32,
+    /// The linear color whose alpha is the opacity.
+    pub color: Vec4,
+    /// The opacity of the white diagonal lines shown when a target is hit. 0.0 means hidden.
+    pub hit_marker: f32,
+}
+
+/// A pipeline implementaion of crosshair.wgsl.
+///
+/// The crosshair is drawn procedurally on a quad instead of a texture,
+/// so it's sharp at any size.
+pub struct CrosshairPipeline {
+    render_pipeline: RenderPipeline,
+    pipeline_layout: PipelineLayout,
+    color_target_state: ColorTargetState,
+    crosshair_buffer: Buffer,
+    bind_group_0: BindGroup,
+    /// The half size (px) of the window.
+    half_size: Vec2,
+}
+
+/// A function to create the render pipeline with a shader module.
+fn create_render_pipeline(
+    device: &Device,
+    pipeline_layout: &PipelineLayout,
+    shader_module: &ShaderModule,
+    color_target_state: &ColorTargetState,
+) -> RenderPipeline {
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: None,
+        layout: Some(pipeline_layout),
+        vertex: VertexState {
+            module: shader_module,
+            entry_point: "vs_main",
+            compilation_options: Default::default(),
+            buffers: model::VERTEX_BUFFER_LAYOUTS,
+        },
+        fragment: Some(FragmentState {
+            module: shader_module,
+            entry_point: "fs_main",
+            compilation_options: Default::default(),
+            targets: &[Some(ColorTargetState {
+                format: color_target_state.format,
+                blend: Some(BlendState {
+                    color: BlendComponent {
+                        src_factor: BlendFactor::SrcAlpha,
+                        dst_factor: BlendFactor::OneMinusSrcAlpha,
+                        operation: BlendOperation::Add,
+                    },
+                    alpha: BlendComponent {
+                        src_factor: BlendFactor::SrcAlpha,
+                        dst_factor: BlendFactor::OneMinusSrcAlpha,
+                        operation: BlendOperation::Add,
+                    },
+                }),
+                write_mask: color_target_state.write_mask,
+            })],
+        }),
+        primitive: PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+impl CrosshairPipeline {
+    /// A constructor.
+    pub fn new(
+        device: &Device,
+        assets: &AssetManager,
+        color_target_state: ColorTargetState,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, RendererError> {
+        // create a shader module
+        let shader = assets
+            .load_shader(ShaderHandle::Crosshair)
+            .map_err(RendererError::LoadAsset)?;
+        let shader_module = device.create_shader_module(ShaderModuleDescriptor {
+            label: None,
+            source: ShaderSource::Wgsl(Cow::from(shader)),
+        });
+
+        // create a bind group layout, @group(0)
+        let bind_group_0_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX_FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: BufferSize::new(mem::size_of::<Crosshair>() as u64),
+                },
+                count: None,
+            }],
+        });
+
+        // create a pipeline layout
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_0_layout],
+            push_constant_ranges: &[],
+        });
+
+        // create a render pipeline
+        let render_pipeline = create_render_pipeline(
+            device,
+            &pipeline_layout,
+            &shader_module,
+            &color_target_state,
+        );
+
+        // create a crosshair uniform buffer
+        const CROSSHAIR: Crosshair = Crosshair {
+            _position: Vec4::ZERO,
+            _color: Vec4::ZERO,
+            _outline_color: Vec4::ZERO,
+            _shape: Vec4::ZERO,
+            _flags: Vec4::ZERO,
+            _hit_marker: Vec4::ZERO,
+        };
+        let crosshair_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: None,
+            contents: memory::anything_to_u8slice(&CROSSHAIR),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
+        // create a bind group, @group(0)
+        let bind_group_0 = device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            layout: &bind_group_0_layout,
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: crosshair_buffer.as_entire_binding(),
+            }],
+        });
+
+        Ok(Self {
+            render_pipeline,
+            pipeline_layout,
+            color_target_state,
+            crosshair_buffer,
+            bind_group_0,
+            half_size: Vec2::new(width as f32 / 2.0, height as f32 / 2.0),
+        })
+    }
+
+    /// A method to recompile the shader and recreate the render pipeline.
+    ///
+    /// If the shader is invalid, it returns the error and the old render pipeline is kept.
+    pub fn reload_shader(&mut self, device: &Device, shader: &str) -> Result<(), String> {
+        self.render_pipeline = shader::try_compile(device, shader, |n| {
+            create_render_pipeline(device, &self.pipeline_layout, n, &self.color_target_state)
+        })?;
+        Ok(())
+    }
+
+    /// A method to draw a crosshair.
+    ///
+    /// It enqueues a `write_buffer` queue to `queue`.
+    ///
+    /// WARN: The uniform buffer is written before the commands are executed,
+    ///       so only the last crosshair is drawn if it's called several times in a frame.
+    pub fn draw(
+        &self,
+        queue: &Queue,
+        command_encoder: &mut CommandEncoder,
+        render_target_view: &TextureView,
+        square: &Model,
+        descriptor: DrawCrosshairDescriptor,
+        timestamp_writes: Option<RenderPassTimestampWrites>,
+    ) {
+        // update the uniform buffer
+        // NOTE: The quad covers the lines, the circle or the hit marker with the outline.
+        let extent = if descriptor.has_lines || descriptor.hit_marker > 0.0 {
+            descriptor.gap + descriptor.length
+        } else {
+            descriptor.gap
+        }
+        .max(descriptor.gap + descriptor.thickness / 2.0)
+        .max(descriptor.thickness);
+        let outline_alpha = if descriptor.outline > 0.0 {
+            descriptor.color.w
+        } else {
+            0.0
+        };
+        let crosshair = Crosshair {
+            _position: Vec4::new(
+                descriptor.position.x,
+                descriptor.position.y,
+                self.half_size.x,
+                self.half_size.y,
+            ),
+            _color: descriptor.color,
+            _outline_color: Vec4::new(0.0, 0.0, 0.0, outline_alpha),
+            _shape: Vec4::new(
+                descriptor.gap,
+                descriptor.thickness,
+                descriptor.length,
+                descriptor.outline,
+            ),
+            _flags: Vec4::new(
+                descriptor.has_lines as u32 as f32,
+                descriptor.has_dot as u32 as f32,
+                descriptor.has_circle as u32 as f32,
+                extent + descriptor.outline + QUAD_MARGIN,
+            ),
+            _hit_marker: Vec4::new(descriptor.hit_marker, 0.0, 0.0, 0.0),
+        };
+        queue.write_buffer(
+            &self.crosshair_buffer,
+            0,
+            memory::anything_to_u8slice(&crosshair),
+        );
+
+        // draw
+        let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: render_target_view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.bind_group_0, &[]);
+        render_pass.set_vertex_buffer(0, square.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(square.index_buffer.slice(..), square.index_format);
+        render_pass.draw_indexed(0..square.index_count as u32, 0, 0..1);
+    }
+
+    /// A method to resize the window size.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.half_size = Vec2::new(width as f32 / 2.0, height as f32 / 2.0);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::system::{
+    asset::{AssetManager, ShaderHandle},
+    renderer::{error::RendererError, shader},
+};
+use std::{borrow::Cow, collections::HashMap};
+use wgpu::{
+    AddressMode, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, Color, ColorTargetState, ColorWrites,
+    CommandEncoderDescriptor, Device, FilterMode, FragmentState, LoadOp, MultisampleState,
+    Operations, PipelineLayout, PipelineLayoutDescriptor, PrimitiveState, Queue,
+    RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor,
+    Sampler, SamplerBindingType, SamplerDescriptor, ShaderModule, ShaderModuleDescriptor,
+    ShaderSource, ShaderStages, StoreOp, Texture, TextureFormat, TextureSampleType,
+    TextureViewDescriptor, TextureViewDimension, VertexState,
+};
+
+/// A constant for the texture formats whose mip levels can be generated.
+pub const MIPMAP_TEXTURE_FORMATS: &[TextureFormat] = &[TextureFormat::Rgba8UnormSrgb];
+
+/// A function to create the render pipelines of all the formats with a shader module.
+fn create_render_pipelines(
+    device: &Device,
+    pipeline_layout: &PipelineLayout,
+    shader_module: &ShaderModule,
+) -> HashMap<TextureFormat, RenderPipeline> {
+    MIPMAP_TEXTURE_FORMATS
+        .iter()
+        .map(|n| {
+            let render_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+                label: None,
+                layout: Some(pipeline_layout),
+                vertex: VertexState {
+                    module: shader_module,
+                    entry_point: "vs_main",
+                    compilation_options: Default::default(),
+                    buffers: &[],
+                },
+                fragment: Some(FragmentState {
+                    module: shader_module,
+                    entry_point: "fs_main",
+                    compilation_options: Default::default(),
+                    targets: &[Some(ColorTargetState {
+                        format: *n,
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    })],
+                }),
+                primitive: PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+            (*n, render_pipeline)
+        })
+        .collect()
+}
+
+/// A pipeline implementaion of mipmap.wgsl.
+///
+/// It generates the mip levels of a texture on the GPU by downsampling each level into the next one.
+///
+/// NOTE: An sRGB texture is filtered in linear space because the sampling decodes it and the rendering encodes it.
+pub struct MipmapPipeline {
+    render_pipelines: HashMap<TextureFormat, RenderPipeline>,
+    pipeline_layout: PipelineLayout,
+    bind_group_layout: BindGroupLayout,
+    sampler: Sampler,
+}
+
+impl MipmapPipeline {
+    /// A constructor.
+    pub fn new(device: &Device, assets: &AssetManager) -> Result<Self, RendererError> {
+        // create a shader module
+        let shader = assets
+            .load_shader(ShaderHandle::Mipmap)
+            .map_err(RendererError::LoadAsset)?;
+        let shader_module = device.create_shader_module(ShaderModuleDescriptor {
+            label: None,
+            source: ShaderSource::Wgsl(Cow::from(shader)),
+        });
+
+        // create a bind group layout, @group(0)
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        // create a pipeline layout
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        // create render pipelines
+        let render_pipelines = create_render_pipelines(device, &pipeline_layout, &shader_module);
+
+        // create a sampler
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: None,
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Ok(Self {
+            render_pipelines,
+            pipeline_layout,
+            bind_group_layout,
+            sampler,
+        })
+    }
+
+    /// A method to recompile the shader and recreate the render pipelines.
+    ///
+    /// If the shader is invalid, it returns the error and the old render pipelines are kept.
+    ///
+    /// NOTE: The mip levels of the textures created before aren't generated again.
+    pub fn reload_shader(&mut self, device: &Device, shader: &str) -> Result<(), String> {
+        self.render_pipelines = shader::try_compile(device, shader, |n| {
+            create_render_pipelines(device, &self.pipeline_layout, n)
+        })?;
+        Ok(())
+    }
+
+    /// A method to generate the mip levels of a 2D texture from its first level.
+    ///
+    /// WARN: The texture must have a single layer, `RENDER_ATTACHMENT` and `TEXTURE_BINDING` usages,
+    ///       and its format must be one of `MIPMAP_TEXTURE_FORMATS`.
+    ///       A view of a layer can't be sampled as a 2D texture on the GL backend.
+    ///       It submits the commands to `queue`.
+    pub fn generate(&self, device: &Device, queue: &Queue, texture: &Texture) {
+        let Some(render_pipeline) = self.render_pipelines.get(&texture.format()) else {
+            warn!(
+                "MipmapPipeline.generate",
+                "the format {:?} isn't supported.",
+                texture.format()
+            );
+            return;
+        };
+        let create_view = |level| {
+            texture.create_view(&TextureViewDescriptor {
+                base_mip_level: level,
+                mip_level_count: Some(1),
+                ..Default::default()
+            })
+        };
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: None });
+        for level in 1..texture.mip_level_count() {
+            let (source_view, target_view) = (create_view(level - 1), create_view(level));
+            let bind_group = device.create_bind_group(&BindGroupDescriptor {
+                label: None,
+                layout: &self.bind_group_layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::TextureView(&source_view),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::Sampler(&self.sampler),
+                    },
+                ],
+            });
+            let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: &target_view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(Color::TRANSPARENT),
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(render_pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        queue.submit(Some(encoder.finish()));
+    }
+}
@@ -0,0 +1,422 @@
+use crate::{
+    system::{
+        asset::{AssetManager, ShaderHandle},
+        renderer::{
+            error::RendererError,
+            model::{self, Model},
+            shader,
+            texture::depth,
+        },
+    },
+    util::{camera::CameraController, memory},
+};
+use glam::{Mat4, Vec3, Vec4};
+use std::{borrow::Cow, mem};
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingType, BlendComponent, BlendFactor, BlendOperation, BlendState,
+    Buffer, BufferBindingType, BufferSize, BufferUsages, ColorTargetState, CommandEncoder, Device,
+    FragmentState, LoadOp, MultisampleState, Operations, PipelineLayout, PipelineLayoutDescriptor,
+    PrimitiveState, Queue, RenderPassColorAttachment, RenderPassDepthStencilAttachment,
+    RenderPassDescriptor, RenderPassTimestampWrites, RenderPipeline, RenderPipelineDescriptor,
+    ShaderModule, ShaderModuleDescriptor, ShaderSource, ShaderStages, StoreOp, TextureView,
+    VertexAttribute, VertexBufferLayout, VertexFormat, VertexState, VertexStepMode,
+};
+
+/// The max count of particles alive at the same time.
+///
+/// NOTE: The particles are stored in a ring buffer, so the oldest ones are overwritten.
+pub const MAX_PARTICLE_COUNT: u64 = 1024;
+
+struct Camera {
+    _projection_matrix: Mat4,
+    _view_matrix: Mat4,
+}
+struct Clock {
+    _time: Vec4,
+}
+#[derive(Clone)]
+struct Instance {
+    _spawn: Vec4,
+    _velocity: Vec4,
+    _start_color: Vec4,
+    _end_color: Vec4,
+    _size: Vec4,
+}
+
+/// The layout of the instance buffer following `model::VERTEX_BUFFER_LAYOUTS`.
+const INSTANCE_BUFFER_LAYOUT: VertexBufferLayout = VertexBufferLayout {
+    array_stride: mem::size_of::<Instance>() as u64,
+    step_mode: VertexStepMode::Instance,
+    attributes: &[
+        VertexAttribute {
+            format: VertexFormat::Float32x4,
+            offset: 0,
+            shader_location: 3,
+        },
+        VertexAttribute {
+            format: VertexFormat::Float32x4,
+            offset: mem::size_of::<[f32; 4]>() as u64,
+            shader_location: 4,
+        },
+        VertexAttribute {
+            format: VertexFormat::Float32x4,
+            offset: mem::size_of::<[f32; 4]>() as u64 * 2,
+            shader_location: 5,
+        },
+        VertexAttribute {
+            format: VertexFormat::Float32x4,
+            offset: mem::size_of::<[f32; 4]>() as u64 * 3,
+            shader_location: 6,
+        },
+        VertexAttribute {
+            format: VertexFormat::Float32x4,
+            offset: mem::size_of::<[f32; 4]>() as u64 * 4,
+            shader_location: 7,
+        },
+    ],
+};
+
+/// A particle spawned on the world.
+///
+/// It's simulated on the GPU from its initial state, so it can't be changed after spawned.
+/// The times are in frames.
+#[derive(Clone, Debug)]
+pub struct Particle {
+    pub position: Vec3,
+    /// The velocity (units per frame).
+    pub velocity: Vec3,
+    /// The acceleration (units per frame^2) downward.
+    pub gravity: f32,
+    pub lifetime: f32,
+    /// The linear color when spawned. The alpha is the opacity.
+    pub start_color: Vec4,
+    /// The linear color when it dies. The color is interpolated over the lifetime.
+    pub end_color: Vec4,
+    /// The diameter when spawned.
+    pub start_size: f32,
+    /// The diameter when it dies. The size is interpolated over the lifetime.
+    pub end_size: f32,
+}
+
+/// A struct for descripting the detail of a draw request on a particle pipeline.
+pub struct DrawParticlesDescriptor {
+    /// The frames elapsed since the previous draw. 0.0 pauses the particles.
+    pub elapsed: f32,
+}
+
+/// A pipeline implementaion of particle.wgsl.
+///
+/// The particles are billboarded quads blended additively on the world.
+pub struct ParticlePipeline {
+    render_pipeline: RenderPipeline,
+    pipeline_layout: PipelineLayout,
+    color_target_state: ColorTargetState,
+    camera_buffer: Buffer,
+    clock_buffer: Buffer,
+    instance_buffer: Buffer,
+    bind_group_0: BindGroup,
+    /// The current time (frames).
+    time: f32,
+    /// The index of the ring buffer the next particle is written to.
+    next: u64,
+    /// The count of the slots ever written.
+    count: u64,
+}
+
+/// A function to create the render pipeline with a shader module.
+///
+/// NOTE: The particles are tested against the depth of the world but don't write it,
+///       so they don't occlude each other.
+fn create_render_pipeline(
+    device: &Device,
+    pipeline_layout: &PipelineLayout,
+    shader_module: &ShaderModule,
+    color_target_state: &ColorTargetState,
+) -> RenderPipeline {
+    let mut depth_stencil_state = depth::DEPTH_STENCIL_STATE;
+    depth_stencil_state.depth_write_enabled = false;
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: None,
+        layout: Some(pipeline_layout),
+        vertex: VertexState {
+            module: shader_module,
+            entry_point: "vs_main",
+            compilation_options: Default::default(),
+            buffers: &[
+                model::VERTEX_BUFFER_LAYOUTS[0].clone(),
+                INSTANCE_BUFFER_LAYOUT,
+            ],
+        },
+        fragment: Some(FragmentState {
+            module: shader_module,
+            entry_point: "fs_main",
+            compilation_options: Default::default(),
+            targets: &[Some(ColorTargetState {
+                format: color_target_state.format,
+                blend: Some(BlendState {
+                    color: BlendComponent {
+                        src_factor: BlendFactor::SrcAlpha,
+                        dst_factor: BlendFactor::One,
+                        operation: BlendOperation::Add,
+                    },
+                    alpha: BlendComponent {
+                        src_factor: BlendFactor::Zero,
+                        dst_factor: BlendFactor::One,
+                        operation: BlendOperation::Add,
+                    },
+                }),
+                write_mask: color_target_state.write_mask,
+            })],
+        }),
+        primitive: PrimitiveState::default(),
+        depth_stencil: Some(depth_stencil_state),
+        multisample: MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+impl ParticlePipeline {
+    /// A constructor.
+    pub fn new(
+        device: &Device,
+        assets: &AssetManager,
+        color_target_state: ColorTargetState,
+    ) -> Result<Self, RendererError> {
+        // create a shader module
+        let shader = assets
+            .load_shader(ShaderHandle::Particle)
+            .map_err(RendererError::LoadAsset)?;
+        let shader_module = device.create_shader_module(ShaderModuleDescriptor {
+            label: None,
+            source: ShaderSource::Wgsl(Cow::from(shader)),
+        });
+
+        // create a bind group layout, @group(0)
+        let bind_group_0_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::VERTEX,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(mem::size_of::<Camera>() as u64),
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::VERTEX,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(mem::size_of::<Clock>() as u64),
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        // create a pipeline layout
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_0_layout],
+            push_constant_ranges: &[],
+        });
+
+        // create a render pipeline
+        let render_pipeline = create_render_pipeline(
+            device,
+            &pipeline_layout,
+            &shader_module,
+            &color_target_state,
+        );
+
+        // create a camera uniform buffer
+        const CAMERA: Camera = Camera {
+            _projection_matrix: Mat4::IDENTITY,
+            _view_matrix: Mat4::IDENTITY,
+        };
+        let camera_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: None,
+            contents: memory::anything_to_u8slice(&CAMERA),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
+        // create a clock uniform buffer
+        const CLOCK: Clock = Clock { _time: Vec4::ZERO };
+        let clock_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: None,
+            contents: memory::anything_to_u8slice(&CLOCK),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
+        // create an instance vertex buffer
+        // NOTE: The lifetime of the empty slots is 0.0, so they are never drawn.
+        let instances = (0..MAX_PARTICLE_COUNT)
+            .map(|_| Instance {
+                _spawn: Vec4::ZERO,
+                _velocity: Vec4::ZERO,
+                _start_color: Vec4::ZERO,
+                _end_color: Vec4::ZERO,
+                _size: Vec4::ZERO,
+            })
+            .collect::<Vec<Instance>>();
+        let instance_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: None,
+            contents: memory::slice_to_u8slice(instances.as_slice()),
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        });
+
+        // create a bind group, @group(0)
+        let bind_group_0 = device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            layout: &bind_group_0_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: clock_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        Ok(Self {
+            render_pipeline,
+            pipeline_layout,
+            color_target_state,
+            camera_buffer,
+            clock_buffer,
+            instance_buffer,
+            bind_group_0,
+            time: 0.0,
+            next: 0,
+            count: 0,
+        })
+    }
+
+    /// A method to recompile the shader and recreate the render pipeline.
+    ///
+    /// If the shader is invalid, it returns the error and the old render pipeline is kept.
+    pub fn reload_shader(&mut self, device: &Device, shader: &str) -> Result<(), String> {
+        self.render_pipeline = shader::try_compile(device, shader, |n| {
+            create_render_pipeline(device, &self.pipeline_layout, n, &self.color_target_state)
+        })?;
+        Ok(())
+    }
+
+    /// A method to spawn particles at the current time.
+    ///
+    /// It enqueues `write_buffer` queues to `queue`.
+    ///
+    /// WARN: If more than `MAX_PARTICLE_COUNT` particles are alive, the oldest ones disappear.
+    pub fn spawn(&mut self, queue: &Queue, particles: Vec<Particle>) {
+        let instances = particles
+            .iter()
+            .rev()
+            .take(MAX_PARTICLE_COUNT as usize)
+            .rev()
+            .map(|n| Instance {
+                _spawn: n.position.extend(self.time),
+                _velocity: n.velocity.extend(n.lifetime),
+                _start_color: n.start_color,
+                _end_color: n.end_color,
+                _size: Vec4::new(n.start_size, n.end_size, n.gravity, 0.0),
+            })
+            .collect::<Vec<Instance>>();
+
+        // write the instances, wrapping around the end of the ring buffer
+        let mut rest = instances.as_slice();
+        while !rest.is_empty() {
+            let length = rest.len().min((MAX_PARTICLE_COUNT - self.next) as usize);
+            queue.write_buffer(
+                &self.instance_buffer,
+                mem::size_of::<Instance>() as u64 * self.next,
+                memory::slice_to_u8slice(&rest[..length]),
+            );
+            rest = &rest[length..];
+            self.next = (self.next + length as u64) % MAX_PARTICLE_COUNT;
+            self.count = (self.count + length as u64).min(MAX_PARTICLE_COUNT);
+        }
+    }
+
+    /// A method to remove all the particles.
+    ///
+    /// NOTE: The instance buffer isn't written since only the written slots are drawn.
+    pub fn clear(&mut self) {
+        self.next = 0;
+        self.count = 0;
+    }
+
+    /// A method to advance the time and draw the particles.
+    ///
+    /// It enqueues a `write_buffer` queue to `queue`.
+    ///
+    /// WARN: The uniform buffer is written before the commands are executed,
+    ///       so it should be called once in a frame after the world is drawn to `depth_texture_view`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw(
+        &mut self,
+        queue: &Queue,
+        command_encoder: &mut CommandEncoder,
+        render_target_view: &TextureView,
+        depth_texture_view: &TextureView,
+        square: &Model,
+        descriptor: DrawParticlesDescriptor,
+        timestamp_writes: Option<RenderPassTimestampWrites>,
+    ) {
+        // update the clock
+        self.time += descriptor.elapsed;
+        let clock = Clock {
+            _time: Vec4::new(self.time, 0.0, 0.0, 0.0),
+        };
+        queue.write_buffer(&self.clock_buffer, 0, memory::anything_to_u8slice(&clock));
+
+        // draw
+        let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: render_target_view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view: depth_texture_view,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.bind_group_0, &[]);
+        render_pass.set_vertex_buffer(0, square.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        render_pass.set_index_buffer(square.index_buffer.slice(..), square.index_format);
+        render_pass.draw_indexed(0..square.index_count as u32, 0, 0..self.count as u32);
+    }
+
+    /// A method to update the camera uniform buffer.
+    ///
+    /// It enqueues a `write_buffer` queue to `queue`.
+    pub fn update_camera(&self, queue: &Queue, camera_controller: &CameraController) {
+        let camera = Camera {
+            _projection_matrix: camera_controller.get_projection_matrix(),
+            _view_matrix: camera_controller.get_view_matrix(),
+        };
+        queue.write_buffer(&self.camera_buffer, 0, memory::anything_to_u8slice(&camera));
+    }
+}
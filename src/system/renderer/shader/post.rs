@@ -0,0 +1,487 @@
+use crate::{
+    system::{
+        asset::{AssetManager, ShaderHandle},
+        renderer::{
+            error::RendererError,
+            profiler::{GpuPass, GpuProfiler},
+            shader,
+            texture::hdr,
+        },
+    },
+    util::memory,
+};
+use glam::Vec4;
+use std::{borrow::Cow, mem};
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
+    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Buffer,
+    BufferBindingType, BufferSize, BufferUsages, ColorTargetState, CommandEncoder, Device,
+    FilterMode, FragmentState, LoadOp, MultisampleState, Operations, PipelineLayout,
+    PipelineLayoutDescriptor, PrimitiveState, Queue, RenderPassColorAttachment,
+    RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, Sampler, SamplerBindingType,
+    SamplerDescriptor, ShaderModule, ShaderModuleDescriptor, ShaderSource, ShaderStages, StoreOp,
+    TextureSampleType, TextureView, TextureViewDimension, VertexState,
+};
+
+struct Parameter {
+    _values: Vec4,
+}
+
+/// An enum for enumerating post-process effects.
+///
+/// Each effect is applied as a fullscreen pass in the order it's registered.
+#[derive(Clone)]
+pub enum PostProcessEffect {
+    /// Adds blurred bright areas to the image.
+    /// It should be registered before `Tonemap` because it works on HDR colors.
+    Bloom {
+        threshold: f32,
+        intensity: f32,
+        radius: f32,
+    },
+    /// Maps HDR colors into [0, 1] with the ACES filmic curve.
+    Tonemap { exposure: f32 },
+    /// Smooths jagged edges.
+    /// It should be registered after `Tonemap` because it works on LDR luminance.
+    Fxaa,
+    /// Darkens the edges of the screen.
+    Vignette {
+        intensity: f32,
+        inner_radius: f32,
+        outer_radius: f32,
+    },
+    /// Applies a gamma correction.
+    ///
+    /// NOTE: The conversion to sRGB is done by the surface, so `gamma: 1.0` does nothing.
+    Gamma { gamma: f32 },
+}
+
+impl PostProcessEffect {
+    fn entry_point(&self) -> &'static str {
+        match self {
+            Self::Bloom { .. } => "fs_bloom",
+            Self::Tonemap { .. } => "fs_tonemap",
+            Self::Fxaa => "fs_fxaa",
+            Self::Vignette { .. } => "fs_vignette",
+            Self::Gamma { .. } => "fs_gamma",
+        }
+    }
+
+    fn parameter(&self) -> Parameter {
+        let values = match self {
+            Self::Bloom {
+                threshold,
+                intensity,
+                radius,
+            } => Vec4::new(*threshold, *intensity, *radius, 0.0),
+            Self::Tonemap { exposure } => Vec4::new(*exposure, 0.0, 0.0, 0.0),
+            Self::Fxaa => Vec4::ZERO,
+            Self::Vignette {
+                intensity,
+                inner_radius,
+                outer_radius,
+            } => Vec4::new(*intensity, *inner_radius, *outer_radius, 0.0),
+            Self::Gamma { gamma } => Vec4::new(*gamma, 0.0, 0.0, 0.0),
+        };
+        Parameter { _values: values }
+    }
+}
+
+/// A function to get the effects to register.
+///
+/// NOTE: No effect is a valid configuration, then a pass-through pass (`gamma: 1.0`) is registered
+///       since the HDR texture can't be copied to the render target of another format.
+fn get_effects_or_pass_through(effects: &[PostProcessEffect]) -> Vec<PostProcessEffect> {
+    if effects.is_empty() {
+        Vec::from([PostProcessEffect::Gamma { gamma: 1.0 }])
+    } else {
+        effects.to_vec()
+    }
+}
+
+struct PostProcessPass {
+    render_pipeline: RenderPipeline,
+    parameter_buffer: Buffer,
+    bind_group: BindGroup,
+}
+
+fn create_bind_group(
+    device: &Device,
+    bind_group_layout: &BindGroupLayout,
+    input_texture_view: &TextureView,
+    sampler: &Sampler,
+    parameter_buffer: &Buffer,
+) -> BindGroup {
+    device.create_bind_group(&BindGroupDescriptor {
+        label: None,
+        layout: bind_group_layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(input_texture_view),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::Sampler(sampler),
+            },
+            BindGroupEntry {
+                binding: 2,
+                resource: parameter_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+/// A function to create the render pipeline of an effect with a shader module.
+///
+/// * `is_last` - whether the pass writes to the final render target instead of an HDR texture
+fn create_render_pipeline(
+    device: &Device,
+    pipeline_layout: &PipelineLayout,
+    shader_module: &ShaderModule,
+    effect: &PostProcessEffect,
+    color_target_state: &ColorTargetState,
+    is_last: bool,
+) -> RenderPipeline {
+    let format = if is_last {
+        color_target_state.format
+    } else {
+        hdr::HDR_TEXTURE_FORMAT
+    };
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: None,
+        layout: Some(pipeline_layout),
+        vertex: VertexState {
+            module: shader_module,
+            entry_point: "vs_main",
+            compilation_options: Default::default(),
+            buffers: &[],
+        },
+        fragment: Some(FragmentState {
+            module: shader_module,
+            entry_point: effect.entry_point(),
+            compilation_options: Default::default(),
+            targets: &[Some(ColorTargetState {
+                format,
+                blend: None,
+                write_mask: color_target_state.write_mask,
+            })],
+        }),
+        primitive: PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+/// A pipeline implementaion of post.wgsl.
+///
+/// It owns two HDR textures.
+/// The first one is the render target of the scene, and the passes ping-pong between them.
+/// The last pass writes to the render target view passed to `draw()`.
+pub struct PostProcessPipeline {
+    /// The source of the shader the render pipelines are created with.
+    shader: String,
+    bind_group_layout: BindGroupLayout,
+    pipeline_layout: PipelineLayout,
+    color_target_state: ColorTargetState,
+    effects: Vec<PostProcessEffect>,
+    sampler: Sampler,
+    texture_views: [TextureView; 2],
+    passes: Vec<PostProcessPass>,
+}
+
+impl PostProcessPipeline {
+    /// A constructor.
+    ///
+    /// * `color_target_state` - the state of the final render target
+    /// * `effects` - the effects applied in order
+    ///
+    /// NOTE: If `effects` is empty, the scene is written as is by a pass-through pass.
+    pub fn new(
+        device: &Device,
+        assets: &AssetManager,
+        color_target_state: ColorTargetState,
+        width: u32,
+        height: u32,
+        effects: &[PostProcessEffect],
+    ) -> Result<Self, RendererError> {
+        // create a shader module
+        let shader = assets
+            .load_shader(ShaderHandle::Post)
+            .map_err(RendererError::LoadAsset)?;
+        let shader_module = device.create_shader_module(ShaderModuleDescriptor {
+            label: None,
+            source: ShaderSource::Wgsl(Cow::from(shader.as_str())),
+        });
+
+        // create a bind group layout, @group(0)
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(mem::size_of::<Parameter>() as u64),
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        // create a pipeline layout
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        // create a sampler
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: None,
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        // create hdr textures
+        let texture_views = [
+            hdr::create_hdr_texture_view(device, width, height),
+            hdr::create_hdr_texture_view(device, width, height),
+        ];
+
+        // create passes
+        let mut post_process_pipeline = Self {
+            shader,
+            bind_group_layout,
+            pipeline_layout,
+            color_target_state,
+            effects: get_effects_or_pass_through(effects),
+            sampler,
+            texture_views,
+            passes: Vec::new(),
+        };
+        post_process_pipeline.passes = post_process_pipeline.create_passes(
+            device,
+            &shader_module,
+            &post_process_pipeline.effects,
+        );
+
+        Ok(post_process_pipeline)
+    }
+
+    /// A method to create the passes of effects with a shader module.
+    fn create_passes(
+        &self,
+        device: &Device,
+        shader_module: &ShaderModule,
+        effects: &[PostProcessEffect],
+    ) -> Vec<PostProcessPass> {
+        let last = effects.len() - 1;
+        effects
+            .iter()
+            .enumerate()
+            .map(|(i, n)| {
+                let render_pipeline = create_render_pipeline(
+                    device,
+                    &self.pipeline_layout,
+                    shader_module,
+                    n,
+                    &self.color_target_state,
+                    i == last,
+                );
+                let parameter_buffer = device.create_buffer_init(&BufferInitDescriptor {
+                    label: None,
+                    contents: memory::anything_to_u8slice(&n.parameter()),
+                    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                });
+                let bind_group = create_bind_group(
+                    device,
+                    &self.bind_group_layout,
+                    &self.texture_views[i % 2],
+                    &self.sampler,
+                    &parameter_buffer,
+                );
+                PostProcessPass {
+                    render_pipeline,
+                    parameter_buffer,
+                    bind_group,
+                }
+            })
+            .collect()
+    }
+
+    /// A method to replace the effects.
+    ///
+    /// If only the parameters differ, the parameter buffers are rewritten instead of recreating the passes.
+    /// If the passes can't be created, it returns the error and the old effects are kept.
+    ///
+    /// NOTE: If `effects` is empty, the scene is written as is by a pass-through pass.
+    pub fn set_effects(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        effects: &[PostProcessEffect],
+    ) -> Result<(), String> {
+        let effects = get_effects_or_pass_through(effects);
+        let is_same_kind = effects.len() == self.effects.len()
+            && effects
+                .iter()
+                .zip(&self.effects)
+                .all(|(n, m)| n.entry_point() == m.entry_point());
+        if is_same_kind {
+            for (i, n) in effects.into_iter().enumerate() {
+                self.set_effect_param(queue, i, n)?;
+            }
+            return Ok(());
+        }
+        self.passes = shader::try_compile(device, &self.shader, |n| {
+            self.create_passes(device, n, &effects)
+        })?;
+        self.effects = effects;
+        Ok(())
+    }
+
+    /// A method to change the parameters of a registered effect.
+    ///
+    /// * `index` - the index of the effect in the order it's applied
+    /// * `effect` - the effect of the same kind as the registered one
+    pub fn set_effect_param(
+        &mut self,
+        queue: &Queue,
+        index: usize,
+        effect: PostProcessEffect,
+    ) -> Result<(), String> {
+        let Some(current) = self.effects.get(index) else {
+            return Err(format!("no effect is registered at {}", index));
+        };
+        if current.entry_point() != effect.entry_point() {
+            return Err(format!(
+                "the effect at {} can't be changed to another kind",
+                index
+            ));
+        }
+        queue.write_buffer(
+            &self.passes[index].parameter_buffer,
+            0,
+            memory::anything_to_u8slice(&effect.parameter()),
+        );
+        self.effects[index] = effect;
+        Ok(())
+    }
+
+    /// A method to recompile the shader and recreate the render pipelines of all the effects.
+    ///
+    /// If the shader is invalid, it returns the error and the old render pipelines are kept.
+    pub fn reload_shader(&mut self, device: &Device, shader: &str) -> Result<(), String> {
+        let last = self.effects.len() - 1;
+        let render_pipelines = shader::try_compile(device, shader, |n| {
+            self.effects
+                .iter()
+                .enumerate()
+                .map(|(i, effect)| {
+                    create_render_pipeline(
+                        device,
+                        &self.pipeline_layout,
+                        n,
+                        effect,
+                        &self.color_target_state,
+                        i == last,
+                    )
+                })
+                .collect::<Vec<RenderPipeline>>()
+        })?;
+        for (pass, render_pipeline) in self.passes.iter_mut().zip(render_pipelines) {
+            pass.render_pipeline = render_pipeline;
+        }
+        self.shader = shader.to_string();
+        Ok(())
+    }
+
+    /// A method to get the HDR texture view the scene should be rendered to.
+    pub fn get_render_target_view(&self) -> &TextureView {
+        &self.texture_views[0]
+    }
+
+    /// A method to apply all the effects and write the result to `render_target_view`.
+    ///
+    /// * `profiler` - the profiler each pass is timestamped with as `GpuPass::Post`
+    ///
+    /// WARN: It overwrites the whole `render_target_view`.
+    pub fn draw(
+        &self,
+        command_encoder: &mut CommandEncoder,
+        render_target_view: &TextureView,
+        mut profiler: Option<&mut GpuProfiler>,
+    ) {
+        let last = self.passes.len() - 1;
+        for (i, n) in self.passes.iter().enumerate() {
+            let view = if i == last {
+                render_target_view
+            } else {
+                &self.texture_views[(i + 1) % 2]
+            };
+            let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Load,
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: profiler
+                    .as_deref_mut()
+                    .and_then(|n| n.get_timestamp_writes(GpuPass::Post)),
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&n.render_pipeline);
+            render_pass.set_bind_group(0, &n.bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+
+    /// A method to recreate and resize the HDR textures.
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+        self.texture_views = [
+            hdr::create_hdr_texture_view(device, width, height),
+            hdr::create_hdr_texture_view(device, width, height),
+        ];
+        for (i, n) in self.passes.iter_mut().enumerate() {
+            n.bind_group = create_bind_group(
+                device,
+                &self.bind_group_layout,
+                &self.texture_views[i % 2],
+                &self.sampler,
+                &n.parameter_buffer,
+            );
+        }
+    }
+}
@@ -1,55 +1,242 @@
 use crate::{
-    system::renderer::{
-        model::{self, Model},
-        texture::{depth, image},
+    system::{
+        asset::{AssetHandle, AssetManager, ShaderHandle, TextureHandle},
+        renderer::{
+            error::RendererError,
+            model::{self, Model},
+            shader,
+            texture::{
+                cubemap::{self, Bitmap, Projection},
+                depth,
+                image::{self, SamplerConfig},
+            },
+        },
     },
     util::{camera::CameraController, memory},
 };
 use glam::{Mat4, Vec3};
-use std::{borrow::Cow, mem, process};
+use std::{
+    borrow::Cow,
+    fmt::{self, Display, Formatter},
+    mem,
+};
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
-    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
-    BindGroupLayoutEntry, BindingResource, BindingType, Buffer, BufferBindingType, BufferSize,
-    BufferUsages, Color, ColorTargetState, CommandEncoder, Device, FragmentState, IndexFormat,
-    LoadOp, MultisampleState, Operations, PipelineLayoutDescriptor, PrimitiveState, Queue,
-    RenderPassColorAttachment, RenderPassDepthStencilAttachment, RenderPassDescriptor,
-    RenderPipeline, RenderPipelineDescriptor, SamplerBindingType, ShaderModuleDescriptor,
-    ShaderSource, ShaderStages, StoreOp, TextureSampleType, TextureView, TextureViewDimension,
-    VertexState,
+    AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
+    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Buffer,
+    BufferBindingType, BufferSize, BufferUsages, Color, ColorTargetState, CommandEncoder, Device,
+    FragmentState, LoadOp, MultisampleState, Operations, PipelineLayout, PipelineLayoutDescriptor,
+    PrimitiveState, Queue, RenderPassColorAttachment, RenderPassDepthStencilAttachment,
+    RenderPassDescriptor, RenderPassTimestampWrites, RenderPipeline, RenderPipelineDescriptor,
+    Sampler, SamplerBindingType, ShaderModule, ShaderModuleDescriptor, ShaderSource, ShaderStages,
+    StoreOp, TextureSampleType, TextureView, TextureViewDimension, VertexState,
 };
 
-const SHADER: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/shader/skybox.wgsl"));
-const IMAGE_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/image/skybox.png");
-
 struct Camera {
     _projection_matrix: Mat4,
     _view_matrix: Mat4,
 }
 
-/// A pipeline implementaion of world.wgsl.
+/// An enum for the sources of the cubemap of a skybox.
+///
+/// The images are PNG or Radiance HDR files, which are distinguished by the extension.
+#[derive(Clone, PartialEq, Debug)]
+pub enum SkyboxSource {
+    /// The asset paths of the six faces in the order of +x, -x, +y, -y, +z and -z.
+    Cube(Vec<String>),
+    /// The asset path of an equirectangular image.
+    Equirect(String),
+    /// The asset path of an image with the northern hemisphere on the left half and the southern one on the right half.
+    Hemispheres(String),
+}
+
+impl Default for SkyboxSource {
+    /// The bundled skybox, `image/skybox.png`.
+    fn default() -> Self {
+        Self::Hemispheres(TextureHandle::Skybox.get_path().to_string())
+    }
+}
+
+impl SkyboxSource {
+    /// A static method to parse a source written as a kind and asset paths such as `equirect image/sky.hdr`.
+    pub fn parse(values: &[&str]) -> Result<Self, String> {
+        let Some((kind, paths)) = values.split_first() else {
+            return Err("no kind is specified for skybox".to_string());
+        };
+        let paths = paths.iter().map(|n| n.to_string()).collect::<Vec<String>>();
+        let count = if *kind == "cube" {
+            cubemap::FACE_COUNT
+        } else {
+            1
+        };
+        if paths.len() != count {
+            return Err(format!(
+                "{} paths are expected for {} but {} found",
+                count,
+                kind,
+                paths.len()
+            ));
+        }
+        match *kind {
+            "cube" => Ok(Self::Cube(paths)),
+            "equirect" => Ok(Self::Equirect(paths[0].clone())),
+            "hemispheres" => Ok(Self::Hemispheres(paths[0].clone())),
+            _ => Err(format!("unknown skybox '{}'", kind)),
+        }
+    }
+
+    /// A method to get the asset paths of the images.
+    pub fn get_paths(&self) -> Vec<&str> {
+        match self {
+            Self::Cube(paths) => paths.iter().map(|n| n.as_str()).collect(),
+            Self::Equirect(path) | Self::Hemispheres(path) => Vec::from([path.as_str()]),
+        }
+    }
+
+    /// A method to load the images and create the faces of the cubemap.
+    fn load_faces(
+        &self,
+        device: &Device,
+        assets: &AssetManager,
+    ) -> Result<Vec<Bitmap>, RendererError> {
+        let max_size = device.limits().max_texture_dimension_2d;
+        let load = |path: &str| {
+            let data = assets.read(path).map_err(RendererError::LoadAsset)?;
+            let bitmap = if path.to_ascii_lowercase().ends_with(".hdr") {
+                cubemap::decode_hdr(&data)
+            } else {
+                cubemap::decode_png(&data)
+            };
+            bitmap.map_err(|e| RendererError::LoadImage(path.to_string(), e))
+        };
+        match self {
+            Self::Cube(paths) => {
+                let faces = paths
+                    .iter()
+                    .map(|n| load(n))
+                    .collect::<Result<Vec<Bitmap>, RendererError>>()?;
+                cubemap::check_faces(&faces, max_size)
+                    .map_err(|e| RendererError::LoadImage(self.to_string(), e.into()))?;
+                Ok(faces)
+            }
+            Self::Equirect(path) => Ok(cubemap::create_faces(
+                &load(path)?,
+                Projection::Equirect,
+                max_size,
+            )),
+            Self::Hemispheres(path) => Ok(cubemap::create_faces(
+                &load(path)?,
+                Projection::Hemispheres,
+                max_size,
+            )),
+        }
+    }
+}
+
+impl Display for SkyboxSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cube(paths) => write!(f, "cube {}", paths.join(" ")),
+            Self::Equirect(path) => write!(f, "equirect {}", path),
+            Self::Hemispheres(path) => write!(f, "hemispheres {}", path),
+        }
+    }
+}
+
+/// A pipeline implementaion of skybox.wgsl.
+///
+/// The skybox is a cubemap sampled by the direction from the camera.
 pub struct SkyboxPipeline {
     render_pipeline: RenderPipeline,
+    pipeline_layout: PipelineLayout,
+    color_target_state: ColorTargetState,
     depth_texture_view: TextureView,
     camera_buffer: Buffer,
+    bind_group_0_layout: BindGroupLayout,
     bind_group_0: BindGroup,
+    sampler: Sampler,
+    /// The source of the cubemap drawn now.
+    source: SkyboxSource,
+}
+
+fn create_bind_group_0(
+    device: &Device,
+    bind_group_0_layout: &BindGroupLayout,
+    camera_buffer: &Buffer,
+    cubemap_texture_view: &TextureView,
+    sampler: &Sampler,
+) -> BindGroup {
+    device.create_bind_group(&BindGroupDescriptor {
+        label: None,
+        layout: bind_group_0_layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::TextureView(cubemap_texture_view),
+            },
+            BindGroupEntry {
+                binding: 2,
+                resource: BindingResource::Sampler(sampler),
+            },
+        ],
+    })
+}
+
+/// A function to create the render pipeline with a shader module.
+fn create_render_pipeline(
+    device: &Device,
+    pipeline_layout: &PipelineLayout,
+    shader_module: &ShaderModule,
+    color_target_state: &ColorTargetState,
+) -> RenderPipeline {
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: None,
+        layout: Some(pipeline_layout),
+        vertex: VertexState {
+            module: shader_module,
+            entry_point: "vs_main",
+            compilation_options: Default::default(),
+            buffers: model::VERTEX_BUFFER_LAYOUTS,
+        },
+        fragment: Some(FragmentState {
+            module: shader_module,
+            entry_point: "fs_main",
+            compilation_options: Default::default(),
+            targets: &[Some(color_target_state.clone())],
+        }),
+        primitive: PrimitiveState::default(),
+        depth_stencil: Some(depth::DEPTH_STENCIL_STATE),
+        multisample: MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
 }
 
 impl SkyboxPipeline {
     /// A constructor.
     ///
-    /// NOTE: It needs a queue to create an image texture.
+    /// NOTE: It needs a queue to create a cubemap texture.
+    ///
+    /// WARN: It fails if the default skybox can't be loaded.
     pub fn new(
         device: &Device,
         queue: &Queue,
+        assets: &AssetManager,
         color_target_state: ColorTargetState,
         width: u32,
         height: u32,
-    ) -> Self {
+    ) -> Result<Self, RendererError> {
         // create a shader module
+        let shader = assets
+            .load_shader(ShaderHandle::Skybox)
+            .map_err(RendererError::LoadAsset)?;
         let shader_module = device.create_shader_module(ShaderModuleDescriptor {
             label: None,
-            source: ShaderSource::Wgsl(Cow::from(SHADER)),
+            source: ShaderSource::Wgsl(Cow::from(shader)),
         });
 
         // create a bind group layout, @group(0)
@@ -71,7 +258,7 @@ impl SkyboxPipeline {
                     visibility: ShaderStages::FRAGMENT,
                     ty: BindingType::Texture {
                         sample_type: TextureSampleType::Float { filterable: true },
-                        view_dimension: TextureViewDimension::D2,
+                        view_dimension: TextureViewDimension::Cube,
                         multisampled: false,
                     },
                     count: None,
@@ -93,27 +280,12 @@ impl SkyboxPipeline {
         });
 
         // create a render pipeline
-        let render_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
-            label: None,
-            layout: Some(&pipeline_layout),
-            vertex: VertexState {
-                module: &shader_module,
-                entry_point: "vs_main",
-                compilation_options: Default::default(),
-                buffers: model::VERTEX_BUFFER_LAYOUTS,
-            },
-            fragment: Some(FragmentState {
-                module: &shader_module,
-                entry_point: "fs_main",
-                compilation_options: Default::default(),
-                targets: &[Some(color_target_state)],
-            }),
-            primitive: PrimitiveState::default(),
-            depth_stencil: Some(depth::DEPTH_STENCIL_STATE),
-            multisample: MultisampleState::default(),
-            multiview: None,
-            cache: None,
-        });
+        let render_pipeline = create_render_pipeline(
+            device,
+            &pipeline_layout,
+            &shader_module,
+            &color_target_state,
+        );
 
         // create a depth texture view
         let depth_texture_view = depth::create_depth_texture_view(device, width, height);
@@ -129,57 +301,107 @@ impl SkyboxPipeline {
             usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
         });
 
-        // create a skybox image texture and a sampler
-        let image_texture_view = match image::create_image_texture_view(device, queue, IMAGE_PATH) {
-            Ok(n) => n,
-            Err(e) => {
-                error!(
-                    "SkyboxPipeline.new",
-                    "failed to create an image texture: {}: {}",
-                    IMAGE_PATH,
-                    e.to_string()
-                );
-                process::exit(1);
-            }
-        };
-        let sampler = image::create_sampler(device);
+        // create a cubemap texture and a sampler
+        let source = SkyboxSource::default();
+        let cubemap_texture_view = cubemap::create_cubemap_texture_view(
+            device,
+            queue,
+            &source.load_faces(device, assets)?,
+        );
+        let sampler = image::create_sampler(
+            device,
+            &SamplerConfig {
+                address_mode_u: AddressMode::ClampToEdge,
+                address_mode_v: AddressMode::ClampToEdge,
+                ..Default::default()
+            },
+        );
 
         // create a bind group, @group(0)
-        let bind_group_0 = device.create_bind_group(&BindGroupDescriptor {
-            label: None,
-            layout: &bind_group_0_layout,
-            entries: &[
-                BindGroupEntry {
-                    binding: 0,
-                    resource: camera_buffer.as_entire_binding(),
-                },
-                BindGroupEntry {
-                    binding: 1,
-                    resource: BindingResource::TextureView(&image_texture_view),
-                },
-                BindGroupEntry {
-                    binding: 2,
-                    resource: BindingResource::Sampler(&sampler),
-                },
-            ],
-        });
+        let bind_group_0 = create_bind_group_0(
+            device,
+            &bind_group_0_layout,
+            &camera_buffer,
+            &cubemap_texture_view,
+            &sampler,
+        );
 
-        Self {
+        Ok(Self {
             render_pipeline,
+            pipeline_layout,
+            color_target_state,
             depth_texture_view,
             camera_buffer,
+            bind_group_0_layout,
             bind_group_0,
-        }
+            sampler,
+            source,
+        })
+    }
+
+    /// A method to get the source of the cubemap drawn now.
+    pub fn get_source(&self) -> &SkyboxSource {
+        &self.source
+    }
+
+    /// A method to replace the cubemap with the one loaded from a source.
+    ///
+    /// If the images can't be loaded, it returns the error and the old cubemap is kept.
+    pub fn load_texture(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        assets: &AssetManager,
+        source: &SkyboxSource,
+    ) -> Result<(), RendererError> {
+        let cubemap_texture_view = cubemap::create_cubemap_texture_view(
+            device,
+            queue,
+            &source.load_faces(device, assets)?,
+        );
+        self.bind_group_0 = create_bind_group_0(
+            device,
+            &self.bind_group_0_layout,
+            &self.camera_buffer,
+            &cubemap_texture_view,
+            &self.sampler,
+        );
+        self.source = source.clone();
+        Ok(())
+    }
+
+    /// A method to load the cubemap again from the current source.
+    ///
+    /// If the images can't be loaded, it returns the error and the old cubemap is kept.
+    pub fn reload_texture(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        assets: &AssetManager,
+    ) -> Result<(), RendererError> {
+        let source = self.source.clone();
+        self.load_texture(device, queue, assets, &source)
+    }
+
+    /// A method to recompile the shader and recreate the render pipeline.
+    ///
+    /// If the shader is invalid, it returns the error and the old render pipeline is kept.
+    pub fn reload_shader(&mut self, device: &Device, shader: &str) -> Result<(), String> {
+        self.render_pipeline = shader::try_compile(device, shader, |n| {
+            create_render_pipeline(device, &self.pipeline_layout, n, &self.color_target_state)
+        })?;
+        Ok(())
     }
 
     /// A method to draw a skybox.
     ///
     /// WARN: It clears render target texture with black.
-    pub fn draw<'a>(
+    pub fn draw(
         &self,
-        command_encoder: &'a mut CommandEncoder,
+        command_encoder: &mut CommandEncoder,
         render_target_view: &TextureView,
         sphere: &Model,
+        timestamp_writes: Option<RenderPassTimestampWrites>,
     ) {
         let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
             label: None,
@@ -204,13 +426,13 @@ impl SkyboxPipeline {
                 }),
                 stencil_ops: None,
             }),
-            timestamp_writes: None,
+            timestamp_writes,
             occlusion_query_set: None,
         });
         render_pass.set_pipeline(&self.render_pipeline);
         render_pass.set_bind_group(0, &self.bind_group_0, &[]);
         render_pass.set_vertex_buffer(0, sphere.vertex_buffer.slice(..));
-        render_pass.set_index_buffer(sphere.index_buffer.slice(..), IndexFormat::Uint16);
+        render_pass.set_index_buffer(sphere.index_buffer.slice(..), sphere.index_format);
         render_pass.draw_indexed(0..sphere.index_count as u32, 0, 0..1);
     }
 
@@ -243,3 +465,24 @@ impl SkyboxPipeline {
         self.depth_texture_view = depth::create_depth_texture_view(device, width, height);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sources_are_formatted_as_parsed() {
+        for text in [
+            "cube px.png nx.png py.png ny.png pz.png nz.png",
+            "equirect image/sky.hdr",
+            "hemispheres image/skybox.png",
+        ] {
+            let values = text.split_whitespace().collect::<Vec<&str>>();
+            assert_eq!(SkyboxSource::parse(&values).unwrap().to_string(), text);
+        }
+        for text in ["", "cube a b c", "equirect", "equirect a b", "sphere a"] {
+            let values = text.split_whitespace().collect::<Vec<&str>>();
+            assert!(SkyboxSource::parse(&values).is_err(), "{}", text);
+        }
+    }
+}
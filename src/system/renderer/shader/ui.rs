@@ -1,27 +1,30 @@
 use crate::{
-    system::renderer::{
-        model::{self, Model},
-        texture::image,
+    system::{
+        asset::{AssetHandle, AssetManager, ShaderHandle, TextureHandle},
+        renderer::{
+            error::RendererError,
+            model::{self, Model},
+            shader,
+            texture::image::{self, SamplerConfig},
+        },
     },
     util::{instance::InstanceController, memory, vector},
 };
 use glam::{Mat4, Vec4};
-use std::{borrow::Cow, cmp, mem, process};
+use std::{borrow::Cow, cmp, collections::HashMap, error::Error, mem};
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
-    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
-    BindGroupLayoutEntry, BindingResource, BindingType, BlendComponent, BlendFactor,
-    BlendOperation, BlendState, Buffer, BufferBindingType, BufferSize, BufferUsages, Color,
-    ColorTargetState, CommandEncoder, Device, FragmentState, IndexFormat, LoadOp, MultisampleState,
-    Operations, PipelineLayoutDescriptor, PrimitiveState, Queue, RenderPassColorAttachment,
-    RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, SamplerBindingType,
-    ShaderModuleDescriptor, ShaderSource, ShaderStages, StoreOp, TextureSampleType, TextureView,
-    TextureViewDimension, VertexState,
+    AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
+    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, BlendComponent,
+    BlendFactor, BlendOperation, BlendState, Buffer, BufferBindingType, BufferSize, BufferUsages,
+    Color, ColorTargetState, CommandEncoder, Device, FilterMode, FragmentState, LoadOp,
+    MultisampleState, Operations, PipelineLayout, PipelineLayoutDescriptor, PrimitiveState, Queue,
+    RenderPassColorAttachment, RenderPassDescriptor, RenderPassTimestampWrites, RenderPipeline,
+    RenderPipelineDescriptor, Sampler, SamplerBindingType, ShaderModule, ShaderModuleDescriptor,
+    ShaderSource, ShaderStages, StoreOp, TextureSampleType, TextureView, TextureViewDimension,
+    VertexState,
 };
 
-const SHADER: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/shader/ui.wgsl"));
-const IMAGE_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/image/ui.png");
-
 struct Camera {
     _projection_matrix: Mat4,
 }
@@ -29,42 +32,136 @@ struct Camera {
 struct Instance {
     _model_matrix: Mat4,
     _tex_coord: Vec4,
+    _color: Vec4,
+}
+/// The max count of instances on the UI.
+///
+/// NOTE: It must be the same as the length of `instances` in ui.wgsl.
+/// WARN: The instances must fit in the 64 KiB uniform buffer binding limit of WebGPU.
+pub const MAX_INSTANCE_COUNT: u64 = 640;
+const _: () = assert!(mem::size_of::<Instance>() as u64 * MAX_INSTANCE_COUNT <= 65536);
+
+/// An enum for enumerating the textures of the UI.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UiTextureId {
+    /// `/image/ui.png` with the pre-baked messages and digits.
+    Ui,
+    /// `/image/font.png` with the glyphs of the text renderer.
+    Font,
 }
-const MAX_INSTANCE_COUNT: u64 = 16;
 
 /// A struct for descripting the detail of a draw request on an ui pipeline.
 pub struct DrawUiDescriptor {
     /// Specify the color that the pipeline clears the render target view with.
     /// If it's `None`, the pipeline doesn't clear the render target view.
     pub clear_color: Option<[f64; 3]>,
-    /// Specify the start and end index of instances.
-    /// To reduce draw calls, you should group the same models together whenever possible.
-    pub instance_indices: Vec<(u32, u32)>,
+    /// Specify the texture id to attach and the start and end index of instances.
+    /// To reduce draw calls, you should group the same textures together whenever possible.
+    pub instance_indices: Vec<(UiTextureId, u32, u32)>,
 }
 
 /// A pipeline implementaion of ui.wgsl.
 pub struct UiPipeline {
     render_pipeline: RenderPipeline,
+    pipeline_layout: PipelineLayout,
+    color_target_state: ColorTargetState,
     camera_buffer: Buffer,
     instance_buffer: Buffer,
     bind_group_0: BindGroup,
+    /// The bind groups of @group(1) for each texture.
+    texture_bind_groups: HashMap<UiTextureId, BindGroup>,
+    bind_group_1_layout: BindGroupLayout,
+    sampler: Sampler,
+}
+
+fn create_texture_bind_group(
+    device: &Device,
+    bind_group_1_layout: &BindGroupLayout,
+    image_texture_view: &TextureView,
+    sampler: &Sampler,
+) -> BindGroup {
+    device.create_bind_group(&BindGroupDescriptor {
+        label: None,
+        layout: bind_group_1_layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(image_texture_view),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::Sampler(sampler),
+            },
+        ],
+    })
+}
+
+/// A function to create the render pipeline with a shader module.
+fn create_render_pipeline(
+    device: &Device,
+    pipeline_layout: &PipelineLayout,
+    shader_module: &ShaderModule,
+    color_target_state: &ColorTargetState,
+) -> RenderPipeline {
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: None,
+        layout: Some(pipeline_layout),
+        vertex: VertexState {
+            module: shader_module,
+            entry_point: "vs_main",
+            compilation_options: Default::default(),
+            buffers: model::VERTEX_BUFFER_LAYOUTS,
+        },
+        fragment: Some(FragmentState {
+            module: shader_module,
+            entry_point: "fs_main",
+            compilation_options: Default::default(),
+            targets: &[Some(ColorTargetState {
+                format: color_target_state.format,
+                blend: Some(BlendState {
+                    color: BlendComponent {
+                        src_factor: BlendFactor::SrcAlpha,
+                        dst_factor: BlendFactor::OneMinusSrcAlpha,
+                        operation: BlendOperation::Add,
+                    },
+                    alpha: BlendComponent {
+                        src_factor: BlendFactor::SrcAlpha,
+                        dst_factor: BlendFactor::OneMinusSrcAlpha,
+                        operation: BlendOperation::Add,
+                    },
+                }),
+                write_mask: color_target_state.write_mask,
+            })],
+        }),
+        primitive: PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
 }
 
 impl UiPipeline {
     /// A constructor.
     ///
     /// NOTE: It needs a queue to create an image texture.
+    ///
+    /// WARN: It fails if the image file can't be loaded.
     pub fn new(
         device: &Device,
         queue: &Queue,
+        assets: &AssetManager,
         color_target_state: ColorTargetState,
         width: u32,
         height: u32,
-    ) -> Self {
+    ) -> Result<Self, RendererError> {
         // create a shader module
+        let shader = assets
+            .load_shader(ShaderHandle::Ui)
+            .map_err(RendererError::LoadAsset)?;
         let shader_module = device.create_shader_module(ShaderModuleDescriptor {
             label: None,
-            source: ShaderSource::Wgsl(Cow::from(SHADER)),
+            source: ShaderSource::Wgsl(Cow::from(shader)),
         });
 
         // create a bind group layout, @group(0)
@@ -93,8 +190,15 @@ impl UiPipeline {
                     },
                     count: None,
                 },
+            ],
+        });
+
+        // create a bind group layout, @group(1)
+        let bind_group_1_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
                 BindGroupLayoutEntry {
-                    binding: 2,
+                    binding: 0,
                     visibility: ShaderStages::FRAGMENT,
                     ty: BindingType::Texture {
                         sample_type: TextureSampleType::Float { filterable: true },
@@ -104,7 +208,7 @@ impl UiPipeline {
                     count: None,
                 },
                 BindGroupLayoutEntry {
-                    binding: 3,
+                    binding: 1,
                     visibility: ShaderStages::FRAGMENT,
                     ty: BindingType::Sampler(SamplerBindingType::Filtering),
                     count: None,
@@ -115,47 +219,17 @@ impl UiPipeline {
         // create a pipeline layout
         let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
             label: None,
-            bind_group_layouts: &[&bind_group_0_layout],
+            bind_group_layouts: &[&bind_group_0_layout, &bind_group_1_layout],
             push_constant_ranges: &[],
         });
 
         // create a render pipeline
-        let render_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
-            label: None,
-            layout: Some(&pipeline_layout),
-            vertex: VertexState {
-                module: &shader_module,
-                entry_point: "vs_main",
-                compilation_options: Default::default(),
-                buffers: model::VERTEX_BUFFER_LAYOUTS,
-            },
-            fragment: Some(FragmentState {
-                module: &shader_module,
-                entry_point: "fs_main",
-                compilation_options: Default::default(),
-                targets: &[Some(ColorTargetState {
-                    format: color_target_state.format,
-                    blend: Some(BlendState {
-                        color: BlendComponent {
-                            src_factor: BlendFactor::SrcAlpha,
-                            dst_factor: BlendFactor::OneMinusSrcAlpha,
-                            operation: BlendOperation::Add,
-                        },
-                        alpha: BlendComponent {
-                            src_factor: BlendFactor::SrcAlpha,
-                            dst_factor: BlendFactor::OneMinusSrcAlpha,
-                            operation: BlendOperation::Add,
-                        },
-                    }),
-                    write_mask: color_target_state.write_mask,
-                })],
-            }),
-            primitive: PrimitiveState::default(),
-            depth_stencil: None,
-            multisample: MultisampleState::default(),
-            multiview: None,
-            cache: None,
-        });
+        let render_pipeline = create_render_pipeline(
+            device,
+            &pipeline_layout,
+            &shader_module,
+            &color_target_state,
+        );
 
         // create a camera uniform buffer
         let half_width = width as f32 / 2.0;
@@ -178,10 +252,10 @@ impl UiPipeline {
 
         // create a instance uniform buffer
         let instances = (0..MAX_INSTANCE_COUNT)
-            .into_iter()
             .map(|_| Instance {
                 _model_matrix: Mat4::IDENTITY,
                 _tex_coord: Vec4::new(0.0, 0.0, 1.0, 1.0),
+                _color: Vec4::new(1.0, 1.0, 1.0, 1.0),
             })
             .collect::<Vec<Instance>>();
         let instance_buffer = device.create_buffer_init(&BufferInitDescriptor {
@@ -190,21 +264,6 @@ impl UiPipeline {
             usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
         });
 
-        // create a skybox image texture and a sampler
-        let image_texture_view = match image::create_image_texture_view(device, queue, IMAGE_PATH) {
-            Ok(n) => n,
-            Err(e) => {
-                error!(
-                    "UiPipeline.new",
-                    "failed to create an image texture: {}: {}",
-                    IMAGE_PATH,
-                    e.to_string()
-                );
-                process::exit(1);
-            }
-        };
-        let sampler = image::create_sampler(device);
-
         // create a bind group, @group(0)
         let bind_group_0 = device.create_bind_group(&BindGroupDescriptor {
             label: None,
@@ -218,23 +277,83 @@ impl UiPipeline {
                     binding: 1,
                     resource: instance_buffer.as_entire_binding(),
                 },
-                BindGroupEntry {
-                    binding: 2,
-                    resource: BindingResource::TextureView(&image_texture_view),
-                },
-                BindGroupEntry {
-                    binding: 3,
-                    resource: BindingResource::Sampler(&sampler),
-                },
             ],
         });
 
-        Self {
+        // create image textures, a sampler and their bind groups, @group(1)
+        // NOTE: The UI is drawn at about the pixel size of the textures,
+        //       so the mip levels and the anisotropy are useless and would blur the glyphs.
+        let sampler = image::create_sampler(
+            device,
+            &SamplerConfig {
+                mipmap_filter: FilterMode::Nearest,
+                anisotropy: 1,
+                address_mode_u: AddressMode::ClampToEdge,
+                address_mode_v: AddressMode::ClampToEdge,
+                ..Default::default()
+            },
+        );
+        let mut texture_bind_groups = HashMap::new();
+        for (id, handle) in [
+            (UiTextureId::Ui, TextureHandle::Ui),
+            (UiTextureId::Font, TextureHandle::Font),
+        ] {
+            let image = assets
+                .load_texture(handle)
+                .map_err(RendererError::LoadAsset)?;
+            let image_texture_view = image::create_image_texture_view(device, queue, None, &image)
+                .map_err(|e| RendererError::LoadImage(handle.get_path().to_string(), e))?;
+            let bind_group = create_texture_bind_group(
+                device,
+                &bind_group_1_layout,
+                &image_texture_view,
+                &sampler,
+            );
+            texture_bind_groups.insert(id, bind_group);
+        }
+
+        Ok(Self {
             render_pipeline,
+            pipeline_layout,
+            color_target_state,
             camera_buffer,
             instance_buffer,
             bind_group_0,
-        }
+            texture_bind_groups,
+            bind_group_1_layout,
+            sampler,
+        })
+    }
+
+    /// A method to replace an image texture with the data of a PNG file.
+    ///
+    /// If the data can't be decoded, it returns the error and the old image is kept.
+    pub fn reload_texture(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        id: UiTextureId,
+        data: &[u8],
+    ) -> Result<(), Box<dyn Error>> {
+        let image_texture_view = image::create_image_texture_view(device, queue, None, data)?;
+        let bind_group = create_texture_bind_group(
+            device,
+            &self.bind_group_1_layout,
+            &image_texture_view,
+            &self.sampler,
+        );
+        self.texture_bind_groups.insert(id, bind_group);
+        Ok(())
+    }
+
+    /// A method to recompile the shader and recreate the render pipeline.
+    ///
+    /// If the shader is invalid, it returns the error and the old render pipeline is kept.
+    pub fn reload_shader(&mut self, device: &Device, shader: &str) -> Result<(), String> {
+        self.render_pipeline = shader::try_compile(device, shader, |n| {
+            create_render_pipeline(device, &self.pipeline_layout, n, &self.color_target_state)
+        })?;
+        Ok(())
     }
 
     /// A method to update instance buffer.
@@ -252,6 +371,7 @@ impl UiPipeline {
                     n.scale, n.rotation, n.position,
                 ),
                 _tex_coord: n.uv,
+                _color: n.color,
             },
         );
         for (i, n) in instances_group {
@@ -266,12 +386,13 @@ impl UiPipeline {
     /// A method to draw models.
     ///
     /// WARN: Indices exceeding `MAX_INSTANCES_COUNT` will be completely ignored.
-    pub fn draw<'a>(
+    pub fn draw(
         &self,
-        command_encoder: &'a mut CommandEncoder,
+        command_encoder: &mut CommandEncoder,
         render_target_view: &TextureView,
         square: &Model,
         descriptor: DrawUiDescriptor,
+        timestamp_writes: Option<RenderPassTimestampWrites>,
     ) {
         // begin render pass
         let load = if let Some(n) = descriptor.clear_color {
@@ -295,7 +416,7 @@ impl UiPipeline {
                 },
             })],
             depth_stencil_attachment: None,
-            timestamp_writes: None,
+            timestamp_writes,
             occlusion_query_set: None,
         });
 
@@ -303,14 +424,15 @@ impl UiPipeline {
         render_pass.set_pipeline(&self.render_pipeline);
         render_pass.set_bind_group(0, &self.bind_group_0, &[]);
         render_pass.set_vertex_buffer(0, square.vertex_buffer.slice(..));
-        render_pass.set_index_buffer(square.index_buffer.slice(..), IndexFormat::Uint16);
+        render_pass.set_index_buffer(square.index_buffer.slice(..), square.index_format);
 
         // draw
-        for (start, end) in descriptor.instance_indices {
+        for (id, start, end) in descriptor.instance_indices {
             if start >= MAX_INSTANCE_COUNT as u32 {
                 continue;
             }
             let end = cmp::min(MAX_INSTANCE_COUNT as u32, end);
+            render_pass.set_bind_group(1, &self.texture_bind_groups[&id], &[]);
             render_pass.draw_indexed(0..square.index_count as u32, 0, start..end);
         }
     }
@@ -1,7 +1,15 @@
 use crate::{
-    system::renderer::{
-        model::{self, Model, ModelId},
-        texture::depth,
+    system::{
+        asset::{AssetManager, ShaderHandle},
+        renderer::{
+            error::RendererError,
+            model::{self, Model, ModelId},
+            shader,
+            texture::{
+                depth,
+                image::{self, SamplerConfig},
+            },
+        },
     },
     util::{camera::CameraController, instance::InstanceController, memory, vector},
 };
@@ -9,17 +17,16 @@ use glam::{Mat4, Vec3, Vec4};
 use std::{borrow::Cow, cmp, collections::HashMap, mem};
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
-    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
-    BindGroupLayoutEntry, BindingType, Buffer, BufferBindingType, BufferSize, BufferUsages,
-    ColorTargetState, CommandEncoder, Device, FragmentState, IndexFormat, LoadOp, MultisampleState,
-    Operations, PipelineLayoutDescriptor, PrimitiveState, Queue, RenderPassColorAttachment,
-    RenderPassDepthStencilAttachment, RenderPassDescriptor, RenderPipeline,
-    RenderPipelineDescriptor, ShaderModuleDescriptor, ShaderSource, ShaderStages, StoreOp,
-    TextureView, VertexState,
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, Buffer, BufferBindingType, BufferSize,
+    BufferUsages, ColorTargetState, CommandEncoder, Device, FragmentState, LoadOp,
+    MultisampleState, Operations, PipelineLayout, PipelineLayoutDescriptor, PrimitiveState, Queue,
+    RenderPassColorAttachment, RenderPassDepthStencilAttachment, RenderPassDescriptor,
+    RenderPassTimestampWrites, RenderPipeline, RenderPipelineDescriptor, Sampler,
+    SamplerBindingType, ShaderModule, ShaderModuleDescriptor, ShaderSource, ShaderStages, StoreOp,
+    TextureSampleType, TextureView, TextureViewDimension, VertexState,
 };
 
-const SHADER: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/shader/world.wgsl"));
-
 struct Camera {
     _projection_matrix: Mat4,
     _view_matrix: Mat4,
@@ -27,12 +34,18 @@ struct Camera {
 struct Light {
     _direction: Vec4,
 }
+struct Material {
+    _base_color_factor: Vec4,
+}
 #[derive(Clone)]
 struct Instance {
     _model_matrix: Mat4,
     _model_matrix_inversed: Mat4,
 }
-const MAX_INSTANCE_COUNT: u64 = 16;
+/// The max count of instances on the world.
+///
+/// NOTE: It must be the same as the length of `instances` in world.wgsl.
+pub const MAX_INSTANCE_COUNT: u64 = 128;
 
 /// A struct for descripting the detail of a draw request on an world pipeline.
 pub struct DrawWorldDescriptor {
@@ -44,24 +57,102 @@ pub struct DrawWorldDescriptor {
 /// A pipeline implementaion of world.wgsl.
 pub struct WorldPipeline {
     render_pipeline: RenderPipeline,
+    pipeline_layout: PipelineLayout,
+    color_target_state: ColorTargetState,
     depth_texture_view: TextureView,
     camera_buffer: Buffer,
     instance_buffer: Buffer,
     bind_group_0: BindGroup,
+    bind_group_1_layout: BindGroupLayout,
+    sampler: Sampler,
+    /// The texture used by the materials without a base color texture.
+    white_texture_view: TextureView,
+    /// The material used by the model parts without a material.
+    default_material: BindGroup,
+}
+
+/// A function to create the render pipeline with a shader module.
+fn create_render_pipeline(
+    device: &Device,
+    pipeline_layout: &PipelineLayout,
+    shader_module: &ShaderModule,
+    color_target_state: &ColorTargetState,
+) -> RenderPipeline {
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: None,
+        layout: Some(pipeline_layout),
+        vertex: VertexState {
+            module: shader_module,
+            entry_point: "vs_main",
+            compilation_options: Default::default(),
+            buffers: model::VERTEX_BUFFER_LAYOUTS,
+        },
+        fragment: Some(FragmentState {
+            module: shader_module,
+            entry_point: "fs_main",
+            compilation_options: Default::default(),
+            targets: &[Some(color_target_state.clone())],
+        }),
+        primitive: PrimitiveState::default(),
+        depth_stencil: Some(depth::DEPTH_STENCIL_STATE),
+        multisample: MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+/// A function to create a material bind group, @group(1).
+fn create_material_bind_group(
+    device: &Device,
+    bind_group_1_layout: &BindGroupLayout,
+    texture_view: &TextureView,
+    sampler: &Sampler,
+    base_color_factor: Vec4,
+) -> BindGroup {
+    let material_buffer = device.create_buffer_init(&BufferInitDescriptor {
+        label: None,
+        contents: memory::anything_to_u8slice(&Material {
+            _base_color_factor: base_color_factor,
+        }),
+        usage: BufferUsages::UNIFORM,
+    });
+    device.create_bind_group(&BindGroupDescriptor {
+        label: None,
+        layout: bind_group_1_layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(texture_view),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::Sampler(sampler),
+            },
+            BindGroupEntry {
+                binding: 2,
+                resource: material_buffer.as_entire_binding(),
+            },
+        ],
+    })
 }
 
 impl WorldPipeline {
     /// A constructor.
     pub fn new(
         device: &Device,
+        queue: &Queue,
+        assets: &AssetManager,
         color_target_state: ColorTargetState,
         width: u32,
         height: u32,
-    ) -> Self {
+    ) -> Result<Self, RendererError> {
         // create a shader module
+        let shader = assets
+            .load_shader(ShaderHandle::World)
+            .map_err(RendererError::LoadAsset)?;
         let shader_module = device.create_shader_module(ShaderModuleDescriptor {
             label: None,
-            source: ShaderSource::Wgsl(Cow::from(SHADER)),
+            source: ShaderSource::Wgsl(Cow::from(shader)),
         });
 
         // create a bind group layout, @group(0)
@@ -103,35 +194,53 @@ impl WorldPipeline {
             ],
         });
 
+        // create a bind group layout, @group(1)
+        let bind_group_1_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(mem::size_of::<Material>() as u64),
+                    },
+                    count: None,
+                },
+            ],
+        });
+
         // create a pipeline layout
         let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
             label: None,
-            bind_group_layouts: &[&bind_group_0_layout],
+            bind_group_layouts: &[&bind_group_0_layout, &bind_group_1_layout],
             push_constant_ranges: &[],
         });
 
         // create a render pipeline
-        let render_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
-            label: None,
-            layout: Some(&pipeline_layout),
-            vertex: VertexState {
-                module: &shader_module,
-                entry_point: "vs_main",
-                compilation_options: Default::default(),
-                buffers: model::VERTEX_BUFFER_LAYOUTS,
-            },
-            fragment: Some(FragmentState {
-                module: &shader_module,
-                entry_point: "fs_main",
-                compilation_options: Default::default(),
-                targets: &[Some(color_target_state)],
-            }),
-            primitive: PrimitiveState::default(),
-            depth_stencil: Some(depth::DEPTH_STENCIL_STATE),
-            multisample: MultisampleState::default(),
-            multiview: None,
-            cache: None,
-        });
+        let render_pipeline = create_render_pipeline(
+            device,
+            &pipeline_layout,
+            &shader_module,
+            &color_target_state,
+        );
 
         // create a depth texture view
         let depth_texture_view = depth::create_depth_texture_view(device, width, height);
@@ -160,7 +269,6 @@ impl WorldPipeline {
 
         // create a instance uniform buffer
         let instances = (0..MAX_INSTANCE_COUNT)
-            .into_iter()
             .map(|_| Instance {
                 _model_matrix: Mat4::IDENTITY,
                 _model_matrix_inversed: Mat4::IDENTITY,
@@ -192,13 +300,61 @@ impl WorldPipeline {
             ],
         });
 
-        Self {
+        // create the default material
+        let sampler = image::create_sampler(device, &SamplerConfig::default());
+        let white_texture_view = image::create_color_texture_view(device, queue, [255; 4]);
+        let default_material = create_material_bind_group(
+            device,
+            &bind_group_1_layout,
+            &white_texture_view,
+            &sampler,
+            Vec4::ONE,
+        );
+
+        Ok(Self {
             render_pipeline,
+            pipeline_layout,
+            color_target_state,
             depth_texture_view,
             camera_buffer,
             instance_buffer,
             bind_group_0,
-        }
+            bind_group_1_layout,
+            sampler,
+            white_texture_view,
+            default_material,
+        })
+    }
+
+    /// A method to create a material bind group for a model part.
+    ///
+    /// * `base_color_texture` - the texture multiplied with the lighting and its sampler, white if it's `None`
+    /// * `base_color_factor` - the linear RGBA color multiplied with the texture
+    pub fn create_material(
+        &self,
+        device: &Device,
+        base_color_texture: Option<(&TextureView, &Sampler)>,
+        base_color_factor: Vec4,
+    ) -> BindGroup {
+        let (texture_view, sampler) =
+            base_color_texture.unwrap_or((&self.white_texture_view, &self.sampler));
+        create_material_bind_group(
+            device,
+            &self.bind_group_1_layout,
+            texture_view,
+            sampler,
+            base_color_factor,
+        )
+    }
+
+    /// A method to recompile the shader and recreate the render pipeline.
+    ///
+    /// If the shader is invalid, it returns the error and the old render pipeline is kept.
+    pub fn reload_shader(&mut self, device: &Device, shader: &str) -> Result<(), String> {
+        self.render_pipeline = shader::try_compile(device, shader, |n| {
+            create_render_pipeline(device, &self.pipeline_layout, n, &self.color_target_state)
+        })?;
+        Ok(())
     }
 
     /// A method to update instance buffer.
@@ -232,12 +388,13 @@ impl WorldPipeline {
     /// A method to draw models.
     ///
     /// WARN: Indices exceeding `MAX_INSTANCES_COUNT` will be completely ignored.
-    pub fn draw<'a>(
+    pub fn draw(
         &self,
-        command_encoder: &'a mut CommandEncoder,
+        command_encoder: &mut CommandEncoder,
         render_target_view: &TextureView,
         models: &HashMap<ModelId, Model>,
         descriptor: DrawWorldDescriptor,
+        timestamp_writes: Option<RenderPassTimestampWrites>,
     ) {
         // begin render pass
         let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
@@ -258,7 +415,7 @@ impl WorldPipeline {
                 }),
                 stencil_ops: None,
             }),
-            timestamp_writes: None,
+            timestamp_writes,
             occlusion_query_set: None,
         });
 
@@ -267,15 +424,22 @@ impl WorldPipeline {
         render_pass.set_bind_group(0, &self.bind_group_0, &[]);
 
         // draw
-        for (name, start, end) in descriptor.instance_indices {
+        // NOTE: A model that isn't loaded, e.g. failed to load, is skipped.
+        for (id, start, end) in descriptor.instance_indices {
             if start >= MAX_INSTANCE_COUNT as u32 {
                 continue;
             }
             let end = cmp::min(MAX_INSTANCE_COUNT as u32, end);
-            let model = &models[&name];
+            let Some(model) = models.get(&id) else {
+                continue;
+            };
             render_pass.set_vertex_buffer(0, model.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(model.index_buffer.slice(..), IndexFormat::Uint16);
-            render_pass.draw_indexed(0..model.index_count as u32, 0, start..end);
+            render_pass.set_index_buffer(model.index_buffer.slice(..), model.index_format);
+            for n in &model.parts {
+                let material = n.material.as_ref().unwrap_or(&self.default_material);
+                render_pass.set_bind_group(1, material, &[]);
+                render_pass.draw_indexed(n.index_range.clone(), 0, start..end);
+            }
         }
     }
 
@@ -284,25 +448,17 @@ impl WorldPipeline {
     /// It enqueues a `write_buffer` queue to `queue`.
     pub fn update_camera(&self, queue: &Queue, camera_controller: &CameraController) {
         let camera = Camera {
-            _projection_matrix: Mat4::perspective_lh(
-                camera_controller.pov,
-                camera_controller.width / camera_controller.height,
-                0.1,
-                1000.0,
-            ),
-            _view_matrix: Mat4::look_to_lh(
-                camera_controller.position,
-                camera_controller
-                    .rotation
-                    .mul_vec3(Vec3::new(0.0, 0.0, 1.0)),
-                camera_controller
-                    .rotation
-                    .mul_vec3(Vec3::new(0.0, 1.0, 0.0)),
-            ),
+            _projection_matrix: camera_controller.get_projection_matrix(),
+            _view_matrix: camera_controller.get_view_matrix(),
         };
         queue.write_buffer(&self.camera_buffer, 0, memory::anything_to_u8slice(&camera));
     }
 
+    /// A method to get the depth texture view the world is drawn with.
+    pub fn get_depth_texture_view(&self) -> &TextureView {
+        &self.depth_texture_view
+    }
+
     /// A method to recreate and resize the depth texture.
     pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
         self.depth_texture_view = depth::create_depth_texture_view(device, width, height);
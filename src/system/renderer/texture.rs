@@ -1,2 +1,4 @@
+pub mod cubemap;
 pub mod depth;
+pub mod hdr;
 pub mod image;
@@ -0,0 +1,574 @@
+//! A module for cubemap textures.
+//!
+//! The faces are in the order of +x, -x, +y, -y, +z and -z, which is the order of the layers of a cube texture.
+//! A face is viewed from the inside of the cube, and its texture coordinates follow the WebGPU convention.
+//!
+//! The images are converted to cubemaps on the CPU, and the mip levels are also generated on the CPU
+//! because a layer of a cube texture can't be rendered and sampled separately on the GL backend.
+
+use crate::system::renderer::texture::image;
+use glam::{Vec2, Vec3, Vec4};
+use std::{error::Error, f32::consts};
+use wgpu::{
+    Device, Extent3d, ImageCopyTexture, ImageDataLayout, Origin3d, Queue, TextureAspect,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView,
+    TextureViewDescriptor, TextureViewDimension,
+};
+
+/// A constant for the format of cubemap textures, which keeps the colors brighter than 1.
+pub const CUBEMAP_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+/// The count of the faces of a cube.
+pub const FACE_COUNT: usize = 6;
+/// The size range of the faces converted from an image.
+const FACE_SIZE_RANGE: (u32, u32) = (16, 2048);
+
+/// A struct for a bitmap of linear RGBA colors.
+///
+/// WARN: The width and the height must not be zero.
+#[derive(Clone, Debug)]
+pub struct Bitmap {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Vec4>,
+}
+
+impl Bitmap {
+    fn get(&self, x: i64, y: i64) -> Vec4 {
+        let x = x.rem_euclid(self.width as i64) as usize;
+        let y = y.clamp(0, self.height as i64 - 1) as usize;
+        self.pixels[y * self.width as usize + x]
+    }
+
+    /// A method to sample the bitmap with the bilinear filtering.
+    ///
+    /// The bitmap is repeated horizontally and clamped vertically.
+    pub fn sample(&self, tex_coord: Vec2) -> Vec4 {
+        let x = tex_coord.x * self.width as f32 - 0.5;
+        let y = tex_coord.y * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let top = self.get(x0, y0).lerp(self.get(x0 + 1, y0), fx);
+        let bottom = self.get(x0, y0 + 1).lerp(self.get(x0 + 1, y0 + 1), fx);
+        top.lerp(bottom, fy)
+    }
+
+    /// A method to create the next mip level, which is half the size, with the box filtering.
+    pub fn downsample(&self) -> Self {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        for y in 0..height as i64 {
+            for x in 0..width as i64 {
+                let sum = self.get(x * 2, y * 2)
+                    + self.get((x * 2 + 1).min(self.width as i64 - 1), y * 2)
+                    + self.get(x * 2, y * 2 + 1)
+                    + self.get((x * 2 + 1).min(self.width as i64 - 1), y * 2 + 1);
+                pixels.push(sum * 0.25);
+            }
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+/// An enum for enumerating the projections of a sphere onto an image.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Projection {
+    /// The longitude is mapped to x and the latitude to y, where the center of the image faces +z.
+    Equirect,
+    /// The northern hemisphere is on the left half and the southern one on the right half,
+    /// each projected onto a disk by the angle from its pole, as seen from the poles.
+    Hemispheres,
+}
+
+impl Projection {
+    /// A method to get the texture coordinate where a direction is projected.
+    pub fn get_tex_coord(self, direction: Vec3) -> Vec2 {
+        let direction = direction.normalize();
+        let horizontal = Vec2::new(direction.x, direction.z).normalize_or_zero();
+        match self {
+            Self::Equirect => Vec2::new(
+                0.5 + direction.x.atan2(direction.z) / consts::TAU,
+                direction.y.clamp(-1.0, 1.0).acos() / consts::PI,
+            ),
+            Self::Hemispheres => {
+                // NOTE: The disks are slightly smaller than the halves not to sample the other half.
+                let (x_offset, y) = if direction.y >= 0.0 {
+                    (0.25, direction.y)
+                } else {
+                    (0.75, -direction.y)
+                };
+                let k = 0.98 * y.clamp(-1.0, 1.0).acos() / consts::FRAC_PI_2;
+                Vec2::new(
+                    x_offset + horizontal.x * 0.25 * k,
+                    0.5 + horizontal.y * 0.5 * k,
+                )
+            }
+        }
+    }
+}
+
+/// A function to get the direction from the center of the cube to a point on a face.
+///
+/// * `face` - the index of the face from 0 to 5
+/// * `tex_coord` - the texture coordinate on the face from (0, 0) at the top left to (1, 1)
+pub fn get_face_direction(face: usize, tex_coord: Vec2) -> Vec3 {
+    let (s, t) = (tex_coord.x * 2.0 - 1.0, tex_coord.y * 2.0 - 1.0);
+    match face {
+        0 => Vec3::new(1.0, -t, -s),
+        1 => Vec3::new(-1.0, -t, s),
+        2 => Vec3::new(s, 1.0, t),
+        3 => Vec3::new(s, -1.0, -t),
+        4 => Vec3::new(s, -t, 1.0),
+        _ => Vec3::new(-s, -t, -1.0),
+    }
+}
+
+/// A function to create the faces of a cubemap from an image of a sphere.
+///
+/// The size of the faces is a quarter of the width of the image,
+/// which keeps the density of the texels around the horizon.
+///
+/// * `max_size` - the max size of the faces supported by the device
+pub fn create_faces(bitmap: &Bitmap, projection: Projection, max_size: u32) -> Vec<Bitmap> {
+    let size = (bitmap.width / 4)
+        .next_power_of_two()
+        .clamp(FACE_SIZE_RANGE.0, FACE_SIZE_RANGE.1.min(max_size));
+    (0..FACE_COUNT)
+        .map(|face| {
+            let mut pixels = Vec::with_capacity((size * size) as usize);
+            for y in 0..size {
+                for x in 0..size {
+                    let tex_coord = (Vec2::new(x as f32, y as f32) + 0.5) / size as f32;
+                    let direction = get_face_direction(face, tex_coord);
+                    pixels.push(bitmap.sample(projection.get_tex_coord(direction)));
+                }
+            }
+            Bitmap {
+                width: size,
+                height: size,
+                pixels,
+            }
+        })
+        .collect()
+}
+
+/// A function to check that the faces loaded from files can be a cubemap.
+///
+/// * `max_size` - the max size of the faces supported by the device
+pub fn check_faces(faces: &[Bitmap], max_size: u32) -> Result<(), String> {
+    if faces.len() != FACE_COUNT {
+        return Err(format!("{} faces are required", FACE_COUNT));
+    }
+    let size = faces[0].width;
+    if faces.iter().any(|n| n.width != size || n.height != size) {
+        return Err("the faces must be squares of the same size".to_string());
+    }
+    if size == 0 || size > max_size {
+        return Err(format!(
+            "the size of the faces must be between 1 and {}",
+            max_size
+        ));
+    }
+    Ok(())
+}
+
+/// A function to convert an sRGB component to a linear one.
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// A function to decode a PNG file into a bitmap.
+///
+/// NOTE: The colors are converted from sRGB to linear, but the alpha isn't.
+pub fn decode_png(data: &[u8]) -> Result<Bitmap, Box<dyn Error>> {
+    let (width, height, rgba) = image::decode_png(data)?;
+    let pixels = rgba
+        .chunks_exact(4)
+        .map(|n| {
+            Vec4::new(
+                srgb_to_linear(n[0] as f32 / 255.0),
+                srgb_to_linear(n[1] as f32 / 255.0),
+                srgb_to_linear(n[2] as f32 / 255.0),
+                n[3] as f32 / 255.0,
+            )
+        })
+        .collect();
+    Ok(Bitmap {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// A function to take the bytes from the front of data.
+fn take<'a>(data: &mut &'a [u8], count: usize) -> Result<&'a [u8], Box<dyn Error>> {
+    if data.len() < count {
+        return Err("the pixel data is truncated".into());
+    }
+    let (bytes, rest) = data.split_at(count);
+    *data = rest;
+    Ok(bytes)
+}
+
+/// A function to read the scanlines of RGBE pixels, which may be run-length encoded.
+fn read_rgbe_scanlines(
+    mut data: &[u8],
+    width: usize,
+    height: usize,
+) -> Result<Vec<[u8; 4]>, Box<dyn Error>> {
+    let mut rgbe = Vec::with_capacity(width * height);
+    for _ in 0..height {
+        let is_encoded = (8..0x8000).contains(&width) && data.starts_with(&[2, 2]);
+        if !is_encoded {
+            let bytes = take(&mut data, width * 4)?;
+            rgbe.extend(bytes.chunks_exact(4).map(|n| [n[0], n[1], n[2], n[3]]));
+            continue;
+        }
+
+        // NOTE: Each component of a run-length encoded scanline is stored separately.
+        let header = take(&mut data, 4)?;
+        if ((header[2] as usize) << 8 | header[3] as usize) != width {
+            return Err("the width of an encoded scanline is wrong".into());
+        }
+        let start = rgbe.len();
+        rgbe.resize(start + width, [0; 4]);
+        for i in 0..4 {
+            let mut x = 0;
+            while x < width {
+                let count = take(&mut data, 1)?[0] as usize;
+                let bytes = if count > 128 {
+                    vec![take(&mut data, 1)?[0]; count - 128]
+                } else {
+                    take(&mut data, count)?.to_vec()
+                };
+                if bytes.is_empty() || x + bytes.len() > width {
+                    return Err("a run of an encoded scanline is wrong".into());
+                }
+                for (pixel, n) in rgbe[start + x..].iter_mut().zip(&bytes) {
+                    pixel[i] = *n;
+                }
+                x += bytes.len();
+            }
+        }
+    }
+    Ok(rgbe)
+}
+
+/// A function to decode a Radiance HDR file into a bitmap.
+///
+/// NOTE: Only the `32-bit_rle_rgbe` format with the `-Y HEIGHT +X WIDTH` orientation is supported,
+///       and the old run-length encoding isn't.
+pub fn decode_hdr(data: &[u8]) -> Result<Bitmap, Box<dyn Error>> {
+    // read the header, which ends with an empty line, and the resolution line
+    let mut lines = Vec::<String>::new();
+    let mut position = 0;
+    while lines.len() < 2 || !lines[lines.len() - 2].is_empty() {
+        let end = data[position..]
+            .iter()
+            .position(|n| *n == b'\n')
+            .ok_or("the header isn't terminated")?;
+        lines.push(String::from_utf8_lossy(&data[position..position + end]).to_string());
+        position += end + 1;
+    }
+    if !lines[0].starts_with("#?") {
+        return Err("the magic number isn't found".into());
+    }
+    if let Some(n) = lines.iter().find_map(|n| n.strip_prefix("FORMAT=")) {
+        if n != "32-bit_rle_rgbe" {
+            return Err(format!("the format '{}' isn't supported", n).into());
+        }
+    }
+    let resolution = lines[lines.len() - 1]
+        .split_whitespace()
+        .collect::<Vec<&str>>();
+    let (height, width) = match resolution.as_slice() {
+        ["-Y", height, "+X", width] => (height.parse::<u32>()?, width.parse::<u32>()?),
+        _ => {
+            return Err(format!(
+                "the resolution '{}' isn't supported",
+                lines[lines.len() - 1]
+            )
+            .into())
+        }
+    };
+    if width == 0 || height == 0 {
+        return Err("the size of the image is zero".into());
+    }
+
+    // decode the pixels
+    let rgbe = read_rgbe_scanlines(&data[position..], width as usize, height as usize)?;
+    let pixels = rgbe
+        .iter()
+        .map(|n| {
+            if n[3] == 0 {
+                return Vec4::new(0.0, 0.0, 0.0, 1.0);
+            }
+            let scale = 2.0f32.powi(n[3] as i32 - 136);
+            Vec4::new(
+                n[0] as f32 * scale,
+                n[1] as f32 * scale,
+                n[2] as f32 * scale,
+                1.0,
+            )
+        })
+        .collect();
+    Ok(Bitmap {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// A function to convert a non-negative value to the bits of a half-precision float.
+///
+/// NOTE: The values out of the range are clamped and the subnormal values are flushed to zero.
+fn to_f16_bits(value: f32) -> u16 {
+    if value.is_nan() {
+        return 0;
+    }
+    let bits = value.clamp(0.0, 65504.0).to_bits();
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127 + 15;
+    if exponent <= 0 {
+        return 0;
+    }
+    let mantissa = bits & 0x7fffff;
+    let half = ((exponent as u32) << 10 | mantissa >> 13) + (mantissa >> 12 & 1);
+    half as u16
+}
+
+/// A function to create a cube texture view from the six faces with the full mip chain.
+///
+/// WARN: The faces must be squares of the same size.
+///       It enqueues `write_texture` queue to `queue` and submits it.
+pub fn create_cubemap_texture_view(
+    device: &Device,
+    queue: &Queue,
+    faces: &[Bitmap],
+) -> TextureView {
+    // create a texture and its view
+    let size = faces[0].width;
+    let mip_level_count = image::get_mip_level_count(size, size);
+    let texture = device.create_texture(&TextureDescriptor {
+        label: None,
+        size: Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: FACE_COUNT as u32,
+        },
+        mip_level_count,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: CUBEMAP_TEXTURE_FORMAT,
+        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    let texture_view = texture.create_view(&TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::Cube),
+        ..Default::default()
+    });
+
+    // write the faces to all the mip levels
+    let mut faces = faces.to_vec();
+    for level in 0..mip_level_count {
+        let size = faces[0].width;
+        let data = faces
+            .iter()
+            .flat_map(|n| n.pixels.iter())
+            .flat_map(|n| n.to_array().map(to_f16_bits))
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<u8>>();
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: &texture,
+                mip_level: level,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            &data,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(size * 8),
+                rows_per_image: Some(size),
+            },
+            Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: FACE_COUNT as u32,
+            },
+        );
+        faces = faces.iter().map(Bitmap::downsample).collect();
+    }
+    queue.submit(None);
+
+    texture_view
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The face and the texture coordinate of a direction by the selection of the major axis in the WebGPU spec.
+    fn lookup(direction: Vec3) -> (usize, Vec2) {
+        let a = direction.abs();
+        let (face, sc, tc, ma) = if a.x >= a.y && a.x >= a.z {
+            if direction.x > 0.0 {
+                (0, -direction.z, -direction.y, a.x)
+            } else {
+                (1, direction.z, -direction.y, a.x)
+            }
+        } else if a.y >= a.z {
+            if direction.y > 0.0 {
+                (2, direction.x, direction.z, a.y)
+            } else {
+                (3, direction.x, -direction.z, a.y)
+            }
+        } else if direction.z > 0.0 {
+            (4, direction.x, -direction.y, a.z)
+        } else {
+            (5, -direction.x, -direction.y, a.z)
+        };
+        (face, (Vec2::new(sc, tc) / ma + 1.0) * 0.5)
+    }
+
+    #[test]
+    fn face_directions_are_sampled_back() {
+        for face in 0..FACE_COUNT {
+            for tex_coord in [
+                Vec2::new(0.5, 0.5),
+                Vec2::new(0.1, 0.3),
+                Vec2::new(0.8, 0.6),
+                Vec2::new(0.3, 0.9),
+            ] {
+                let (n, t) = lookup(get_face_direction(face, tex_coord));
+                assert_eq!(n, face);
+                assert!(
+                    t.abs_diff_eq(tex_coord, 1e-6),
+                    "{} {} {}",
+                    face,
+                    tex_coord,
+                    t
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn projections_match_poles_and_horizon() {
+        let cases = [
+            (Projection::Equirect, Vec3::Z, Vec2::new(0.5, 0.5)),
+            (Projection::Equirect, Vec3::X, Vec2::new(0.75, 0.5)),
+            (Projection::Equirect, Vec3::Y, Vec2::new(0.5, 0.0)),
+            (Projection::Equirect, Vec3::NEG_Y, Vec2::new(0.5, 1.0)),
+            (Projection::Hemispheres, Vec3::Y, Vec2::new(0.25, 0.5)),
+            (Projection::Hemispheres, Vec3::NEG_Y, Vec2::new(0.75, 0.5)),
+            (Projection::Hemispheres, Vec3::Z, Vec2::new(0.25, 0.99)),
+            (Projection::Hemispheres, Vec3::X, Vec2::new(0.495, 0.5)),
+        ];
+        for (projection, direction, expected) in cases {
+            let tex_coord = projection.get_tex_coord(direction);
+            assert!(
+                tex_coord.abs_diff_eq(expected, 1e-6),
+                "{:?} {} {}",
+                projection,
+                direction,
+                tex_coord
+            );
+        }
+    }
+
+    #[test]
+    fn hdr_files_are_decoded() {
+        let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 2 +X 8\n";
+
+        // flat
+        let mut data = header.to_vec();
+        for i in 0..16 {
+            data.extend_from_slice(&[128, 64, i, 129]);
+        }
+        let bitmap = decode_hdr(&data).unwrap();
+        assert_eq!((bitmap.width, bitmap.height), (8, 2));
+        assert_eq!(bitmap.pixels[0], Vec4::new(1.0, 0.5, 0.0, 1.0));
+        assert_eq!(bitmap.pixels[15], Vec4::new(1.0, 0.5, 15.0 / 128.0, 1.0));
+
+        // run-length encoded
+        let mut data = header.to_vec();
+        for _ in 0..2 {
+            data.extend_from_slice(&[2, 2, 0, 8]);
+            data.extend_from_slice(&[136, 128]);
+            data.extend_from_slice(&[4, 64, 64, 64, 64, 132, 32]);
+            data.extend_from_slice(&[8, 0, 1, 2, 3, 4, 5, 6, 7]);
+            data.extend_from_slice(&[136, 129]);
+        }
+        let bitmap = decode_hdr(&data).unwrap();
+        assert_eq!(bitmap.pixels[0], Vec4::new(1.0, 0.5, 0.0, 1.0));
+        assert_eq!(bitmap.pixels[15], Vec4::new(1.0, 0.25, 7.0 / 128.0, 1.0));
+
+        // invalid
+        assert!(decode_hdr(b"#?RADIANCE\n\n+Y 2 +X 8\n").is_err());
+        assert!(decode_hdr(b"P6\n\n-Y 2 +X 8\n").is_err());
+        assert!(decode_hdr(&header[..header.len() - 1]).is_err());
+        assert!(decode_hdr(header).is_err());
+        assert!(decode_hdr(b"#?RADIANCE\n\n-Y 0 +X 8\n").is_err());
+        assert!(decode_hdr(b"#?RADIANCE\n\n-Y 2 +X 0\n").is_err());
+        assert!(decode_hdr(b"#?RADIANCE\n\n-Y 0 +X 0\n").is_err());
+    }
+
+    #[test]
+    fn faces_are_checked() {
+        let face = |width: u32, height: u32| Bitmap {
+            width,
+            height,
+            pixels: vec![Vec4::ONE; (width * height) as usize],
+        };
+        assert!(check_faces(&vec![face(16, 16); FACE_COUNT], 16).is_ok());
+
+        // count
+        assert!(check_faces(&vec![face(16, 16); FACE_COUNT - 1], 16).is_err());
+        // zero-sized
+        assert!(check_faces(&vec![face(0, 0); FACE_COUNT], 16).is_err());
+        // non-square
+        assert!(check_faces(&vec![face(16, 8); FACE_COUNT], 16).is_err());
+        // mismatched
+        let mut faces = vec![face(16, 16); FACE_COUNT];
+        faces[5] = face(8, 8);
+        assert!(check_faces(&faces, 16).is_err());
+        // too large
+        assert!(check_faces(&vec![face(32, 32); FACE_COUNT], 16).is_err());
+    }
+
+    #[test]
+    fn faces_are_limited_by_device() {
+        let bitmap = Bitmap {
+            width: 1024,
+            height: 512,
+            pixels: vec![Vec4::ONE; 1024 * 512],
+        };
+        assert_eq!(
+            create_faces(&bitmap, Projection::Equirect, 8192)[0].width,
+            256
+        );
+        assert_eq!(create_faces(&bitmap, Projection::Equirect, 64)[0].width, 64);
+    }
+
+    #[test]
+    fn half_floats_are_rounded_and_clamped() {
+        assert_eq!(to_f16_bits(0.0), 0);
+        assert_eq!(to_f16_bits(-1.0), 0);
+        assert_eq!(to_f16_bits(f32::NAN), 0);
+        assert_eq!(to_f16_bits(1e-8), 0);
+        assert_eq!(to_f16_bits(1.0), 0x3c00);
+        assert_eq!(to_f16_bits(0.5), 0x3800);
+        assert_eq!(to_f16_bits(1.0 + 1.0 / 1024.0), 0x3c01);
+        assert_eq!(to_f16_bits(65504.0), 0x7bff);
+        assert_eq!(to_f16_bits(1e6), 0x7bff);
+    }
+}
@@ -0,0 +1,29 @@
+use wgpu::{
+    Device, Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+    TextureView, TextureViewDescriptor,
+};
+
+/// A constant for the format of HDR render target textures.
+pub const HDR_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// A function to create an HDR render target texture view.
+///
+/// The texture can be both rendered to and sampled.
+pub fn create_hdr_texture_view(device: &Device, width: u32, height: u32) -> TextureView {
+    device
+        .create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: HDR_TEXTURE_FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        })
+        .create_view(&TextureViewDescriptor::default())
+}
@@ -1,39 +1,108 @@
-use std::{error::Error, fs::File};
-
-use png::Decoder;
+use crate::system::renderer::shader::mipmap::MipmapPipeline;
+use png::{ColorType, Decoder, Transformations};
+use std::error::Error;
 use wgpu::{
     AddressMode, Device, Extent3d, FilterMode, ImageDataLayout, Queue, Sampler, SamplerDescriptor,
     TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView,
     TextureViewDescriptor,
 };
 
-/// A function to create an image texture view.
+/// A struct for the configuration of a sampler, which is chosen per texture.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Debug)]
+pub struct SamplerConfig {
+    pub mag_filter: FilterMode,
+    pub min_filter: FilterMode,
+    /// The filter between the mip levels.
+    pub mipmap_filter: FilterMode,
+    /// The maximum anisotropy from 1 to 16, where 1 disables the anisotropic filtering.
+    ///
+    /// NOTE: It's ignored unless all the filters are `Linear`.
+    pub anisotropy: u16,
+    pub address_mode_u: AddressMode,
+    pub address_mode_v: AddressMode,
+}
+
+impl Default for SamplerConfig {
+    /// The trilinear and 16x anisotropic filtering with the repeated texture coordinates.
+    fn default() -> Self {
+        Self {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Linear,
+            anisotropy: 16,
+            address_mode_u: AddressMode::Repeat,
+            address_mode_v: AddressMode::Repeat,
+        }
+    }
+}
+
+/// A function to get the count of the mip levels down to 1x1.
+pub fn get_mip_level_count(width: u32, height: u32) -> u32 {
+    u32::BITS - width.max(height).max(1).leading_zeros()
+}
+
+/// A function to decode a PNG file into RGBA bitmap data.
 ///
-/// WARN: It enqueues `write_texture` queue to `queue` and submits it.
-pub fn create_image_texture_view(
-    device: &Device,
-    queue: &Queue,
-    path: &str,
-) -> Result<TextureView, Box<dyn Error>> {
-    // load an image file
-    let mut reader = Decoder::new(File::open(path)?).read_info()?;
+/// NOTE: Grayscale, RGB and indexed images are expanded to RGBA, and 16-bit channels are stripped to 8-bit.
+pub fn decode_png(data: &[u8]) -> Result<(u32, u32, Vec<u8>), Box<dyn Error>> {
+    let mut decoder = Decoder::new(data);
+    decoder.set_transformations(Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
     let mut buffer = vec![0; reader.output_buffer_size()];
     let output_info = reader.next_frame(&mut buffer)?;
+    buffer.truncate(output_info.buffer_size());
+    let rgba = match output_info.color_type {
+        ColorType::Rgba => buffer,
+        ColorType::Rgb => buffer
+            .chunks_exact(3)
+            .flat_map(|n| [n[0], n[1], n[2], 255])
+            .collect(),
+        ColorType::GrayscaleAlpha => buffer
+            .chunks_exact(2)
+            .flat_map(|n| [n[0], n[0], n[0], n[1]])
+            .collect(),
+        ColorType::Grayscale => buffer.iter().flat_map(|n| [*n, *n, *n, 255]).collect(),
+        ColorType::Indexed => return Err("the indexed image isn't expanded".into()),
+    };
+    Ok((output_info.width, output_info.height, rgba))
+}
 
+/// A function to create a texture view from RGBA bitmap data.
+///
+/// If `mipmap_pipeline` is specified, the full mip chain is generated from the bitmap data.
+///
+/// WARN: It enqueues `write_texture` queue to `queue` and submits it.
+fn create_texture_view(
+    device: &Device,
+    queue: &Queue,
+    mipmap_pipeline: Option<&MipmapPipeline>,
+    width: u32,
+    height: u32,
+    data: &[u8],
+) -> TextureView {
     // create a texture and its view
     let size = Extent3d {
-        width: output_info.width,
-        height: output_info.height,
+        width,
+        height,
         depth_or_array_layers: 1,
     };
+    let (mip_level_count, usage) = match mipmap_pipeline {
+        Some(_) => (
+            get_mip_level_count(width, height),
+            TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+        ),
+        None => (1, TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST),
+    };
     let texture = device.create_texture(&TextureDescriptor {
         label: None,
         size,
-        mip_level_count: 1,
+        mip_level_count,
         sample_count: 1,
         dimension: TextureDimension::D2,
         format: TextureFormat::Rgba8UnormSrgb,
-        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+        usage,
         view_formats: &[],
     });
     let texture_view = texture.create_view(&TextureViewDescriptor::default());
@@ -41,29 +110,86 @@ pub fn create_image_texture_view(
     // write the bitmap data to texture
     queue.write_texture(
         texture.as_image_copy(),
-        &buffer,
+        data,
         ImageDataLayout {
             offset: 0,
-            bytes_per_row: Some(output_info.width * 4),
+            bytes_per_row: Some(width * 4),
             rows_per_image: None,
         },
         size,
     );
-    queue.submit(None);
+    match mipmap_pipeline {
+        Some(n) if mip_level_count > 1 => n.generate(device, queue, &texture),
+        _ => {
+            queue.submit(None);
+        }
+    }
+
+    texture_view
+}
+
+/// A function to create an image texture view from the data of a PNG file.
+///
+/// If `mipmap_pipeline` is specified, the full mip chain is generated.
+/// Otherwise, the texture has only the base level, e.g. for the UI drawn at about the pixel size.
+///
+/// WARN: It enqueues `write_texture` queue to `queue` and submits it.
+pub fn create_image_texture_view(
+    device: &Device,
+    queue: &Queue,
+    mipmap_pipeline: Option<&MipmapPipeline>,
+    data: &[u8],
+) -> Result<TextureView, Box<dyn Error>> {
+    let (width, height, rgba) = decode_png(data)?;
+    Ok(create_texture_view(
+        device,
+        queue,
+        mipmap_pipeline,
+        width,
+        height,
+        &rgba,
+    ))
+}
 
-    Ok(texture_view)
+/// A function to create a 1x1 texture view filled with an sRGB color.
+///
+/// WARN: It enqueues `write_texture` queue to `queue` and submits it.
+pub fn create_color_texture_view(device: &Device, queue: &Queue, color: [u8; 4]) -> TextureView {
+    create_texture_view(device, queue, None, 1, 1, &color)
 }
 
 /// A function to create a sampler.
-pub fn create_sampler(device: &Device) -> Sampler {
+pub fn create_sampler(device: &Device, config: &SamplerConfig) -> Sampler {
+    let is_linear = [config.mag_filter, config.min_filter, config.mipmap_filter]
+        .iter()
+        .all(|n| *n == FilterMode::Linear);
     device.create_sampler(&SamplerDescriptor {
         label: None,
-        address_mode_u: AddressMode::Repeat,
-        address_mode_v: AddressMode::Repeat,
-        address_mode_w: AddressMode::Repeat,
-        mag_filter: FilterMode::Linear,
-        min_filter: FilterMode::Nearest,
-        mipmap_filter: FilterMode::Nearest,
+        address_mode_u: config.address_mode_u,
+        address_mode_v: config.address_mode_v,
+        address_mode_w: AddressMode::ClampToEdge,
+        mag_filter: config.mag_filter,
+        min_filter: config.min_filter,
+        mipmap_filter: config.mipmap_filter,
+        anisotropy_clamp: if is_linear {
+            config.anisotropy.clamp(1, 16)
+        } else {
+            1
+        },
         ..Default::default()
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mip_chain_ends_at_1x1() {
+        assert_eq!(get_mip_level_count(1, 1), 1);
+        assert_eq!(get_mip_level_count(2, 1), 2);
+        assert_eq!(get_mip_level_count(256, 256), 9);
+        assert_eq!(get_mip_level_count(300, 17), 9);
+        assert_eq!(get_mip_level_count(0, 0), 1);
+    }
+}
@@ -0,0 +1,219 @@
+//! A module for recording and replaying inputs.
+//!
+//! A replay file starts with a `replay VERSION` line,
+//! and it's followed by one line per frame with the following values separated by spaces:
+//!
+//! ```text
+//! KEY_W KEY_A KEY_S KEY_D KEY_E MOUSE_LEFT ESCAPE ARROW_UP ARROW_DOWN ARROW_LEFT ARROW_RIGHT MOVING_X MOVING_Y
+//! ```
+//!
+//! The pressing values are the counts of frames held by `InputManager`.
+//! Since the game updates per frame, the same inputs reproduce the same game with the same seed.
+//!
+//! NOTE: The version must be bumped when the values change, e.g. a pressing input is added.
+//!       The files of the first version have no version line and only six pressing values.
+
+use crate::system::input::{InputStates, MovingInputState, PRESSING_INPUTS};
+use std::fs;
+
+/// The version of the replay format written by this build.
+const REPLAY_VERSION: u32 = 2;
+
+fn to_line(input_states: &InputStates) -> String {
+    let mut values = PRESSING_INPUTS
+        .iter()
+        .map(|n| input_states.pressing.get(n).to_string())
+        .collect::<Vec<String>>();
+    values.push(input_states.moving.x.to_string());
+    values.push(input_states.moving.y.to_string());
+    values.join(" ")
+}
+
+fn parse_line(line: &str) -> Result<InputStates, String> {
+    let values = line.split_whitespace().collect::<Vec<&str>>();
+    if values.len() != PRESSING_INPUTS.len() + 2 {
+        return Err(format!(
+            "{} values are expected but {} found",
+            PRESSING_INPUTS.len() + 2,
+            values.len()
+        ));
+    }
+    let mut input_states = InputStates::default();
+    for (input, value) in PRESSING_INPUTS.iter().zip(values.iter()) {
+        let value = value
+            .parse::<u32>()
+            .map_err(|e| format!("invalid pressing value '{}': {}", value, e))?;
+        input_states.pressing.states.insert(*input, value);
+    }
+    let moving = &values[PRESSING_INPUTS.len()..];
+    let parse_f64 = |n: &str| {
+        n.parse::<f64>()
+            .map_err(|e| format!("invalid moving value '{}': {}", n, e))
+    };
+    input_states.moving = MovingInputState {
+        x: parse_f64(moving[0])?,
+        y: parse_f64(moving[1])?,
+    };
+    Ok(input_states)
+}
+
+/// A function to parse the text of a replay file into the inputs of the frames.
+///
+/// If it fails, the error message starts with the line number.
+fn parse_replay(text: &str) -> Result<Vec<InputStates>, String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, n)| !n.trim().is_empty());
+
+    // check the version
+    let version = match lines.next() {
+        Some((_, n)) => match n.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            ["replay", version] => version
+                .parse::<u32>()
+                .map_err(|e| format!("1: invalid version '{}': {}", version, e))?,
+            _ => 1,
+        },
+        None => REPLAY_VERSION,
+    };
+    if version != REPLAY_VERSION {
+        return Err(format!(
+            "1: the version {} isn't supported, record it again with the version {}",
+            version, REPLAY_VERSION
+        ));
+    }
+
+    // parse the frames
+    lines
+        .map(|(i, n)| parse_line(n).map_err(|e| format!("{}: {}", i + 1, e)))
+        .collect()
+}
+
+/// A recorder of inputs.
+///
+/// The inputs are kept in memory and written to the file by `save()`.
+pub struct InputRecorder {
+    path: String,
+    lines: Vec<String>,
+}
+
+impl InputRecorder {
+    /// A constructor.
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            lines: Vec::new(),
+        }
+    }
+
+    /// A method to record the inputs of a frame.
+    pub fn push(&mut self, input_states: &InputStates) {
+        self.lines.push(to_line(input_states));
+    }
+
+    /// A method to write the recorded inputs to the file.
+    pub fn save(&self) -> Result<(), String> {
+        let mut text = format!("replay {}\n", REPLAY_VERSION);
+        for n in &self.lines {
+            text.push_str(n);
+            text.push('\n');
+        }
+        fs::write(&self.path, text).map_err(|e| format!("{}: {}", self.path, e))?;
+        info!(
+            "InputRecorder.save",
+            "{} frames are recorded to {}.",
+            self.lines.len(),
+            self.path
+        );
+        Ok(())
+    }
+}
+
+/// A player of recorded inputs.
+pub struct InputPlayer {
+    frames: Vec<InputStates>,
+    index: usize,
+}
+
+impl InputPlayer {
+    /// A static method to load recorded inputs from a file.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let frames = parse_replay(&text).map_err(|e| format!("{}:{}", path, e))?;
+        info!(
+            "InputPlayer.load",
+            "{} frames are loaded from {}.",
+            frames.len(),
+            path
+        );
+        Ok(Self { frames, index: 0 })
+    }
+
+    /// A method to get the inputs of the next frame.
+    ///
+    /// If all the frames are played, it returns `None`.
+    pub fn next_frame(&mut self) -> Option<&InputStates> {
+        let input_states = self.frames.get(self.index)?;
+        self.index += 1;
+        Some(input_states)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::input::PressingInput;
+
+    #[test]
+    fn old_versions_are_rejected() {
+        let frame = "0 0 0 0 0 1 0 0 0 0 0 2.5 -1";
+        assert_eq!(
+            parse_replay(&format!("replay {}\n{}\n", REPLAY_VERSION, frame))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(parse_replay("").unwrap().is_empty());
+
+        // the first version has no version line and six pressing values
+        let error = parse_replay("0 0 0 0 0 1 2.5 -1\n").err().unwrap();
+        assert!(error.starts_with("1: the version 1"), "{}", error);
+        assert!(parse_replay(&format!("replay 3\n{}\n", frame)).is_err());
+        assert!(parse_replay(&format!("replay x\n{}\n", frame)).is_err());
+
+        // a frame with the values of another version
+        let error = parse_replay(&format!("replay {}\n0 0 0 0 0 1 2.5 -1\n", REPLAY_VERSION))
+            .err()
+            .unwrap();
+        assert!(error.starts_with("2: "), "{}", error);
+    }
+
+    #[test]
+    fn inputs_are_replayed_as_recorded() {
+        let mut frames = Vec::from([InputStates::default(), InputStates::default()]);
+        for (i, n) in PRESSING_INPUTS.iter().enumerate() {
+            frames[0].pressing.states.insert(*n, i as u32);
+        }
+        frames[0].moving = MovingInputState { x: 2.5, y: -0.125 };
+        frames[1]
+            .pressing
+            .states
+            .insert(PressingInput::MouseLeft, 100);
+
+        // the same text as saved by the recorder
+        let mut text = format!("replay {}\n", REPLAY_VERSION);
+        for n in &frames {
+            text.push_str(&to_line(n));
+            text.push('\n');
+        }
+        let replayed = parse_replay(&text).unwrap();
+        assert_eq!(replayed.len(), frames.len());
+        for (expected, actual) in frames.iter().zip(replayed.iter()) {
+            for n in &PRESSING_INPUTS {
+                assert_eq!(expected.pressing.get(n), actual.pressing.get(n));
+            }
+            assert_eq!(expected.moving.x, actual.moving.x);
+            assert_eq!(expected.moving.y, actual.moving.y);
+        }
+    }
+}
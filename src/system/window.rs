@@ -0,0 +1,161 @@
+use crate::settings::{Settings, WindowMode};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event_loop::ActiveEventLoop,
+    monitor::{MonitorHandle, VideoModeHandle},
+    window::{Fullscreen, Window, WindowAttributes, WindowButtons},
+};
+
+const DEFAULT_WINDOWED_SIZE: (u32, u32) = (1280, 720);
+
+/// A function to select a monitor.
+///
+/// * `index` - the index in the available monitors; if it's `None`, the primary monitor is selected
+///
+/// If the monitor of `index` isn't found, it falls back to the primary monitor.
+pub fn select_monitor(event_loop: &ActiveEventLoop, index: Option<usize>) -> Option<MonitorHandle> {
+    if let Some(i) = index {
+        if let Some(n) = event_loop.available_monitors().nth(i) {
+            return Some(n);
+        }
+        warn!(
+            "select_monitor",
+            "monitor {} isn't found. the primary monitor is used instead.", i
+        );
+    }
+    event_loop
+        .primary_monitor()
+        .or_else(|| event_loop.available_monitors().next())
+}
+
+/// A function to select a video mode of a monitor for exclusive fullscreen.
+///
+/// If `resolution` is `None`, the current size of the monitor is used.
+/// If `refresh_rate` is `None` or not found, the highest refresh rate is chosen.
+fn select_video_mode(
+    monitor: &MonitorHandle,
+    resolution: Option<(u32, u32)>,
+    refresh_rate: Option<u32>,
+) -> Option<VideoModeHandle> {
+    let (width, height) = resolution.unwrap_or((monitor.size().width, monitor.size().height));
+    let mut video_modes = monitor
+        .video_modes()
+        .filter(|n| n.size() == PhysicalSize::new(width, height))
+        .collect::<Vec<VideoModeHandle>>();
+    if video_modes.is_empty() {
+        warn!(
+            "select_video_mode",
+            "no video mode of {}x{} is found.", width, height
+        );
+        return None;
+    }
+    video_modes.sort_by_key(|n| (n.refresh_rate_millihertz(), n.bit_depth()));
+    if let Some(refresh_rate) = refresh_rate {
+        let found = video_modes
+            .iter()
+            .rev()
+            .find(|n| (n.refresh_rate_millihertz() + 500) / 1000 == refresh_rate);
+        if let Some(n) = found {
+            return Some(n.clone());
+        }
+        warn!(
+            "select_video_mode",
+            "no video mode of {}Hz is found.", refresh_rate
+        );
+    }
+    video_modes.pop()
+}
+
+/// A function to get the fullscreen state of a window mode.
+///
+/// If no suitable video mode is found for exclusive mode, it falls back to borderless.
+pub fn get_fullscreen(
+    window_mode: WindowMode,
+    monitor: &MonitorHandle,
+    settings: &Settings,
+) -> Option<Fullscreen> {
+    match window_mode {
+        WindowMode::Windowed => None,
+        WindowMode::Borderless => Some(Fullscreen::Borderless(Some(monitor.clone()))),
+        WindowMode::Exclusive => {
+            match select_video_mode(monitor, settings.resolution, settings.refresh_rate) {
+                Some(n) => Some(Fullscreen::Exclusive(n)),
+                None => Some(Fullscreen::Borderless(Some(monitor.clone()))),
+            }
+        }
+    }
+}
+
+/// A function to get the inner size of a window in windowed mode.
+///
+/// It never exceeds the size of the monitor.
+fn get_windowed_size(monitor: &MonitorHandle, settings: &Settings) -> PhysicalSize<u32> {
+    let (width, height) = settings.resolution.unwrap_or(DEFAULT_WINDOWED_SIZE);
+    PhysicalSize::new(
+        width.min(monitor.size().width),
+        height.min(monitor.size().height),
+    )
+}
+
+/// A function to create window attributes depending on the settings.
+pub fn create_window_attributes(monitor: &MonitorHandle, settings: &Settings) -> WindowAttributes {
+    let attributes = Window::default_attributes().with_title("WebGPU Simple FPS");
+    match settings.window_mode {
+        WindowMode::Windowed => {
+            let size = get_windowed_size(monitor, settings);
+            attributes
+                .with_resizable(true)
+                .with_inner_size(size)
+                .with_position(PhysicalPosition::new(
+                    monitor.position().x + (monitor.size().width - size.width) as i32 / 2,
+                    monitor.position().y + (monitor.size().height - size.height) as i32 / 2,
+                ))
+        }
+        _ => attributes
+            .with_resizable(false)
+            .with_inner_size(monitor.size())
+            .with_fullscreen(get_fullscreen(settings.window_mode, monitor, settings)),
+    }
+}
+
+/// A function to configure a window depending on whether it's fullscreen or not.
+///
+/// The maximize button is disabled in fullscreen.
+pub fn configure_window(window: &Window) {
+    if window.fullscreen().is_some() {
+        window.set_resizable(false);
+        window.set_enabled_buttons(WindowButtons::CLOSE | WindowButtons::MINIMIZE);
+    } else {
+        window.set_resizable(true);
+        window.set_enabled_buttons(WindowButtons::all());
+    }
+}
+
+/// A function to apply the window mode in the settings to a window.
+///
+/// In windowed mode, the window is resized to the resolution in the settings.
+pub fn apply_window_mode(window: &Window, monitor: &MonitorHandle, settings: &Settings) {
+    window.set_fullscreen(get_fullscreen(settings.window_mode, monitor, settings));
+    if settings.window_mode == WindowMode::Windowed {
+        let _ = window.request_inner_size(get_windowed_size(monitor, settings));
+    }
+    configure_window(window);
+}
+
+/// A function to toggle a window between windowed and fullscreen.
+///
+/// The fullscreen mode is the one in the settings.
+/// If the settings is windowed mode, borderless mode is used.
+pub fn toggle_fullscreen(window: &Window, monitor: &MonitorHandle, settings: &Settings) {
+    if window.fullscreen().is_some() {
+        window.set_fullscreen(None);
+        let _ = window.request_inner_size(get_windowed_size(monitor, settings));
+    } else {
+        let window_mode = match settings.window_mode {
+            WindowMode::Windowed => WindowMode::Borderless,
+            n => n,
+        };
+        window.set_fullscreen(get_fullscreen(window_mode, monitor, settings));
+    }
+    configure_window(window);
+}
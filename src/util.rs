@@ -1,5 +1,7 @@
 pub mod cache;
 pub mod camera;
+pub mod font;
 pub mod instance;
 pub mod memory;
+pub mod random;
 pub mod vector;
@@ -26,12 +26,11 @@ where
         }
     }
 
-    pub fn get(&self) -> &T {
-        &self.content
+    pub fn invalidate(&mut self) {
+        self.is_cached = false;
     }
 
-    pub fn get_mut(&mut self) -> &mut T {
-        self.is_cached = false;
-        &mut self.content
+    pub fn get(&self) -> &T {
+        &self.content
     }
 }
@@ -1,4 +1,4 @@
-use glam::{EulerRot, Quat, Vec3};
+use glam::{EulerRot, Mat4, Quat, Vec2, Vec3, Vec3Swizzles, Vec4Swizzles};
 
 #[derive(Clone)]
 pub struct CameraController {
@@ -49,4 +49,43 @@ impl CameraController {
         };
         self.rotation = Quat::from_euler(EulerRot::YXZ, y, x, 0.0);
     }
+
+    /// A method to rotate the camera to look in a direction.
+    ///
+    /// The roll is always zero.
+    pub fn look_to(&mut self, direction: Vec3) {
+        let y = direction.x.atan2(direction.z);
+        let x = (-direction.y).atan2(direction.xz().length());
+        self.rotation = Quat::from_euler(EulerRot::YXZ, y, x, 0.0);
+    }
+
+    /// A method to get the perspective projection matrix.
+    pub fn get_projection_matrix(&self) -> Mat4 {
+        Mat4::perspective_lh(self.pov, self.width / self.height, 0.1, 1000.0)
+    }
+
+    /// A method to get the view matrix.
+    pub fn get_view_matrix(&self) -> Mat4 {
+        Mat4::look_to_lh(
+            self.position,
+            self.rotation.mul_vec3(Vec3::new(0.0, 0.0, 1.0)),
+            self.rotation.mul_vec3(Vec3::new(0.0, 1.0, 0.0)),
+        )
+    }
+
+    /// A method to project a point on the world onto the screen.
+    ///
+    /// It returns the position in UI coordinates.
+    /// If the point is behind the camera, it returns `None`.
+    pub fn project(&self, position: Vec3) -> Option<Vec2> {
+        let clip = self.get_projection_matrix() * self.get_view_matrix() * position.extend(1.0);
+        if clip.w <= 0.0 {
+            return None;
+        }
+        let ndc = clip.xy() / clip.w;
+        Some(Vec2::new(
+            ndc.x * self.width / 2.0,
+            ndc.y * self.height / 2.0,
+        ))
+    }
 }
@@ -0,0 +1,314 @@
+//! A module for bitmap fonts.
+//!
+//! A font is described in the text format of BMFont (AngelCode).
+//! See `/image/font.fnt` for an example.
+//! Only the fonts with a single page are supported.
+//!
+//! NOTE: The page image isn't loaded here. `UiPipeline` loads it from `/image/font.png`.
+
+use crate::system::asset::AssetManager;
+use glam::Vec4;
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+/// The asset path of the font descriptor used by the application.
+const DEFAULT_FONT_PATH: &str = "image/font.fnt";
+
+/// The character drawn instead of the characters that the font doesn't have.
+const REPLACEMENT_CHARACTER: char = '?';
+/// The character whose glyph is filled with white.
+const SOLID_CHARACTER: char = '█';
+
+/// A glyph of a font.
+///
+/// All the values are in px of the page image.
+#[derive(Clone, Debug)]
+pub struct Glyph {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// The offset from the pen position to the left of the glyph.
+    pub x_offset: f32,
+    /// The offset from the top of the line to the top of the glyph.
+    pub y_offset: f32,
+    /// The distance the pen moves after the glyph.
+    pub x_advance: f32,
+}
+
+fn split_line(line: &str) -> Result<(&str, HashMap<&str, &str>), String> {
+    let line = line.trim();
+    let (tag, mut rest) = line.split_once(' ').unwrap_or((line, ""));
+    let mut values = HashMap::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        let Some((key, value)) = rest.split_once('=') else {
+            return Err(format!("'=' is expected after '{}'", rest));
+        };
+        if key.contains(' ') {
+            return Err(format!("'=' is expected after '{}'", key));
+        }
+        // NOTE: The quoted values can contain spaces.
+        let (value, next) = if let Some(value) = value.strip_prefix('"') {
+            let Some(end) = value.find('"') else {
+                return Err(format!("unclosed quote in the value of '{}'", key));
+            };
+            (&value[..end], &value[end + 1..])
+        } else {
+            value.split_once(' ').unwrap_or((value, ""))
+        };
+        values.insert(key, value);
+        rest = next;
+    }
+    Ok((tag, values))
+}
+
+fn get_value<T>(values: &HashMap<&str, &str>, key: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let Some(value) = values.get(key) else {
+        return Err(format!("'{}' is not specified", key));
+    };
+    value
+        .parse::<T>()
+        .map_err(|e| format!("invalid value '{}' of '{}': {}", value, key, e))
+}
+
+/// A bitmap font.
+pub struct Font {
+    /// The distance (px) between the tops of lines.
+    line_height: f32,
+    /// The size (px) of the page image.
+    page_size: (f32, f32),
+    glyphs: HashMap<char, Glyph>,
+    kernings: HashMap<(char, char), f32>,
+}
+
+impl Font {
+    /// A static method to load the default font from the assets.
+    pub fn default_font(assets: &AssetManager) -> Result<Self, String> {
+        let text = assets
+            .read_text(DEFAULT_FONT_PATH)
+            .map_err(|e| e.to_string())?;
+        Self::parse(&text).map_err(|e| format!("{}:{}", DEFAULT_FONT_PATH, e))
+    }
+
+    /// A static method to parse a font descriptor.
+    ///
+    /// If it fails, the error message starts with the line number.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut font = Self {
+            line_height: 0.0,
+            page_size: (0.0, 0.0),
+            glyphs: HashMap::new(),
+            kernings: HashMap::new(),
+        };
+        for (i, line) in text.lines().enumerate() {
+            font.parse_line(line)
+                .map_err(|e| format!("{}: {}", i + 1, e))?;
+        }
+
+        // check
+        if font.page_size.0 <= 0.0 || font.page_size.1 <= 0.0 {
+            return Err("0: no valid 'common' line is found".to_string());
+        }
+        if font.glyphs.is_empty() {
+            return Err("0: no glyph is found".to_string());
+        }
+
+        Ok(font)
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        let (tag, values) = split_line(line)?;
+        match tag {
+            "common" => {
+                let pages = get_value::<u32>(&values, "pages")?;
+                if pages != 1 {
+                    return Err(format!("only 1 page is supported but {} found", pages));
+                }
+                self.line_height = get_value(&values, "lineHeight")?;
+                self.page_size = (get_value(&values, "scaleW")?, get_value(&values, "scaleH")?);
+            }
+            "char" => {
+                let id = get_value::<u32>(&values, "id")?;
+                let Some(character) = char::from_u32(id) else {
+                    return Err(format!("invalid character id {}", id));
+                };
+                let glyph = Glyph {
+                    x: get_value(&values, "x")?,
+                    y: get_value(&values, "y")?,
+                    width: get_value(&values, "width")?,
+                    height: get_value(&values, "height")?,
+                    x_offset: get_value(&values, "xoffset")?,
+                    y_offset: get_value(&values, "yoffset")?,
+                    x_advance: get_value(&values, "xadvance")?,
+                };
+                if glyph.x + glyph.width > self.page_size.0
+                    || glyph.y + glyph.height > self.page_size.1
+                {
+                    return Err(format!("the glyph of {} is out of the page", id));
+                }
+                self.glyphs.insert(character, glyph);
+            }
+            "kerning" => {
+                let first = get_value::<u32>(&values, "first")?;
+                let second = get_value::<u32>(&values, "second")?;
+                let (Some(first), Some(second)) = (char::from_u32(first), char::from_u32(second))
+                else {
+                    return Err(format!("invalid character id {} or {}", first, second));
+                };
+                self.kernings
+                    .insert((first, second), get_value(&values, "amount")?);
+            }
+            // NOTE: The other tags such as `info` and `page` aren't needed.
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// A method to get the distance (px) between the tops of lines.
+    pub fn get_line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    /// A method to get the glyph of a character.
+    ///
+    /// If the font doesn't have it, it returns the glyph of `'?'` or `None`.
+    pub fn get_glyph(&self, character: char) -> Option<&Glyph> {
+        self.glyphs
+            .get(&character)
+            .or_else(|| self.glyphs.get(&REPLACEMENT_CHARACTER))
+    }
+
+    /// A method to get the kerning amount (px) between two characters.
+    pub fn get_kerning(&self, first: char, second: char) -> f32 {
+        self.kernings.get(&(first, second)).copied().unwrap_or(0.0)
+    }
+
+    /// A method to get the uv of a glyph in the page image.
+    pub fn get_uv(&self, glyph: &Glyph) -> Vec4 {
+        Vec4::new(
+            glyph.x / self.page_size.0,
+            glyph.y / self.page_size.1,
+            glyph.width / self.page_size.0,
+            glyph.height / self.page_size.1,
+        )
+    }
+
+    /// A method to get the uv of the solid white glyph `'█'`.
+    ///
+    /// It's used to draw filled rectangles with the font texture.
+    /// If the font doesn't have it, it returns `None`.
+    pub fn get_solid_uv(&self) -> Option<Vec4> {
+        let uv = self.get_uv(self.glyphs.get(&SOLID_CHARACTER)?);
+        // NOTE: Shrink it not to sample the neighbor glyphs.
+        Some(Vec4::new(
+            uv.x + uv.z * 0.25,
+            uv.y + uv.w * 0.25,
+            uv.z * 0.5,
+            uv.w * 0.5,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::entity::text::{Text, TextAlign};
+
+    const FONT: &str = r#"info face="Test Font" size=16
+common lineHeight=20 base=16 scaleW=64 scaleH=32 pages=1
+page id=0 file="font.png"
+char id=65 x=0 y=0 width=8 height=16 xoffset=1 yoffset=2 xadvance=10
+char id=66 x=8 y=0 width=8 height=16 xoffset=0 yoffset=2 xadvance=9
+char id=63 x=16 y=0 width=6 height=16 xoffset=0 yoffset=2 xadvance=7
+char id=32 x=0 y=0 width=0 height=0 xoffset=0 yoffset=0 xadvance=5
+kerning first=65 second=66 amount=-2
+"#;
+
+    #[test]
+    fn fonts_are_parsed() {
+        let font = Font::parse(FONT).unwrap();
+        assert_eq!(font.get_line_height(), 20.0);
+        let glyph = font.get_glyph('A').unwrap();
+        assert_eq!(glyph.x_offset, 1.0);
+        assert_eq!(glyph.x_advance, 10.0);
+        assert_eq!(font.get_uv(glyph), Vec4::new(0.0, 0.0, 0.125, 0.5));
+        assert_eq!(font.get_kerning('A', 'B'), -2.0);
+        assert_eq!(font.get_kerning('B', 'A'), 0.0);
+        // no solid glyph
+        assert!(font.get_solid_uv().is_none());
+    }
+
+    #[test]
+    fn malformed_fonts_are_rejected() {
+        let check = |text: &str, expected: &str| {
+            let error = Font::parse(text).err().unwrap();
+            assert!(error.starts_with(expected), "{}", error);
+        };
+
+        // the errors of a line start with the line number
+        check(
+            &FONT.replace("xadvance=9", "xadvance"),
+            "5: '=' is expected",
+        );
+        check(
+            &FONT.replace("xadvance=9", "xadvance=x"),
+            "5: invalid value 'x'",
+        );
+        check(&FONT.replace("Font\"", "Font"), "1: unclosed quote");
+        check(&FONT.replace("pages=1", "pages=2"), "2: only 1 page");
+        check(&FONT.replace("x=8 y=0", "x=60 y=0"), "5: the glyph of 66");
+
+        // the errors of the whole font start with 0
+        check("info face=\"Test Font\"\n", "0: no valid 'common'");
+        check(
+            &FONT.lines().take(3).collect::<Vec<&str>>().join("\n"),
+            "0: no glyph",
+        );
+    }
+
+    #[test]
+    fn missing_glyphs_fall_back_to_replacement() {
+        let font = Font::parse(FONT).unwrap();
+        assert_eq!(font.get_glyph('Z').unwrap().x_advance, 7.0);
+
+        // without the replacement glyph, the characters are skipped and the kerning is kept
+        let text = FONT.replace("char id=63", "char id=67");
+        let font = Font::parse(&text).unwrap();
+        assert!(font.get_glyph('Z').is_none());
+        let text = Text::new(&font, 0.0, 0.0, 1.0, Vec4::ONE, TextAlign::Left, "AZB");
+        assert_eq!(text.get_width(), 17.0);
+    }
+
+    #[test]
+    fn widths_are_aligned() {
+        let font = Font::parse(FONT).unwrap();
+        // 10 + 9 - 2 (kerning) + 5 + 7 ('?' of 'Z'), 2 times
+        let expected_width = 58.0;
+        for (align, expected_left) in [
+            (TextAlign::Left, 100.0),
+            (TextAlign::Center, 100.0 - expected_width / 2.0),
+            (TextAlign::Right, 100.0 - expected_width),
+        ] {
+            let mut text = Text::new(&font, 100.0, 0.0, 2.0, Vec4::ONE, align, "AB Z\nA");
+            assert_eq!(text.get_width(), expected_width);
+            assert_eq!(text.get_height(), 80.0);
+
+            // the first glyph is offset by its xoffset from the left of the line
+            let instances = text.get_instance_controllers();
+            assert_eq!(instances.len(), 4);
+            let first = instances[0].as_ref().unwrap();
+            let left = first.position.x - first.scale.x / 2.0;
+            assert_eq!(left, expected_left + 2.0, "{:?}", align);
+        }
+    }
+}
@@ -6,6 +6,8 @@ pub struct InstanceController {
     pub rotation: Quat,
     pub position: Vec3,
     pub uv: Vec4,
+    /// The color multiplied by the texture color, only used in the UI pipeline.
+    pub color: Vec4,
 }
 
 impl Default for InstanceController {
@@ -15,6 +17,7 @@ impl Default for InstanceController {
             rotation: Quat::default(),
             position: Vec3::default(),
             uv: Vec4::new(0.0, 0.0, 1.0, 1.0),
+            color: Vec4::new(1.0, 1.0, 1.0, 1.0),
         }
     }
 }
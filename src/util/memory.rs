@@ -5,5 +5,5 @@ pub fn anything_to_u8slice<T>(a: &T) -> &[u8] {
 }
 
 pub fn slice_to_u8slice<T>(a: &[T]) -> &[u8] {
-    unsafe { std::slice::from_raw_parts(a.as_ptr().cast::<u8>(), mem::size_of::<T>() * a.len()) }
+    unsafe { std::slice::from_raw_parts(a.as_ptr().cast::<u8>(), mem::size_of_val(a)) }
 }
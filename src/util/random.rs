@@ -0,0 +1,35 @@
+/// A pseudo random number generator with xorshift64*.
+///
+/// It's deterministic for the same seed, so it can be used to reproduce scenarios.
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    /// A constructor.
+    ///
+    /// NOTE: The seed is scrambled because xorshift doesn't work with zero state.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: (seed ^ 0x9E37_79B9_7F4A_7C15).max(1),
+        }
+    }
+
+    /// A method to get the next random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// A method to get the next random `f32` in [0, 1).
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// A method to get the next random `f32` in [`min`, `max`).
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
@@ -1,4 +1,4 @@
-pub fn get_upto<'a, T>(v: &'a Vec<T>, max_size: usize) -> &'a [T] {
+pub fn get_upto<T>(v: &Vec<T>, max_size: usize) -> &[T] {
     if v.len() <= max_size {
         v.as_slice()
     } else {
@@ -14,7 +14,7 @@ where
     let mut group = Vec::new();
     let mut cv = Vec::new();
     let mut ci = 0;
-    for (i, n) in v.into_iter().enumerate() {
+    for (i, n) in v.iter().enumerate() {
         if let Some(n) = n {
             if cv.is_empty() {
                 ci = i;